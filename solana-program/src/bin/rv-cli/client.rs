@@ -0,0 +1,111 @@
+// RPC connection, signer and instruction plumbing shared by the subcommands
+
+use crate::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use clap::Args;
+use remote_viewing_verifier::legacy::{decode_pool, decode_session};
+use remote_viewing_verifier::{config_address, RemoteViewingInstruction, Session, TargetPool};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct ClientConfig {
+    /// JSON RPC endpoint; defaults to a local solana-test-validator
+    #[clap(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Keypair file that signs and pays for transactions
+    #[clap(long, short = 'k', global = true, default_value = "~/.config/solana/id.json")]
+    keypair: PathBuf,
+    /// Address the program is deployed at
+    #[clap(long, global = true, default_value_t = remote_viewing_verifier::id())]
+    program_id: Pubkey,
+}
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+}
+
+impl Client {
+    pub fn new(config: &ClientConfig) -> Result<Self> {
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(config.url.clone(), CommitmentConfig::confirmed()),
+            payer: read_keypair(&config.keypair)?,
+            program_id: config.program_id,
+        })
+    }
+
+    /// Build a program instruction; the program uses borsh 0.10, so the data
+    /// is serialized here rather than through `Instruction::new_with_borsh`
+    pub fn instruction(&self, data: &RemoteViewingInstruction, accounts: Vec<AccountMeta>) -> Result<Instruction> {
+        Ok(Instruction::new_with_bytes(self.program_id, &data.try_to_vec()?, accounts))
+    }
+
+    /// The program config account, passed last to every instruction except
+    /// finalization and governance
+    pub fn config_account(&self) -> AccountMeta {
+        AccountMeta::new_readonly(config_address(&self.program_id), false)
+    }
+
+    /// Sign with the payer, send and wait for confirmation
+    pub fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        self.send_with_signers(instructions, &[])
+    }
+
+    /// Sign with the payer and `signers`, send and wait for confirmation
+    pub fn send_with_signers(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &all_signers, blockhash);
+        Ok(self.rpc.send_and_confirm_transaction(&transaction)?)
+    }
+
+    /// Fetch and decode a program account
+    pub fn account<T: BorshDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let data = self.account_data(address)?;
+        Ok(T::try_from_slice(&data).map_err(|err| format!("decoding {}: {}", address, err))?)
+    }
+
+    /// Fetch and decode a pool account, converting one in the legacy layout
+    pub fn pool(&self, address: &Pubkey) -> Result<TargetPool> {
+        let data = self.account_data(address)?;
+        Ok(decode_pool(&data).ok_or_else(|| format!("decoding {}: not a pool account", address))?)
+    }
+
+    /// Fetch and decode a session account, converting one in the legacy layout
+    pub fn session(&self, address: &Pubkey) -> Result<Session> {
+        let data = self.account_data(address)?;
+        Ok(decode_session(&data).ok_or_else(|| format!("decoding {}: not a session account", address))?)
+    }
+
+    /// Fetch the raw data of a program account
+    pub fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>> {
+        let account = self
+            .rpc
+            .get_account(address)
+            .map_err(|err| format!("fetching {}: {}", address, err))?;
+        if account.owner != self.program_id {
+            return Err(format!("{} is not owned by program {}", address, self.program_id).into());
+        }
+        Ok(account.data)
+    }
+}
+
+/// Read a keypair file, expanding a leading `~` to the home directory
+pub fn read_keypair(path: &Path) -> Result<Keypair> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => PathBuf::from(std::env::var("HOME")?).join(rest),
+        Err(_) => path.to_path_buf(),
+    };
+    Ok(read_keypair_file(&path).map_err(|err| format!("reading keypair {}: {}", path.display(), err))?)
+}
@@ -0,0 +1,184 @@
+// `rv-cli governance`: program config, freezing, pausing and timelocked upgrades
+
+use crate::client::Client;
+use crate::{hex, Result};
+use clap::Subcommand;
+use remote_viewing_verifier::{
+    config_address, program_data_address, ProgramConfig, RemoteViewingInstruction, DEFAULT_MAX_TARGETS_PER_POOL,
+    MIN_UPGRADE_DELAY,
+};
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    compute_budget::ComputeBudgetInstruction,
+    hash::hash,
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    sysvar::{clock, rent},
+};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum GovernanceCommand {
+    /// Create the program config; the keypair must be the program's upgrade authority
+    Init {
+        /// Authority that may freeze the program and upgrade it
+        #[clap(long)]
+        governance: Pubkey,
+        /// Seconds between announcing and executing an upgrade
+        #[clap(long, default_value_t = MIN_UPGRADE_DELAY)]
+        upgrade_delay: i64,
+        /// Authority that may pause submissions and change limits; defaults to the governance authority
+        #[clap(long)]
+        admin: Option<Pubkey>,
+        #[clap(long, default_value_t = DEFAULT_MAX_TARGETS_PER_POOL)]
+        max_targets_per_pool: u16,
+    },
+    /// Refuse everything except finalization
+    Freeze,
+    /// Accept all instructions again
+    Unfreeze,
+    /// Announce the program binary to upgrade to, starting the upgrade delay
+    Announce {
+        /// Program binary (`.so`) that will be written to the buffer
+        program: PathBuf,
+    },
+    /// Withdraw the pending upgrade announcement
+    Withdraw,
+    /// Upgrade from a buffer holding the announced binary once the delay has passed
+    Execute {
+        /// Buffer account; hand its authority to the config account first
+        buffer: Pubkey,
+        /// Receives the buffer's lamports; defaults to the signer
+        #[clap(long)]
+        spill: Option<Pubkey>,
+    },
+    /// Hand governance to another authority
+    Transfer { governance: Pubkey },
+    /// Stop pool creation and session submission (admin)
+    Pause,
+    /// Allow pool creation and session submission again (admin)
+    Resume,
+    /// Change limits for pools created or extended from now on (admin)
+    Limits {
+        #[clap(long)]
+        max_targets_per_pool: u16,
+    },
+    /// Replace the admin
+    SetAdmin { admin: Pubkey },
+}
+
+pub fn run(client: &Client, command: GovernanceCommand) -> Result<()> {
+    let config = config_address(&client.program_id);
+    let governance_accounts = || {
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(client.payer.pubkey(), true),
+            AccountMeta::new_readonly(clock::id(), false),
+        ]
+    };
+    // Admin instructions and SetGovernance/SetAdmin take no clock
+    let signer_accounts = || {
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(client.payer.pubkey(), true),
+        ]
+    };
+
+    match command {
+        GovernanceCommand::Init {
+            governance,
+            upgrade_delay,
+            admin,
+            max_targets_per_pool,
+        } => {
+            let init = RemoteViewingInstruction::InitializeConfig {
+                governance,
+                upgrade_delay,
+                admin: admin.unwrap_or(governance),
+                max_targets_per_pool,
+            };
+            let accounts = vec![
+                AccountMeta::new(config, false),
+                AccountMeta::new(client.payer.pubkey(), true),
+                AccountMeta::new_readonly(program_data_address(&client.program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ];
+            let signature = client.send(&[client.instruction(&init, accounts)?])?;
+            println!("Initialized config {} ({})", config, signature);
+            println!(
+                "Hand over the upgrade authority so upgrades go through governance:\n  \
+                 solana program set-upgrade-authority {} --new-upgrade-authority {} \
+                 --skip-new-upgrade-authority-signer-check",
+                client.program_id, config
+            );
+        }
+        GovernanceCommand::Freeze | GovernanceCommand::Unfreeze => {
+            let frozen = matches!(command, GovernanceCommand::Freeze);
+            let set = RemoteViewingInstruction::SetFrozen { frozen };
+            let signature = client.send(&[client.instruction(&set, governance_accounts())?])?;
+            println!("Program {} ({})", if frozen { "frozen" } else { "unfrozen" }, signature);
+        }
+        GovernanceCommand::Announce { program } => {
+            let binary = std::fs::read(&program).map_err(|err| format!("{}: {}", program.display(), err))?;
+            let program_hash = hash(&binary).to_bytes();
+            let announce = RemoteViewingInstruction::AnnounceUpgrade { program_hash };
+            let signature = client.send(&[client.instruction(&announce, governance_accounts())?])?;
+            let state: ProgramConfig = client.account(&config)?;
+            println!("Announced upgrade to {} ({})", hex(&program_hash), signature);
+            println!("Executable from unix time {}", state.upgrade_effective_at);
+        }
+        GovernanceCommand::Withdraw => {
+            let withdraw = RemoteViewingInstruction::AnnounceUpgrade { program_hash: [0; 32] };
+            let signature = client.send(&[client.instruction(&withdraw, governance_accounts())?])?;
+            println!("Withdrew pending upgrade ({})", signature);
+        }
+        GovernanceCommand::Execute { buffer, spill } => {
+            let accounts = vec![
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(client.payer.pubkey(), true),
+                AccountMeta::new(program_data_address(&client.program_id), false),
+                AccountMeta::new(client.program_id, false),
+                AccountMeta::new(buffer, false),
+                AccountMeta::new(spill.unwrap_or_else(|| client.payer.pubkey()), false),
+                AccountMeta::new_readonly(rent::id(), false),
+                AccountMeta::new_readonly(clock::id(), false),
+                AccountMeta::new_readonly(bpf_loader_upgradeable::id(), false),
+            ];
+            // Hashing the whole buffer needs far more than the default budget
+            let instructions = [
+                ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+                client.instruction(&RemoteViewingInstruction::ExecuteUpgrade, accounts)?,
+            ];
+            let signature = client.send(&instructions)?;
+            println!("Upgraded program {} ({})", client.program_id, signature);
+        }
+        GovernanceCommand::Transfer { governance } => {
+            let transfer = RemoteViewingInstruction::SetGovernance { governance };
+            let signature = client.send(&[client.instruction(&transfer, signer_accounts())?])?;
+            println!("Governance handed to {} ({})", governance, signature);
+        }
+        GovernanceCommand::Pause | GovernanceCommand::Resume => {
+            let paused = matches!(command, GovernanceCommand::Pause);
+            let set = RemoteViewingInstruction::SetPaused { paused };
+            let signature = client.send(&[client.instruction(&set, signer_accounts())?])?;
+            println!(
+                "Pool creation and session submission {} ({})",
+                if paused { "paused" } else { "resumed" },
+                signature
+            );
+        }
+        GovernanceCommand::Limits { max_targets_per_pool } => {
+            let set = RemoteViewingInstruction::SetLimits { max_targets_per_pool };
+            let signature = client.send(&[client.instruction(&set, signer_accounts())?])?;
+            println!("Max targets per pool set to {} ({})", max_targets_per_pool, signature);
+        }
+        GovernanceCommand::SetAdmin { admin } => {
+            let set = RemoteViewingInstruction::SetAdmin { admin };
+            let signature = client.send(&[client.instruction(&set, signer_accounts())?])?;
+            println!("Admin set to {} ({})", admin, signature);
+        }
+    }
+    Ok(())
+}
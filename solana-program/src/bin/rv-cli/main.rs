@@ -0,0 +1,144 @@
+// Command-line client for the remote viewing program
+
+mod client;
+mod governance;
+mod pool;
+mod reconcile;
+mod report;
+mod session;
+mod show;
+mod size;
+mod verify;
+
+use clap::{Parser, Subcommand};
+use client::{Client, ClientConfig};
+use remote_viewing_verifier::{HashAlgorithm, RecordId};
+pub use remote_viewing_verifier::hex;
+use std::error::Error;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[clap(name = "rv-cli", version, about = "Client for the remote viewing verifier program")]
+struct Cli {
+    #[clap(flatten)]
+    config: ClientConfig,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create, extend and finalize target pools
+    #[clap(subcommand)]
+    Pool(pool::PoolCommand),
+    /// Submit, re-anchor and finalize sessions
+    #[clap(subcommand)]
+    Session(session::SessionCommand),
+    /// Print decoded program accounts
+    #[clap(subcommand)]
+    Show(show::ShowCommand),
+    /// Re-check a finalized session's assignment and, optionally, its files
+    Verify(verify::VerifyArgs),
+    /// Compare a database export of sessions with the sessions on chain
+    Reconcile(reconcile::ReconcileArgs),
+    /// Write a self-contained JSON and HTML verification report for a session
+    Report(report::ReportArgs),
+    /// Estimate account sizes and rent before creating anything
+    #[clap(subcommand)]
+    Size(size::SizeCommand),
+    /// Manage the program config: freezing, pausing, limits and timelocked upgrades
+    #[clap(subcommand)]
+    Governance(governance::GovernanceCommand),
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Pool(command) => pool::run(&Client::new(&cli.config)?, command),
+        Command::Session(command) => session::run(&Client::new(&cli.config)?, command),
+        Command::Show(command) => show::run(&Client::new(&cli.config)?, command),
+        Command::Verify(args) => verify::run(&Client::new(&cli.config)?, args),
+        Command::Reconcile(args) => reconcile::run(&Client::new(&cli.config)?, args),
+        Command::Report(args) => report::run(&Client::new(&cli.config)?, args),
+        Command::Governance(command) => governance::run(&Client::new(&cli.config)?, command),
+        Command::Size(command) => {
+            size::run(command);
+            Ok(())
+        }
+    }
+}
+
+/// Parse a pool or session id: `uuid:<uuid>` and `hex:<64 hex digits>` give
+/// fixed ids, anything else is a text id
+pub fn parse_record_id(input: &str) -> std::result::Result<RecordId, String> {
+    let id = if let Some(uuid) = input.strip_prefix("uuid:") {
+        let bytes = decode_hex(&uuid.replace('-', ""))?;
+        RecordId::from_uuid(bytes.try_into().map_err(|_| "a UUID is 16 bytes".to_string())?)
+    } else if let Some(hex) = input.strip_prefix("hex:") {
+        RecordId::Fixed(decode_hex(hex)?.try_into().map_err(|_| "a hex id is 32 bytes".to_string())?)
+    } else {
+        RecordId::from(input)
+    };
+    if !id.is_valid() {
+        return Err("ids must be non-empty, non-zero and at most 64 bytes".to_string());
+    }
+    Ok(id)
+}
+
+/// Parse a hash algorithm that can be computed from file contents
+pub fn parse_hash_algorithm(input: &str) -> std::result::Result<HashAlgorithm, String> {
+    match input.to_ascii_lowercase().as_str() {
+        "sha256" => Ok(HashAlgorithm::Sha256),
+        "keccak256" => Ok(HashAlgorithm::Keccak256),
+        "blake3" => Ok(HashAlgorithm::Blake3),
+        _ => Err("expected sha256, keccak256 or blake3".to_string()),
+    }
+}
+
+// Works on bytes so non-ASCII input is an error rather than a panic on a
+// char boundary
+pub fn decode_hex(input: &str) -> std::result::Result<Vec<u8>, String> {
+    if input.len() % 2 != 0 {
+        return Err("odd number of hex digits".to_string());
+    }
+    let digit = |byte: u8| (byte as char).to_digit(16).ok_or_else(|| format!("invalid hex: {}", input));
+    input
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| Ok((digit(pair[0])? * 16 + digit(pair[1])?) as u8))
+        .collect()
+}
+
+/// Read several files in the order given
+pub fn read_files(paths: &[PathBuf]) -> Result<Vec<Vec<u8>>> {
+    paths
+        .iter()
+        .map(|path| std::fs::read(path).map_err(|err| format!("{}: {}", path.display(), err).into()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("00ff7A"), Ok(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode_hex(""), Ok(vec![]));
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("zz").is_err());
+        // Multi-byte characters must not split on a char boundary
+        assert!(decode_hex("é").is_err());
+        assert!(decode_hex("aéb").is_err());
+        assert!(parse_record_id("uuid:é").is_err());
+    }
+}
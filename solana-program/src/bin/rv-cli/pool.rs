@@ -0,0 +1,290 @@
+// `rv-cli pool`: create, extend and finalize target pools from image directories
+
+use crate::client::Client;
+use crate::{hex, parse_hash_algorithm, parse_record_id, Result};
+use borsh::BorshDeserialize;
+use clap::Subcommand;
+use remote_viewing_verifier::pool_builder::{
+    build_manifest, ManifestTarget, PoolManifest, MANIFEST_VERSION, TARGETS_PER_INSTRUCTION,
+};
+use remote_viewing_verifier::{
+    config_address, pool_address, pool_stats_address, HashAlgorithm, ProgramConfig, RecordId,
+    RemoteViewingInstruction, DEFAULT_MAX_TARGETS_PER_POOL, MAX_FINALIZATION_DELAY_SLOTS,
+    MIN_FINALIZATION_DELAY_SLOTS,
+};
+use serde_json::Value;
+use solana_sdk::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    system_program,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Subcommand)]
+pub enum PoolCommand {
+    /// Create a pool from every file under a directory
+    Create {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+        /// Directory of target images
+        dir: PathBuf,
+        /// sha256, keccak256 or blake3
+        #[clap(long, default_value = "sha256", parse(try_from_str = parse_hash_algorithm))]
+        hash_algorithm: HashAlgorithm,
+        /// Slots to wait after submission before a session can be finalized
+        #[clap(long, default_value_t = MIN_FINALIZATION_DELAY_SLOTS)]
+        min_delay: u64,
+        /// Slots after submission during which a session can be finalized
+        #[clap(long, default_value_t = MAX_FINALIZATION_DELAY_SLOTS)]
+        max_delay: u64,
+        /// Finalize the pool once every target is on-chain
+        #[clap(long)]
+        finalize: bool,
+        /// Also write the signed pool manifest to this file
+        #[clap(long)]
+        manifest: Option<PathBuf>,
+    },
+    /// Append every file under a directory to an unfinalized pool
+    Append {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+        dir: PathBuf,
+    },
+    /// Finalize a pool so no more targets can be added
+    Finalize {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+    },
+    /// Hash a directory into a signed pool manifest without sending anything
+    Manifest {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+        dir: PathBuf,
+        /// sha256, keccak256 or blake3
+        #[clap(long, default_value = "sha256", parse(try_from_str = parse_hash_algorithm))]
+        hash_algorithm: HashAlgorithm,
+        /// Write the manifest here instead of standard output
+        #[clap(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Check a signed manifest's signature against the on-chain pool and,
+    /// optionally, against the files themselves
+    CheckManifest {
+        manifest: PathBuf,
+        /// Rebuild the manifest from this directory and compare
+        #[clap(long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+pub fn run(client: &Client, command: PoolCommand) -> Result<()> {
+    match command {
+        PoolCommand::Create {
+            pool_id,
+            dir,
+            hash_algorithm,
+            min_delay,
+            max_delay,
+            finalize,
+            manifest: manifest_path,
+        } => {
+            let manifest = build_manifest(pool_id.clone(), &dir, hash_algorithm)?;
+            check_target_limit(client, manifest.targets.len())?;
+            print_targets(&manifest.targets, 0);
+            if let Some(path) = manifest_path {
+                write_signed_manifest(client, &manifest, Some(&path))?;
+            }
+
+            let accounts = pool_accounts(client, &pool_id);
+            for instruction in manifest.instructions(min_delay, max_delay) {
+                let signature = client.send(&[client.instruction(&instruction, accounts.clone())?])?;
+                match instruction {
+                    RemoteViewingInstruction::CreateTargetPool { target_hashes, .. } => println!(
+                        "Created pool {} at {} with {} targets ({})",
+                        pool_id,
+                        pool_address(&client.program_id, &pool_id),
+                        target_hashes.len(),
+                        signature
+                    ),
+                    RemoteViewingInstruction::AppendTargetsToPool { target_hashes, .. } => {
+                        println!("Appended {} targets ({})", target_hashes.len(), signature)
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            if finalize {
+                finalize_pool(client, &pool_id)?;
+            }
+        }
+        PoolCommand::Append { pool_id, dir } => {
+            let pool = client.pool(&pool_address(&client.program_id, &pool_id))?;
+            let manifest = build_manifest(pool_id.clone(), &dir, pool.hash_algorithm)?;
+            check_target_limit(client, pool.targets.len() + manifest.targets.len())?;
+            print_targets(&manifest.targets, pool.targets.len());
+
+            let accounts = pool_accounts(client, &pool_id);
+            for batch in manifest.target_hashes().chunks(TARGETS_PER_INSTRUCTION) {
+                let append = RemoteViewingInstruction::AppendTargetsToPool {
+                    pool_id: pool_id.clone(),
+                    target_hashes: batch.to_vec(),
+                };
+                let signature = client.send(&[client.instruction(&append, accounts.clone())?])?;
+                println!("Appended {} targets ({})", batch.len(), signature);
+            }
+        }
+        PoolCommand::Finalize { pool_id } => finalize_pool(client, &pool_id)?,
+        PoolCommand::Manifest {
+            pool_id,
+            dir,
+            hash_algorithm,
+            output,
+        } => {
+            let manifest = build_manifest(pool_id, &dir, hash_algorithm)?;
+            write_signed_manifest(client, &manifest, output.as_deref())?;
+        }
+        PoolCommand::CheckManifest { manifest, dir } => check_manifest(client, &manifest, dir.as_deref())?,
+    }
+    Ok(())
+}
+
+fn print_targets(targets: &[ManifestTarget], first_index: usize) {
+    for (offset, target) in targets.iter().enumerate() {
+        println!("{:>5}  {}  {}", first_index + offset, hex(&target.hash), target.file);
+    }
+}
+
+// Sign the canonical manifest with the payer keypair and write the published
+// form to `path`, or standard output
+fn write_signed_manifest(client: &Client, manifest: &PoolManifest, path: Option<&Path>) -> Result<()> {
+    let signature = client.payer.sign_message(manifest.canonical_json().as_bytes());
+    let signature: [u8; 64] = signature.as_ref().try_into()?;
+    let json = manifest.signed_json(&client.payer.pubkey(), &signature);
+    match path {
+        Some(path) => {
+            std::fs::write(path, json).map_err(|err| format!("{}: {}", path.display(), err))?;
+            eprintln!("Wrote signed manifest to {}", path.display());
+        }
+        None => print!("{}", json),
+    }
+    Ok(())
+}
+
+fn check_manifest(client: &Client, path: &Path, dir: Option<&Path>) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let (manifest, signer, signature) = parse_signed_manifest(&text)?;
+
+    if !signature.verify(signer.as_ref(), manifest.canonical_json().as_bytes()) {
+        return Err(format!("signature does not match signer {}", signer).into());
+    }
+    println!("Signature: ok (signed by {})", signer);
+
+    let pool_key = pool_address(&client.program_id, &manifest.pool_id);
+    let pool = client.pool(&pool_key)?;
+    manifest
+        .check_pool(&pool)
+        .map_err(|mismatch| format!("on-chain pool {}: {:?}", pool_key, mismatch))?;
+    println!("On-chain pool: ok ({} targets at {})", pool.targets.len(), pool_key);
+    if pool.creator != signer {
+        println!("Note: the manifest signer is not the pool creator ({})", pool.creator);
+    }
+    if !pool.finalized {
+        println!("Note: the pool is not finalized; targets can still be appended");
+    }
+
+    if let Some(dir) = dir {
+        let rebuilt = build_manifest(manifest.pool_id.clone(), dir, manifest.hash_algorithm)?;
+        if let Some(index) = (0..manifest.targets.len().max(rebuilt.targets.len()))
+            .find(|&i| manifest.targets.get(i) != rebuilt.targets.get(i))
+        {
+            return Err(format!("files in {} differ from the manifest at index {}", dir.display(), index).into());
+        }
+        println!("Files: ok ({})", dir.display());
+    }
+    Ok(())
+}
+
+fn parse_signed_manifest(text: &str) -> Result<(PoolManifest, Pubkey, Signature)> {
+    let document: Value = serde_json::from_str(text)?;
+    let field = |value: &Value, name: &str| -> Result<String> {
+        value[name]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("manifest field `{}` is missing", name).into())
+    };
+
+    let body = &document["manifest"];
+    if body["version"].as_u64() != Some(MANIFEST_VERSION) {
+        return Err("unsupported manifest version".into());
+    }
+    let targets = body["targets"]
+        .as_array()
+        .ok_or("manifest field `targets` is missing")?
+        .iter()
+        .enumerate()
+        .map(|(index, target)| {
+            if target["index"].as_u64() != Some(index as u64) {
+                return Err(format!("manifest target {} is out of order", index).into());
+            }
+            let hash = crate::decode_hex(&field(target, "hash")?)?;
+            Ok(ManifestTarget {
+                file: field(target, "file")?,
+                hash: hash.try_into().map_err(|_| "target hashes are 32 bytes")?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if body["target_count"].as_u64() != Some(targets.len() as u64) {
+        return Err("manifest `target_count` does not match its targets".into());
+    }
+
+    let manifest = PoolManifest {
+        pool_id: parse_record_id(&field(body, "pool_id")?)?,
+        hash_algorithm: parse_hash_algorithm(&field(body, "hash_algorithm")?)?,
+        targets,
+    };
+    let signer = Pubkey::from_str(&field(&document, "signer")?)?;
+    let signature = Signature::from_str(&field(&document, "signature")?)?;
+    Ok((manifest, signer, signature))
+}
+
+fn finalize_pool(client: &Client, pool_id: &RecordId) -> Result<()> {
+    let finalize = RemoteViewingInstruction::FinalizePool { pool_id: pool_id.clone() };
+    let accounts = vec![
+        AccountMeta::new(pool_address(&client.program_id, pool_id), false),
+        AccountMeta::new_readonly(client.payer.pubkey(), true),
+    ];
+    let signature = client.send(&[client.instruction(&finalize, accounts)?])?;
+    println!("Finalized pool {} ({})", pool_id, signature);
+    Ok(())
+}
+
+// Refuse up front rather than leave a partially built pool behind
+fn check_target_limit(client: &Client, target_count: usize) -> Result<()> {
+    let limit = match client.rpc.get_account(&config_address(&client.program_id)) {
+        Ok(account) if account.owner == client.program_id => {
+            ProgramConfig::try_from_slice(&account.data)?.max_targets_per_pool
+        }
+        _ => DEFAULT_MAX_TARGETS_PER_POOL,
+    };
+    if target_count > limit as usize {
+        return Err(format!("{} targets exceed the program's limit of {} per pool", target_count, limit).into());
+    }
+    Ok(())
+}
+
+// Accounts of CreateTargetPool and AppendTargetsToPool
+fn pool_accounts(client: &Client, pool_id: &RecordId) -> Vec<AccountMeta> {
+    let pool = pool_address(&client.program_id, pool_id);
+    vec![
+        AccountMeta::new(pool, false),
+        AccountMeta::new(client.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(pool_stats_address(&client.program_id, &pool), false),
+        client.config_account(),
+    ]
+}
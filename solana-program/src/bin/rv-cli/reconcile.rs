@@ -0,0 +1,176 @@
+// `rv-cli reconcile`: compare a database export with the sessions on chain
+
+use crate::client::Client;
+use crate::{decode_hex, parse_record_id, Result};
+use clap::{ArgEnum, Args};
+use remote_viewing_verifier::legacy::decode_session;
+use remote_viewing_verifier::reconcile::{reconcile, ExpectedSession, MismatchField};
+use remote_viewing_verifier::{session_address, RecordId, Session};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct ReconcileArgs {
+    /// Export with `session_id`, `pool_id`, `media_hash` and `expected_target`
+    /// columns (CSV with a header row) or fields (JSON array of objects)
+    export: PathBuf,
+    /// Export format; guessed from the file extension by default
+    #[clap(long, arg_enum)]
+    format: Option<ExportFormat>,
+    /// Only consider on-chain sessions in these pools when looking for orphans
+    #[clap(long = "pool", parse(try_from_str = parse_record_id))]
+    pools: Vec<RecordId>,
+    /// Read plain UUIDs in the export as fixed ids rather than text ids
+    #[clap(long)]
+    uuid_ids: bool,
+}
+
+#[derive(Clone, Copy, ArgEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+const COLUMNS: [&str; 4] = ["session_id", "pool_id", "media_hash", "expected_target"];
+
+pub fn run(client: &Client, args: ReconcileArgs) -> Result<()> {
+    let format = args.format.unwrap_or_else(|| guess_format(&args.export));
+    let records = match format {
+        ExportFormat::Csv => read_csv(&args.export)?,
+        ExportFormat::Json => read_json(&args.export)?,
+    };
+    let expected = records
+        .iter()
+        .enumerate()
+        .map(|(row, record)| parse_row(record, args.uuid_ids).map_err(|err| format!("export row {}: {}", row + 1, err)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let sessions: Vec<Session> = client
+        .rpc
+        .get_program_accounts(&client.program_id)?
+        .into_iter()
+        .filter_map(|(address, account)| {
+            // Legacy accounts carry no type tag; only keep decodes whose id
+            // derives to the account's address
+            let session = decode_session(&account.data)?;
+            (session_address(&client.program_id, &session.session_id) == address).then_some(session)
+        })
+        .filter(|session| args.pools.is_empty() || args.pools.contains(&session.pool_id))
+        .collect();
+
+    let report = reconcile(&expected, &sessions);
+    println!(
+        "{} exported row(s), {} on-chain session(s): {} matched",
+        expected.len(),
+        sessions.len(),
+        report.matched
+    );
+    print_ids("Missing on chain", &report.missing);
+    if !report.mismatched.is_empty() {
+        println!("Mismatched ({}):", report.mismatched.len());
+        for mismatch in &report.mismatched {
+            let field = match mismatch.field {
+                MismatchField::PoolId => "pool id",
+                MismatchField::MediaHash => "media hash",
+                MismatchField::AssignedTarget => "target",
+            };
+            println!(
+                "  {}: {} is {} in the export, {} on chain",
+                mismatch.session_id, field, mismatch.expected, mismatch.on_chain
+            );
+        }
+    }
+    print_ids("Not finalized", &report.unfinalized);
+    print_ids("Orphaned (on chain only)", &report.orphans);
+    print_ids("Duplicated in the export", &report.duplicates);
+
+    if !report.is_clean() {
+        return Err("the export and the chain disagree".into());
+    }
+    Ok(())
+}
+
+fn print_ids(heading: &str, ids: &[RecordId]) {
+    if !ids.is_empty() {
+        println!("{} ({}):", heading, ids.len());
+        for id in ids {
+            println!("  {}", id);
+        }
+    }
+}
+
+fn guess_format(path: &Path) -> ExportFormat {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json") => ExportFormat::Json,
+        _ => ExportFormat::Csv,
+    }
+}
+
+/// Rows as the four known columns, blank cells as `None`
+type Record = [Option<String>; 4];
+
+fn read_csv(path: &Path) -> Result<Vec<Record>> {
+    let mut reader = csv::Reader::from_path(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let headers = reader.headers()?.clone();
+    let positions = COLUMNS.map(|column| headers.iter().position(|header| header.trim() == column));
+    if positions[0].is_none() {
+        return Err("the export has no `session_id` column".into());
+    }
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row?;
+        records.push(positions.map(|position| {
+            position
+                .and_then(|position| row.get(position))
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .map(str::to_string)
+        }));
+    }
+    Ok(records)
+}
+
+fn read_json(path: &Path) -> Result<Vec<Record>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let document: Value = serde_json::from_str(&text)?;
+    let rows = document.as_array().ok_or("the export should be a JSON array of objects")?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            COLUMNS.map(|column| match &row[column] {
+                Value::String(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+                Value::Number(number) => Some(number.to_string()),
+                _ => None,
+            })
+        })
+        .collect())
+}
+
+fn parse_row(record: &Record, uuid_ids: bool) -> Result<ExpectedSession> {
+    let id = |cell: &str| {
+        let is_uuid = cell.len() == 36 && cell.chars().all(|c| c == '-' || c.is_ascii_hexdigit());
+        if uuid_ids && is_uuid {
+            parse_record_id(&format!("uuid:{}", cell))
+        } else {
+            parse_record_id(cell)
+        }
+    };
+    let [session_id, pool_id, media_hash, expected_target] = record;
+
+    Ok(ExpectedSession {
+        session_id: id(session_id.as_deref().ok_or("`session_id` is blank")?)?,
+        pool_id: pool_id.as_deref().map(id).transpose()?,
+        media_hash: media_hash
+            .as_deref()
+            .map(|hash| -> Result<[u8; 32]> {
+                let bytes = decode_hex(hash.strip_prefix("0x").unwrap_or(hash))?;
+                Ok(bytes.try_into().map_err(|_| "media hashes are 32 bytes")?)
+            })
+            .transpose()?,
+        expected_target: expected_target
+            .as_deref()
+            .map(|target| target.parse().map_err(|_| format!("invalid target index: {}", target)))
+            .transpose()?,
+    })
+}
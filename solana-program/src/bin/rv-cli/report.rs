@@ -0,0 +1,77 @@
+// `rv-cli report`: write a shareable JSON + HTML proof bundle for a session
+
+use crate::client::Client;
+use crate::{parse_record_id, Result};
+use clap::Args;
+use remote_viewing_verifier::report::{build_report, TransactionEvidence};
+use remote_viewing_verifier::{find_slot_hash, pool_address, session_address, RecordId, SessionKind};
+use solana_sdk::sysvar;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Session id (`uuid:...` or `hex:...` for fixed ids)
+    #[clap(parse(try_from_str = parse_record_id))]
+    session_id: RecordId,
+    /// Directory to write `<session address>.json` and `.html` into
+    #[clap(long, short = 'o', default_value = ".")]
+    output_dir: PathBuf,
+}
+
+pub fn run(client: &Client, args: ReportArgs) -> Result<()> {
+    let session_key = session_address(&client.program_id, &args.session_id);
+    // Fetched as raw bytes so the report publishes exactly what is on chain
+    let session_data = client.account_data(&session_key)?;
+    let session = client.session(&session_key)?;
+    let pool_data = client.account_data(&pool_address(&client.program_id, &session.pool_id))?;
+
+    // Oldest first; a session only ever sees a handful of transactions
+    let transactions = client
+        .rpc
+        .get_signatures_for_address(&session_key)?
+        .into_iter()
+        .rev()
+        .map(|status| TransactionEvidence {
+            signature: status.signature,
+            slot: status.slot,
+            block_time: status.block_time,
+            success: status.err.is_none(),
+        })
+        .collect();
+
+    // SlotHashes only retains recent slots, so this is only available for a
+    // while after finalization. Legacy sessions were drawn from a blockhash
+    // the finalizer supplied, not from a slot's entry.
+    let from_slot_hash = session.finalized && session.kind == SessionKind::Standard && session.layout_version > 0;
+    let observed_slot_hash = if from_slot_hash {
+        let slot_hashes = client.rpc.get_account(&sysvar::slot_hashes::id())?;
+        find_slot_hash(&slot_hashes.data, session.entropy_slot)
+    } else {
+        None
+    };
+
+    let report = build_report(
+        &client.program_id,
+        &session_data,
+        &pool_data,
+        transactions,
+        observed_slot_hash,
+    )
+    .map_err(|err| format!("building report: {:?}", err))?;
+
+    std::fs::create_dir_all(&args.output_dir)?;
+    for (extension, contents) in [("json", report.to_json()), ("html", report.to_html())] {
+        let path = args.output_dir.join(format!("{}.{}", session_key, extension));
+        std::fs::write(&path, contents).map_err(|err| format!("{}: {}", path.display(), err))?;
+        println!("Wrote {}", path.display());
+    }
+
+    match &report.result {
+        Ok(()) => println!("Assignment verified (target {:?})", report.target_index),
+        Err(err) => println!("Verification failed: {:?}", err),
+    }
+    if observed_slot_hash.is_none() && from_slot_hash {
+        println!("Slot {} has left SlotHashes; the report carries the recorded hash only", session.entropy_slot);
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+// `rv-cli session`: submit, re-anchor and finalize sessions
+
+use crate::client::{read_keypair, Client};
+use crate::{hex, parse_hash_algorithm, parse_record_id, read_files, Result};
+use clap::Subcommand;
+use remote_viewing_verifier::manifest::MediaManifest;
+use remote_viewing_verifier::{
+    experiment_address, pool_address, pool_stats_address, session_address, viewer_stats_address, HashAlgorithm,
+    RecordId, RemoteViewingInstruction, Session, SessionKind,
+};
+use solana_sdk::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    sysvar::{clock, slot_hashes},
+};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Subcommand)]
+pub enum SessionCommand {
+    /// Submit a session committing to one or more media files, in order
+    Submit {
+        /// Session id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        session_id: RecordId,
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+        /// Media files; several files are committed as a manifest root
+        #[clap(required = true)]
+        media: Vec<PathBuf>,
+        /// sha256, keccak256 or blake3
+        #[clap(long, default_value = "sha256", parse(try_from_str = parse_hash_algorithm))]
+        hash_algorithm: HashAlgorithm,
+        /// Target indices the viewer has already completed, comma separated
+        #[clap(long, use_value_delimiter = true)]
+        completed: Vec<u16>,
+        /// Lamports escrowed for whoever finalizes the session
+        #[clap(long, default_value_t = 0)]
+        reward: u64,
+        /// Keypair of the viewer credited with the session, who co-signs the
+        /// submission; defaults to the signer
+        #[clap(long)]
+        viewer_keypair: Option<PathBuf>,
+        /// Pre-registered experiment the session counts towards
+        #[clap(long)]
+        experiment: Option<String>,
+        /// Decoys drawn alongside the target at finalization for blind judging
+        #[clap(long, default_value_t = 0)]
+        decoys: u8,
+    },
+    /// Finalize a session once its delay has passed, waiting if needed
+    Finalize {
+        /// Session id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        session_id: RecordId,
+    },
+    /// Re-anchor a session whose finalization window has passed
+    Reanchor {
+        /// Session id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        session_id: RecordId,
+    },
+}
+
+pub fn run(client: &Client, command: SessionCommand) -> Result<()> {
+    match command {
+        SessionCommand::Submit {
+            session_id,
+            pool_id,
+            media,
+            hash_algorithm,
+            completed,
+            reward,
+            viewer_keypair,
+            experiment,
+            decoys,
+        } => {
+            let files = read_files(&media)?;
+            let file_refs: Vec<&[u8]> = files.iter().map(Vec::as_slice).collect();
+            let manifest = MediaManifest::from_media(hash_algorithm, &file_refs)
+                .ok_or("media hash cannot be computed locally")?;
+
+            let viewer_keypair = viewer_keypair.as_deref().map(read_keypair).transpose()?;
+            let viewer = viewer_keypair.as_ref().map_or(client.payer.pubkey(), |keypair| keypair.pubkey());
+            let experiment = experiment.map(|id| experiment_address(&client.program_id, &id));
+            let session = session_address(&client.program_id, &session_id);
+
+            let mut accounts = vec![
+                AccountMeta::new(session, false),
+                AccountMeta::new_readonly(pool_address(&client.program_id, &pool_id), false),
+                AccountMeta::new(client.payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(clock::id(), false),
+                AccountMeta::new_readonly(viewer, true),
+                AccountMeta::new(viewer_stats_address(&client.program_id, &viewer), false),
+            ];
+            if let Some(experiment) = experiment {
+                accounts.push(AccountMeta::new(experiment, false));
+            }
+            accounts.push(client.config_account());
+
+            let submit = RemoteViewingInstruction::SubmitSession {
+                session_id: session_id.clone(),
+                pool_id,
+                session_media_hash: manifest.root(),
+                target_selector_program: Pubkey::default(),
+                completed_target_indices: completed,
+                media_hash_algorithm: hash_algorithm,
+                media_item_count: manifest.item_count(),
+                finalization_reward: reward,
+                decoy_count: decoys,
+                tasking_commitment: [0; 32],
+                experiment: experiment.unwrap_or_default(),
+            };
+            let signers: Vec<_> = viewer_keypair.iter().collect();
+            let signature = client.send_with_signers(&[client.instruction(&submit, accounts)?], &signers)?;
+            println!("Submitted session {} at {} ({})", session_id, session, signature);
+            println!("Media hash {} over {} item(s)", hex(&manifest.root()), manifest.item_count());
+        }
+        SessionCommand::Finalize { session_id } => finalize(client, &session_id)?,
+        SessionCommand::Reanchor { session_id } => {
+            let reanchor = RemoteViewingInstruction::ReanchorSession {
+                session_id: session_id.clone(),
+            };
+            let accounts = vec![
+                AccountMeta::new(session_address(&client.program_id, &session_id), false),
+                AccountMeta::new_readonly(client.payer.pubkey(), true),
+                AccountMeta::new_readonly(clock::id(), false),
+                client.config_account(),
+            ];
+            let signature = client.send(&[client.instruction(&reanchor, accounts)?])?;
+            println!("Re-anchored session {} ({})", session_id, signature);
+        }
+    }
+    Ok(())
+}
+
+// Picks the finalization instruction matching the session's state. Standard
+// sessions always take their entropy from the SlotHashes sysvar, never from a
+// caller-supplied blockhash.
+fn finalize(client: &Client, session_id: &RecordId) -> Result<()> {
+    let session_key = session_address(&client.program_id, session_id);
+    let session = client.session(&session_key)?;
+    if session.finalized {
+        println!("Session {} is already finalized (target index {})", session_id, session.assigned_target_index);
+        return Ok(());
+    }
+
+    let pool = pool_address(&client.program_id, &session.pool_id);
+    let viewer_stats = viewer_stats_address(&client.program_id, &session.viewer);
+    let pool_stats = pool_stats_address(&client.program_id, &pool);
+
+    let (finalize, accounts) = if session.kind == SessionKind::AssociativePrediction {
+        let finalize = RemoteViewingInstruction::FinalizeAssociativeSession {
+            session_id: session_id.clone(),
+        };
+        let accounts = vec![
+            AccountMeta::new(session_key, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(client.payer.pubkey(), true),
+            AccountMeta::new_readonly(clock::id(), false),
+            AccountMeta::new(viewer_stats, false),
+            AccountMeta::new(pool_stats, false),
+        ];
+        (finalize, accounts)
+    } else if session.reanchor_count > 0 {
+        wait_for_window(client, session.reanchor_slot, &session)?;
+        let finalize = RemoteViewingInstruction::FinalizeLateSession {
+            session_id: session_id.clone(),
+        };
+        let accounts = vec![
+            AccountMeta::new(session_key, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(client.payer.pubkey(), true),
+            AccountMeta::new_readonly(clock::id(), false),
+            AccountMeta::new_readonly(slot_hashes::id(), false),
+            AccountMeta::new(viewer_stats, false),
+            AccountMeta::new(pool_stats, false),
+        ];
+        (finalize, accounts)
+    } else {
+        wait_for_window(client, session.submission_slot, &session)?;
+        let finalize = RemoteViewingInstruction::FinalizeSessionsBatch {
+            submission_slot: session.submission_slot,
+        };
+        let accounts = vec![
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(client.payer.pubkey(), true),
+            AccountMeta::new_readonly(clock::id(), false),
+            AccountMeta::new_readonly(slot_hashes::id(), false),
+            AccountMeta::new(pool_stats, false),
+            AccountMeta::new(session_key, false),
+            AccountMeta::new(viewer_stats, false),
+        ];
+        (finalize, accounts)
+    };
+
+    let signature = client.send(&[client.instruction(&finalize, accounts)?])?;
+    let session = client.session(&session_key)?;
+    println!(
+        "Finalized session {} with target index {} ({})",
+        session_id, session.assigned_target_index, signature
+    );
+    Ok(())
+}
+
+// Block until `anchor_slot + min_finalization_delay` has a hash; fail if the
+// window has already closed
+fn wait_for_window(client: &Client, anchor_slot: u64, session: &Session) -> Result<()> {
+    loop {
+        let slot = client.rpc.get_slot()?;
+        if slot > anchor_slot + session.max_finalization_delay {
+            return Err(format!(
+                "finalization window closed at slot {}; run `rv-cli session reanchor` first",
+                anchor_slot + session.max_finalization_delay
+            )
+            .into());
+        }
+        if slot > anchor_slot + session.min_finalization_delay {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(400));
+    }
+}
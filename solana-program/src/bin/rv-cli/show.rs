@@ -0,0 +1,194 @@
+// `rv-cli show`: fetch and print decoded program accounts
+
+use crate::client::Client;
+use crate::{hex, parse_record_id, Result};
+use clap::Subcommand;
+use remote_viewing_verifier::stats::{assignment_uniformity, sum_of_ranks};
+use remote_viewing_verifier::{
+    config_address, experiment_address, pool_address, pool_stats_address, session_address, tasking_code,
+    viewer_stats_address, Experiment, PoolStats, ProgramConfig, RecordId, Session, TargetPool, ViewerStats,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[derive(Subcommand)]
+pub enum ShowCommand {
+    /// Pool settings and target hashes
+    Pool {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+    },
+    /// Session commitment and assignment
+    Session {
+        /// Session id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        session_id: RecordId,
+    },
+    /// Pool counters with their significance tests
+    PoolStats {
+        /// Pool id (`uuid:...` or `hex:...` for fixed ids)
+        #[clap(parse(try_from_str = parse_record_id))]
+        pool_id: RecordId,
+    },
+    /// A viewer's track record; defaults to the signer
+    ViewerStats { viewer: Option<Pubkey> },
+    /// Pre-registered experiment
+    Experiment { experiment_id: String },
+    /// Governance and admin settings, freeze and pause state, any pending upgrade
+    Config,
+}
+
+pub fn run(client: &Client, command: ShowCommand) -> Result<()> {
+    match command {
+        ShowCommand::Pool { pool_id } => {
+            let address = pool_address(&client.program_id, &pool_id);
+            print_pool(&address, &client.pool(&address)?);
+        }
+        ShowCommand::Session { session_id } => {
+            let address = session_address(&client.program_id, &session_id);
+            print_session(&address, &client.session(&address)?);
+        }
+        ShowCommand::PoolStats { pool_id } => {
+            let pool = pool_address(&client.program_id, &pool_id);
+            let address = pool_stats_address(&client.program_id, &pool);
+            let stats: PoolStats = client.account(&address)?;
+            println!("Address: {}", address);
+            println!("{:#?}", stats);
+            if let Some(result) = sum_of_ranks(&stats) {
+                println!("Sum of ranks: {:?}", result);
+            }
+            if let Some(result) = assignment_uniformity(&stats) {
+                println!("Assignment uniformity: {:?}", result);
+            }
+        }
+        ShowCommand::ViewerStats { viewer } => {
+            let viewer = viewer.unwrap_or_else(|| client.payer.pubkey());
+            let address = viewer_stats_address(&client.program_id, &viewer);
+            let stats: ViewerStats = client.account(&address)?;
+            println!("Address: {}", address);
+            println!("{:#?}", stats);
+        }
+        ShowCommand::Experiment { experiment_id } => {
+            let address = experiment_address(&client.program_id, &experiment_id);
+            print_experiment(&address, &client.account(&address)?);
+        }
+        ShowCommand::Config => {
+            let address = config_address(&client.program_id);
+            print_config(&address, &client.account(&address)?);
+        }
+    }
+    Ok(())
+}
+
+pub fn print_pool(address: &Pubkey, pool: &TargetPool) {
+    println!("Address:            {}", address);
+    println!("Pool id:            {}", pool.pool_id);
+    println!("Creator:            {}", pool.creator);
+    println!("Kind:               {:?}", pool.kind);
+    println!("Hash algorithm:     {:?}", pool.hash_algorithm);
+    println!("Finalized:          {}", pool.finalized);
+    println!("Created at:         {}", pool.created_at);
+    println!(
+        "Finalization delay: {}..={} slots",
+        pool.min_finalization_delay, pool.max_finalization_delay
+    );
+    if !pool.outcome_labels.is_empty() {
+        println!("Resolver:           {}", pool.resolver);
+        println!("Event time:         {}", pool.event_time);
+        println!("Submissions close:  {}", pool.submission_deadline);
+        match pool.outcome_labels.get(pool.resolved_outcome as usize) {
+            Some(label) => println!("Resolved outcome:   {} at slot {}", label, pool.resolved_slot),
+            None => println!("Resolved outcome:   pending"),
+        }
+    }
+    println!("Targets:            {}", pool.target_count);
+    for (index, target) in pool.targets.iter().enumerate() {
+        let label = pool.outcome_labels.get(index).map(String::as_str).unwrap_or("");
+        println!("{:>5}  {}  {}", index, hex(target), label);
+    }
+}
+
+pub fn print_session(address: &Pubkey, session: &Session) {
+    println!("Address:            {}", address);
+    println!("Session id:         {}", session.session_id);
+    println!("Pool id:            {}", session.pool_id);
+    println!("Kind:               {:?}", session.kind);
+    println!("Submitter:          {}", session.submitter);
+    println!("Viewer:             {}", session.viewer);
+    println!("Submitted at:       {} (slot {})", session.submitted_at, session.submission_slot);
+    println!(
+        "Media hash:         {} ({:?}, {} item(s))",
+        hex(&session.session_media_hash),
+        session.media_hash_algorithm,
+        session.media_item_count
+    );
+    println!("Media amendments:   {}", session.media_history.len());
+    println!("Completed targets:  {:?}", session.completed_target_indices);
+    println!("Reward:             {} lamports", session.finalization_reward);
+    if session.experiment != Pubkey::default() {
+        println!("Experiment:         {}", session.experiment);
+    }
+    if session.tasking_commitment != [0; 32] {
+        println!(
+            "Tasking code:       {}{}",
+            tasking_code(&session.tasking_reference),
+            if session.tasking_revealed { " (revealed)" } else { "" }
+        );
+    }
+    if session.reanchor_count > 0 {
+        println!(
+            "Re-anchored:        {} time(s), last at slot {}",
+            session.reanchor_count, session.reanchor_slot
+        );
+    }
+    if session.finalized {
+        println!("Finalized at:       {}{}", session.finalized_at, if session.late_finalized { " (late)" } else { "" });
+        println!("Entropy:            {} (slot {})", hex(&session.submission_blockhash), session.entropy_slot);
+        println!("Assigned target:    {}", session.assigned_target_index);
+        if session.decoy_count > 0 {
+            println!("Judging candidates: {:?}", session.candidates);
+        }
+    } else {
+        println!("Finalized:          false");
+    }
+}
+
+fn print_experiment(address: &Pubkey, experiment: &Experiment) {
+    println!("Address:            {}", address);
+    println!("Experiment id:      {}", experiment.experiment_id);
+    println!("Creator:            {}", experiment.creator);
+    println!("Protocol hash:      {}", hex(&experiment.protocol_hash));
+    println!("Analysis hash:      {}", hex(&experiment.analysis_method_hash));
+    println!("Hypothesis hash:    {}", hex(&experiment.hypothesis_hash));
+    println!("Window:             {}..{}", experiment.start_time, experiment.end_time);
+    println!(
+        "Sessions:           {} of {} planned",
+        experiment.session_count, experiment.planned_sample_size
+    );
+    println!("Created at:         {}", experiment.created_at);
+    println!("Plan updated at:    {}", experiment.plan_updated_at);
+    println!("Allowed pools:");
+    for pool in &experiment.allowed_pools {
+        println!("  {}", pool);
+    }
+}
+
+fn print_config(address: &Pubkey, config: &ProgramConfig) {
+    println!("Address:            {}", address);
+    println!("Governance:         {}", config.governance);
+    println!("Upgrade delay:      {}s", config.upgrade_delay);
+    println!("Frozen:             {} (since {})", config.frozen, config.frozen_changed_at);
+    println!("Admin:              {}", config.admin);
+    println!("Paused:             {}", config.paused);
+    println!("Max targets/pool:   {}", config.max_targets_per_pool);
+    if config.pending_upgrade_hash == [0; 32] {
+        println!("Pending upgrade:    none");
+    } else {
+        println!("Pending upgrade:    {}", hex(&config.pending_upgrade_hash));
+        println!("Announced at:       {}", config.upgrade_announced_at);
+        println!("Effective at:       {}", config.upgrade_effective_at);
+    }
+    if config.last_upgraded_at != 0 {
+        println!("Last upgrade:       {} at {}", hex(&config.last_upgrade_hash), config.last_upgraded_at);
+    }
+}
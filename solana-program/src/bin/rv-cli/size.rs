@@ -0,0 +1,94 @@
+// `rv-cli size`: account sizes and rent, computed offline
+
+use clap::{Args, Subcommand};
+use remote_viewing_verifier::size::{
+    finalized_session_account_size, format_sol, pool_account_size, pool_stats_account_size, rent_exempt_lamports,
+    viewer_stats_account_size,
+};
+use remote_viewing_verifier::RecordId;
+
+#[derive(Subcommand)]
+pub enum SizeCommand {
+    /// Standard pool and its stats account
+    Pool {
+        /// Number of targets once all batches are appended
+        #[clap(long)]
+        targets: usize,
+        #[clap(flatten)]
+        ids: IdArgs,
+    },
+    /// One session, with room for its judging candidates but before any amendments
+    Session {
+        /// Number of previously completed target indices passed at submission
+        #[clap(long, default_value_t = 0)]
+        completed: usize,
+        /// Decoys drawn alongside the target at finalization; their rent is
+        /// escrowed at submission
+        #[clap(long, default_value_t = 0)]
+        decoys: u8,
+        /// Finalization reward escrowed in the session, in lamports
+        #[clap(long, default_value_t = 0)]
+        reward: u64,
+        #[clap(flatten)]
+        ids: IdArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct IdArgs {
+    /// Length in bytes of text pool and session ids
+    #[clap(long, default_value_t = 36)]
+    id_len: usize,
+    /// Use fixed 32-byte ids instead of text ids
+    #[clap(long)]
+    fixed_id: bool,
+}
+
+impl IdArgs {
+    // Representative id of the requested shape; only its size matters
+    fn sample(&self) -> RecordId {
+        if self.fixed_id {
+            RecordId::Fixed([1; 32])
+        } else {
+            RecordId::Text("x".repeat(self.id_len))
+        }
+    }
+}
+
+pub fn run(command: SizeCommand) {
+    match command {
+        SizeCommand::Pool { targets, ids } => {
+            let pool_id = ids.sample();
+            let rows = [
+                ("Pool account", pool_account_size(targets, &pool_id)),
+                ("Pool stats account", pool_stats_account_size(targets)),
+            ];
+            print_costs(&rows, 0);
+        }
+        SizeCommand::Session { completed, decoys, reward, ids } => {
+            let id = ids.sample();
+            let rows = [
+                ("Session account", finalized_session_account_size(&id, &id, completed, decoys)),
+                ("Viewer stats (first pool)", viewer_stats_account_size(1)),
+            ];
+            print_costs(&rows, reward);
+            println!(
+                "Viewer stats are paid once per viewer; each further pool adds {} bytes.",
+                viewer_stats_account_size(2) - viewer_stats_account_size(1)
+            );
+        }
+    }
+}
+
+fn print_costs(rows: &[(&str, usize)], escrow: u64) {
+    let mut total = escrow;
+    for (name, space) in rows {
+        let lamports = rent_exempt_lamports(*space);
+        total += lamports;
+        println!("{:<28}{:>10} bytes  {}", name, space, format_sol(lamports));
+    }
+    if escrow > 0 {
+        println!("{:<28}{:>16}  {}", "Finalization reward", "", format_sol(escrow));
+    }
+    println!("{:<28}{:>16}  {}", "Total", "", format_sol(total));
+}
@@ -0,0 +1,70 @@
+// `rv-cli verify`: re-check a finalized session against its pool and files
+
+use crate::client::Client;
+use crate::{hex, parse_record_id, read_files, Result};
+use clap::Args;
+use remote_viewing_verifier::manifest::MediaManifest;
+use remote_viewing_verifier::verify::{
+    verify_candidates, verify_session_media, verify_target_assignment, verify_target_image, verify_tasking,
+};
+use remote_viewing_verifier::{pool_address, session_address, RecordId};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Session id (`uuid:...` or `hex:...` for fixed ids)
+    #[clap(parse(try_from_str = parse_record_id))]
+    session_id: RecordId,
+    /// Session media files, in the order they were committed
+    #[clap(long)]
+    media: Vec<PathBuf>,
+    /// Image believed to be the assigned target
+    #[clap(long)]
+    target: Option<PathBuf>,
+}
+
+pub fn run(client: &Client, args: VerifyArgs) -> Result<()> {
+    let session_key = session_address(&client.program_id, &args.session_id);
+    let session = client.session(&session_key)?;
+    let pool = client.pool(&pool_address(&client.program_id, &session.pool_id))?;
+
+    let target_hash = verify_target_assignment(&session, &pool).map_err(|err| format!("target assignment: {:?}", err))?;
+    println!(
+        "Target assignment: ok (index {}, hash {})",
+        session.assigned_target_index,
+        hex(&target_hash)
+    );
+
+    if session.decoy_count > 0 {
+        verify_candidates(&session, &pool).map_err(|err| format!("judging candidates: {:?}", err))?;
+        println!("Judging candidates: ok {:?}", session.candidates);
+    }
+
+    if session.tasking_revealed {
+        verify_tasking(&session, &session_key).map_err(|err| format!("tasking: {:?}", err))?;
+        println!("Tasking reveal: ok");
+    }
+
+    if !args.media.is_empty() {
+        let files = read_files(&args.media)?;
+        if files.len() == 1 && session.media_item_count <= 1 {
+            verify_session_media(&session, &files[0]).map_err(|err| format!("media: {:?}", err))?;
+        } else {
+            let file_refs: Vec<&[u8]> = files.iter().map(Vec::as_slice).collect();
+            let manifest = MediaManifest::from_media(session.media_hash_algorithm, &file_refs)
+                .ok_or("media hash cannot be computed locally")?;
+            if manifest.item_count() != session.media_item_count || manifest.root() != session.session_media_hash {
+                return Err("media: manifest root does not match the session".into());
+            }
+        }
+        println!("Media: ok ({} file(s))", files.len());
+    }
+
+    if let Some(target) = args.target {
+        let image = std::fs::read(&target).map_err(|err| format!("{}: {}", target.display(), err))?;
+        verify_target_image(&pool, session.assigned_target_index, &image)
+            .map_err(|err| format!("target image: {:?}", err))?;
+        println!("Target image: ok ({})", target.display());
+    }
+    Ok(())
+}
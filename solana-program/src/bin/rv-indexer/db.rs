@@ -0,0 +1,464 @@
+// SQLite schema and writes
+//
+// Pools, targets, sessions and finalizations mirror the latest account state;
+// transactions and instructions are the program's history, in slot order.
+// Keys are base58 addresses, hashes are lowercase hex and ids are written as
+// in pool manifests (`pool_id_string`).
+
+use crate::decode::{InstructionRecord, FINALIZATION_INSTRUCTIONS};
+use remote_viewing_verifier::pool_builder::pool_id_string;
+use remote_viewing_verifier::{hex, pool_address, Session, TargetPool};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS pools (
+    address TEXT PRIMARY KEY,
+    pool_id TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    hash_algorithm TEXT NOT NULL,
+    target_count INTEGER NOT NULL,
+    finalized INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    min_finalization_delay INTEGER NOT NULL,
+    max_finalization_delay INTEGER NOT NULL,
+    resolver TEXT,
+    resolved_outcome INTEGER,
+    resolved_slot INTEGER,
+    event_time INTEGER,
+    submission_deadline INTEGER,
+    indexed_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS targets (
+    pool TEXT NOT NULL REFERENCES pools(address),
+    target_index INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    outcome_label TEXT,
+    PRIMARY KEY (pool, target_index)
+);
+CREATE TABLE IF NOT EXISTS sessions (
+    address TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    pool_id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    submitter TEXT NOT NULL,
+    viewer TEXT NOT NULL,
+    media_hash TEXT NOT NULL,
+    media_hash_algorithm TEXT NOT NULL,
+    media_item_count INTEGER NOT NULL,
+    media_amendments INTEGER NOT NULL,
+    completed_target_indices TEXT NOT NULL,
+    submission_slot INTEGER NOT NULL,
+    submitted_at INTEGER NOT NULL,
+    finalization_reward INTEGER NOT NULL,
+    experiment TEXT,
+    finalized INTEGER NOT NULL,
+    indexed_slot INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_by_pool ON sessions (pool);
+CREATE TABLE IF NOT EXISTS finalizations (
+    session TEXT PRIMARY KEY REFERENCES sessions(address),
+    signature TEXT,
+    assigned_target_index INTEGER NOT NULL,
+    target_hash TEXT,
+    entropy_slot INTEGER NOT NULL,
+    entropy_hash TEXT NOT NULL,
+    late INTEGER NOT NULL,
+    reanchor_count INTEGER NOT NULL,
+    finalized_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    success INTEGER NOT NULL,
+    logs TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS instructions (
+    signature TEXT NOT NULL REFERENCES transactions(signature),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    pool TEXT,
+    PRIMARY KEY (signature, position)
+);
+CREATE TABLE IF NOT EXISTS instruction_sessions (
+    signature TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    session TEXT NOT NULL,
+    PRIMARY KEY (signature, position, session),
+    FOREIGN KEY (signature, position) REFERENCES instructions(signature, position)
+);
+CREATE INDEX IF NOT EXISTS instruction_sessions_by_session ON instruction_sessions (session);
+CREATE TABLE IF NOT EXISTS sync_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+// Dependent tables first; the bundled SQLite enforces foreign keys
+const TABLES: [&str; 8] = [
+    "instruction_sessions",
+    "instructions",
+    "transactions",
+    "finalizations",
+    "sessions",
+    "targets",
+    "pools",
+    "sync_state",
+];
+
+/// One confirmed transaction that invoked the program
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub success: bool,
+    pub logs: Vec<String>,
+    pub instructions: Vec<InstructionRecord>,
+}
+
+/// Row counts, for progress output
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub pools: u64,
+    pub targets: u64,
+    pub sessions: u64,
+    pub finalizations: u64,
+    pub transactions: u64,
+}
+
+pub struct Index {
+    conn: Connection,
+}
+
+impl Index {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Drop everything, for a resync from the program's first transaction
+    pub fn reset(&mut self) -> Result<()> {
+        let drops: String = TABLES.iter().map(|table| format!("DROP TABLE IF EXISTS {};", table)).collect();
+        self.conn.execute_batch(&drops)?;
+        self.conn.execute_batch(SCHEMA)
+    }
+
+    /// Newest transaction indexed so far
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM sync_state WHERE key = 'last_signature'", [], |row| row.get(0))
+            .optional()
+    }
+
+    /// Store a transaction and its instructions, and advance the sync point
+    pub fn record_transaction(&mut self, record: &TransactionRecord) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for table in ["instruction_sessions", "instructions"] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE signature = ?1", table),
+                params![record.signature],
+            )?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO transactions (signature, slot, block_time, success, logs) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.signature,
+                record.slot,
+                record.block_time,
+                record.success,
+                record.logs.join("\n")
+            ],
+        )?;
+        for (position, instruction) in record.instructions.iter().enumerate() {
+            tx.execute(
+                "INSERT OR REPLACE INTO instructions (signature, position, name, pool) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    record.signature,
+                    position,
+                    instruction.name,
+                    instruction.pool.map(|pool| pool.to_string())
+                ],
+            )?;
+            for session in &instruction.sessions {
+                tx.execute(
+                    "INSERT OR IGNORE INTO instruction_sessions (signature, position, session) VALUES (?1, ?2, ?3)",
+                    params![record.signature, position, session.to_string()],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO sync_state (key, value) VALUES ('last_signature', ?1)",
+            params![record.signature],
+        )?;
+        tx.commit()
+    }
+
+    /// Replace the stored state of pools and sessions with a snapshot taken
+    /// at `slot`
+    pub fn store_accounts(
+        &mut self,
+        program_id: &Pubkey,
+        pools: &[(Pubkey, TargetPool)],
+        sessions: &[(Pubkey, Session)],
+        slot: u64,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for (address, pool) in pools {
+            store_pool(&tx, address, pool, slot)?;
+        }
+        for (address, session) in sessions {
+            let pool = pools.iter().find(|(_, pool)| pool.pool_id == session.pool_id);
+            let target_hash = pool.and_then(|(_, pool)| pool.targets.get(session.assigned_target_index as usize));
+            let pool_key = pool_address(program_id, &session.pool_id);
+            store_session(&tx, address, &pool_key, session, target_hash, slot)?;
+        }
+        tx.commit()
+    }
+
+    pub fn counts(&self) -> Result<Counts> {
+        let count = |table: &str| -> Result<u64> {
+            self.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+        };
+        Ok(Counts {
+            pools: count("pools")?,
+            targets: count("targets")?,
+            sessions: count("sessions")?,
+            finalizations: count("finalizations")?,
+            transactions: count("transactions")?,
+        })
+    }
+}
+
+fn store_pool(tx: &Connection, address: &Pubkey, pool: &TargetPool, slot: u64) -> Result<()> {
+    let associative = !pool.outcome_labels.is_empty();
+    let resolved = pool.resolved_outcome != u16::MAX;
+    tx.execute("DELETE FROM targets WHERE pool = ?1", params![address.to_string()])?;
+    tx.execute(
+        "INSERT OR REPLACE INTO pools (address, pool_id, creator, kind, hash_algorithm, target_count, finalized,
+             created_at, min_finalization_delay, max_finalization_delay, resolver, resolved_outcome, resolved_slot,
+             event_time, submission_deadline, indexed_slot)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            address.to_string(),
+            pool_id_string(&pool.pool_id),
+            pool.creator.to_string(),
+            format!("{:?}", pool.kind),
+            format!("{:?}", pool.hash_algorithm),
+            pool.target_count,
+            pool.finalized,
+            pool.created_at,
+            pool.min_finalization_delay,
+            pool.max_finalization_delay,
+            associative.then(|| pool.resolver.to_string()),
+            resolved.then_some(pool.resolved_outcome),
+            resolved.then_some(pool.resolved_slot),
+            associative.then_some(pool.event_time),
+            associative.then_some(pool.submission_deadline),
+            slot
+        ],
+    )?;
+
+    for (index, hash) in pool.targets.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO targets (pool, target_index, hash, outcome_label) VALUES (?1, ?2, ?3, ?4)",
+            params![address.to_string(), index, hex(hash), pool.outcome_labels.get(index)],
+        )?;
+    }
+    Ok(())
+}
+
+fn store_session(
+    tx: &Connection,
+    address: &Pubkey,
+    pool: &Pubkey,
+    session: &Session,
+    target_hash: Option<&[u8; 32]>,
+    slot: u64,
+) -> Result<()> {
+    let completed: Vec<String> = session.completed_target_indices.iter().map(u16::to_string).collect();
+    tx.execute("DELETE FROM finalizations WHERE session = ?1", params![address.to_string()])?;
+    tx.execute(
+        "INSERT OR REPLACE INTO sessions (address, session_id, pool_id, pool, kind, submitter, viewer, media_hash,
+             media_hash_algorithm, media_item_count, media_amendments, completed_target_indices, submission_slot,
+             submitted_at, finalization_reward, experiment, finalized, indexed_slot)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        params![
+            address.to_string(),
+            pool_id_string(&session.session_id),
+            pool_id_string(&session.pool_id),
+            pool.to_string(),
+            format!("{:?}", session.kind),
+            session.submitter.to_string(),
+            session.viewer.to_string(),
+            hex(&session.session_media_hash),
+            format!("{:?}", session.media_hash_algorithm),
+            session.media_item_count,
+            session.media_history.len(),
+            completed.join(","),
+            session.submission_slot,
+            session.submitted_at,
+            session.finalization_reward,
+            (session.experiment != Pubkey::default()).then(|| session.experiment.to_string()),
+            session.finalized,
+            slot
+        ],
+    )?;
+
+    if !session.finalized {
+        return Ok(());
+    }
+
+    // The successful finalizing transaction, if it has been indexed
+    let names: Vec<String> = FINALIZATION_INSTRUCTIONS.iter().map(|name| format!("'{}'", name)).collect();
+    let signature: Option<String> = tx
+        .query_row(
+            &format!(
+                "SELECT t.signature FROM instruction_sessions s
+                 JOIN instructions i ON i.signature = s.signature AND i.position = s.position
+                 JOIN transactions t ON t.signature = s.signature
+                 WHERE s.session = ?1 AND t.success AND i.name IN ({})
+                 ORDER BY t.slot DESC LIMIT 1",
+                names.join(",")
+            ),
+            params![address.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    tx.execute(
+        "INSERT INTO finalizations (session, signature, assigned_target_index, target_hash, entropy_slot,
+             entropy_hash, late, reanchor_count, finalized_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            address.to_string(),
+            signature,
+            session.assigned_target_index,
+            target_hash.map(|hash| hex(hash)),
+            session.entropy_slot,
+            hex(&session.submission_blockhash),
+            session.late_finalized,
+            session.reanchor_count,
+            session.finalized_at
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use remote_viewing_verifier::{session_address, HashAlgorithm, RecordId};
+    use solana_sdk::clock::Clock;
+
+    // The library's test fixtures are not visible to binaries; build the
+    // accounts through the same constructors the processors use
+    fn pool() -> TargetPool {
+        let mut pool = TargetPool::new_standard(
+            RecordId::from("pool"),
+            Pubkey::new_unique(),
+            (0..3).map(|i| [i; 32]).collect(),
+            HashAlgorithm::Sha256,
+            2,
+            150,
+            0,
+        );
+        pool.finalized = true;
+        pool
+    }
+
+    fn finalized_session() -> Session {
+        let clock = Clock {
+            slot: 100,
+            ..Clock::default()
+        };
+        let mut session = Session::new_submitted(
+            &Pubkey::default(),
+            RecordId::from("session"),
+            &pool(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &clock,
+            [7; 32],
+            HashAlgorithm::Sha256,
+            1,
+            vec![0],
+            Pubkey::default(),
+            0,
+            0,
+            [0; 32],
+            Pubkey::default(),
+        );
+        session.submission_blockhash = [8; 32];
+        session.entropy_slot = 100;
+        session.assigned_target_index = 2;
+        session.finalized = true;
+        session.finalized_at = 10;
+        session
+    }
+
+    #[test]
+    fn test_finalization_links_signature_and_target() {
+        let program_id = remote_viewing_verifier::id();
+        let pool_key = pool_address(&program_id, &RecordId::from("pool"));
+        let session_key = session_address(&program_id, &RecordId::from("session"));
+        let mut index = Index::open_in_memory().unwrap();
+
+        for (signature, slot, success) in [("failed", 103, false), ("finalize", 104, true)] {
+            index
+                .record_transaction(&TransactionRecord {
+                    signature: signature.to_string(),
+                    slot,
+                    block_time: None,
+                    success,
+                    logs: Vec::new(),
+                    instructions: vec![InstructionRecord {
+                        name: "FinalizeSessionsBatch",
+                        pool: Some(pool_key),
+                        sessions: vec![session_key],
+                    }],
+                })
+                .unwrap();
+        }
+        assert_eq!(index.last_signature().unwrap().as_deref(), Some("finalize"));
+
+        index
+            .store_accounts(&program_id, &[(pool_key, pool())], &[(session_key, finalized_session())], 105)
+            .unwrap();
+        let (signature, target_hash): (String, String) = index
+            .conn
+            .query_row("SELECT signature, target_hash FROM finalizations", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(signature, "finalize");
+        assert_eq!(target_hash, "02".repeat(32));
+        assert_eq!(
+            index.counts().unwrap(),
+            Counts {
+                pools: 1,
+                targets: 3,
+                sessions: 1,
+                finalizations: 1,
+                transactions: 2,
+            }
+        );
+
+        index.reset().unwrap();
+        assert_eq!(index.counts().unwrap(), Counts::default());
+        assert_eq!(index.last_signature().unwrap(), None);
+    }
+}
@@ -0,0 +1,150 @@
+// Decoding of program instructions and accounts into what the index stores
+
+use borsh::BorshDeserialize;
+use remote_viewing_verifier::legacy::{decode_pool, decode_session};
+use remote_viewing_verifier::{pool_address, session_address, RemoteViewingInstruction, Session, TargetPool};
+use solana_sdk::pubkey::Pubkey;
+
+/// Pools and sessions one program instruction refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionRecord {
+    pub name: &'static str,
+    pub pool: Option<Pubkey>,
+    pub sessions: Vec<Pubkey>,
+}
+
+/// Program accounts the index materializes; stats, judging and experiment
+/// accounts are left to RPC
+pub enum ProgramAccount {
+    Pool(Box<TargetPool>),
+    Session(Box<Session>),
+}
+
+/// Decode one of our instructions given its data and the addresses of its
+/// accounts, in instruction order. Pools and sessions are derived from the
+/// ids in the instruction data where present, since those cannot be spoofed
+/// by passing other accounts.
+pub fn decode_instruction(program_id: &Pubkey, data: &[u8], accounts: &[Pubkey]) -> Option<InstructionRecord> {
+    use RemoteViewingInstruction::*;
+
+    let instruction = RemoteViewingInstruction::try_from_slice(data).ok()?;
+    let pool = |pool_id| Some(pool_address(program_id, pool_id));
+    let session = |session_id| vec![session_address(program_id, session_id)];
+
+    let (name, pool, sessions) = match &instruction {
+        CreateTargetPool { pool_id, .. } => ("CreateTargetPool", pool(pool_id), Vec::new()),
+        SubmitSession { session_id, pool_id, .. } => ("SubmitSession", pool(pool_id), session(session_id)),
+        FinalizeSession { session_id, .. } => ("FinalizeSession", None, session(session_id)),
+        AppendTargetsToPool { pool_id, .. } => ("AppendTargetsToPool", pool(pool_id), Vec::new()),
+        FinalizePool { pool_id } => ("FinalizePool", pool(pool_id), Vec::new()),
+        AmendSessionMedia { session_id, .. } => ("AmendSessionMedia", None, session(session_id)),
+        SubmitSessionBatch { pool_id, sessions, .. } => (
+            "SubmitSessionBatch",
+            pool(pool_id),
+            sessions
+                .iter()
+                .map(|entry| session_address(program_id, &entry.session_id))
+                .collect(),
+        ),
+        // Sessions only appear as accounts: (session, viewer stats) pairs
+        // after the five fixed accounts
+        FinalizeSessionsBatch { .. } => (
+            "FinalizeSessionsBatch",
+            accounts.first().copied(),
+            accounts.iter().skip(5).step_by(2).copied().collect(),
+        ),
+        ReanchorSession { session_id } => ("ReanchorSession", None, session(session_id)),
+        FinalizeLateSession { session_id } => ("FinalizeLateSession", None, session(session_id)),
+        CreateAssociativePool { pool_id, .. } => ("CreateAssociativePool", pool(pool_id), Vec::new()),
+        ResolveOutcome { pool_id, .. } => ("ResolveOutcome", pool(pool_id), Vec::new()),
+        FinalizeAssociativeSession { session_id } => ("FinalizeAssociativeSession", None, session(session_id)),
+        AuthorizeJudge { pool_id, .. } => ("AuthorizeJudge", pool(pool_id), Vec::new()),
+        CommitJudgement { session_id, .. } => ("CommitJudgement", None, session(session_id)),
+        JudgeSession { session_id, .. } => ("JudgeSession", None, session(session_id)),
+        ForfeitJudgement { session_id, .. } => ("ForfeitJudgement", None, session(session_id)),
+        RevealTasking { session_id, .. } => ("RevealTasking", None, session(session_id)),
+        CreateExperiment { .. } => ("CreateExperiment", None, Vec::new()),
+        UpdateExperimentPlan { .. } => ("UpdateExperimentPlan", None, Vec::new()),
+        InitializeConfig { .. } => ("InitializeConfig", None, Vec::new()),
+        SetFrozen { .. } => ("SetFrozen", None, Vec::new()),
+        AnnounceUpgrade { .. } => ("AnnounceUpgrade", None, Vec::new()),
+        ExecuteUpgrade => ("ExecuteUpgrade", None, Vec::new()),
+        SetGovernance { .. } => ("SetGovernance", None, Vec::new()),
+        SetPaused { .. } => ("SetPaused", None, Vec::new()),
+        SetLimits { .. } => ("SetLimits", None, Vec::new()),
+        SetAdmin { .. } => ("SetAdmin", None, Vec::new()),
+    };
+    Some(InstructionRecord { name, pool, sessions })
+}
+
+/// Instructions that finalize the sessions they refer to
+pub const FINALIZATION_INSTRUCTIONS: [&str; 4] = [
+    "FinalizeSession",
+    "FinalizeSessionsBatch",
+    "FinalizeLateSession",
+    "FinalizeAssociativeSession",
+];
+
+/// Decode a program account as a pool or session, in the current layout or
+/// the legacy one. Legacy accounts carry no type tag, so a decoded value only
+/// counts if its id derives to `address`.
+pub fn classify_account(program_id: &Pubkey, address: &Pubkey, data: &[u8]) -> Option<ProgramAccount> {
+    if let Some(pool) = decode_pool(data) {
+        if pool_address(program_id, &pool.pool_id) == *address {
+            return Some(ProgramAccount::Pool(Box::new(pool)));
+        }
+    }
+    if let Some(session) = decode_session(data) {
+        if session_address(program_id, &session.session_id) == *address {
+            return Some(ProgramAccount::Session(Box::new(session)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use remote_viewing_verifier::{BatchSessionEntry, HashAlgorithm, RecordId};
+
+    #[test]
+    fn test_decode_batch_instructions() {
+        let program_id = remote_viewing_verifier::id();
+        let entry = |id: &str| BatchSessionEntry {
+            session_id: RecordId::from(id),
+            session_media_hash: [1; 32],
+            completed_target_indices: Vec::new(),
+            media_item_count: 1,
+            tasking_commitment: [0; 32],
+        };
+        let submit = RemoteViewingInstruction::SubmitSessionBatch {
+            pool_id: RecordId::from("pool"),
+            target_selector_program: Pubkey::default(),
+            media_hash_algorithm: HashAlgorithm::Sha256,
+            finalization_reward: 0,
+            sessions: vec![entry("a"), entry("b")],
+            decoy_count: 0,
+            experiment: Pubkey::default(),
+        };
+        let record = decode_instruction(&program_id, &submit.try_to_vec().unwrap(), &[]).unwrap();
+        assert_eq!(record.name, "SubmitSessionBatch");
+        assert_eq!(record.pool, Some(pool_address(&program_id, &RecordId::from("pool"))));
+        assert_eq!(
+            record.sessions,
+            [
+                session_address(&program_id, &RecordId::from("a")),
+                session_address(&program_id, &RecordId::from("b"))
+            ]
+        );
+
+        let finalize = RemoteViewingInstruction::FinalizeSessionsBatch { submission_slot: 7 };
+        let accounts: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+        let record = decode_instruction(&program_id, &finalize.try_to_vec().unwrap(), &accounts).unwrap();
+        assert!(FINALIZATION_INSTRUCTIONS.contains(&record.name));
+        assert_eq!(record.pool, Some(accounts[0]));
+        assert_eq!(record.sessions, [accounts[5], accounts[7]]);
+
+        assert_eq!(decode_instruction(&program_id, &[255], &[]), None);
+    }
+}
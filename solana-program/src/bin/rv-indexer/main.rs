@@ -0,0 +1,185 @@
+// Indexer that materializes the program's pools and sessions into SQLite
+//
+// Each sync walks the program's transaction history from the last indexed
+// signature (or from its first transaction on a resync), records every
+// instruction sent to the program, then snapshots all pool and session
+// accounts. Point `--url` at a local validator, or at one started from an
+// existing ledger to replay it.
+
+mod db;
+mod decode;
+
+use clap::{Parser, Subcommand};
+use db::{Index, TransactionRecord};
+use decode::{classify_account, decode_instruction, ProgramAccount};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{UiLoadedAddresses, UiTransactionEncoding};
+use std::error::Error;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Duration;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[clap(name = "rv-indexer", version, about = "Index remote viewing pools and sessions into SQLite")]
+struct Cli {
+    /// JSON RPC endpoint; defaults to a local solana-test-validator
+    #[clap(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Address the program is deployed at
+    #[clap(long, global = true, default_value_t = remote_viewing_verifier::id())]
+    program_id: Pubkey,
+    /// SQLite database to write
+    #[clap(long, global = true, default_value = "rv-index.sqlite")]
+    db: PathBuf,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index transactions since the last sync and refresh account state
+    Sync {
+        /// Keep syncing, pausing this many seconds between rounds
+        #[clap(long)]
+        follow: Option<u64>,
+    },
+    /// Drop the index and rebuild it from the program's first transaction
+    Resync,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+    let mut index = Index::open(&cli.db)?;
+
+    match cli.command {
+        Command::Sync { follow } => loop {
+            sync(&rpc, &mut index, &cli.program_id)?;
+            match follow {
+                Some(seconds) => sleep(Duration::from_secs(seconds)),
+                None => return Ok(()),
+            }
+        },
+        Command::Resync => {
+            index.reset()?;
+            sync(&rpc, &mut index, &cli.program_id)
+        }
+    }
+}
+
+fn sync(rpc: &RpcClient, index: &mut Index, program_id: &Pubkey) -> Result<()> {
+    // Signatures come newest first, a page at a time
+    let until = index.last_signature()?.map(|signature| Signature::from_str(&signature)).transpose()?;
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            program_id,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: None,
+                commitment: Some(rpc.commitment()),
+            },
+        )?;
+        let Some(oldest) = page.last() else { break };
+        before = Some(Signature::from_str(&oldest.signature)?);
+        signatures.extend(page.into_iter().map(|status| status.signature));
+    }
+
+    for signature in signatures.iter().rev() {
+        index.record_transaction(&fetch_transaction(rpc, program_id, signature)?)?;
+    }
+
+    let slot = rpc.get_slot()?;
+    let (mut pools, mut sessions) = (Vec::new(), Vec::new());
+    for (address, account) in rpc.get_program_accounts(program_id)? {
+        match classify_account(program_id, &address, &account.data) {
+            Some(ProgramAccount::Pool(pool)) => pools.push((address, *pool)),
+            Some(ProgramAccount::Session(session)) => sessions.push((address, *session)),
+            None => {}
+        }
+    }
+    index.store_accounts(program_id, &pools, &sessions, slot)?;
+
+    let counts = index.counts()?;
+    println!(
+        "Indexed {} new transaction(s) at slot {}: {} pools, {} targets, {} sessions, {} finalized",
+        signatures.len(),
+        slot,
+        counts.pools,
+        counts.targets,
+        counts.sessions,
+        counts.finalizations
+    );
+    Ok(())
+}
+
+// Only top-level instructions are recorded; calls into the program from
+// other programs show up in account state but not in the instruction tables
+fn fetch_transaction(rpc: &RpcClient, program_id: &Pubkey, signature: &str) -> Result<TransactionRecord> {
+    let confirmed = rpc.get_transaction_with_config(
+        &Signature::from_str(signature)?,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| format!("cannot decode transaction {}", signature))?;
+    let meta = confirmed.transaction.meta;
+
+    // Account keys of v0 transactions continue with addresses loaded from
+    // lookup tables, writable ones first
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    let loaded = meta
+        .as_ref()
+        .and_then(|meta| Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone()));
+    if let Some(loaded) = loaded {
+        for key in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(Pubkey::from_str(key)?);
+        }
+    }
+
+    let instructions = transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| keys.get(instruction.program_id_index as usize) == Some(program_id))
+        .filter_map(|instruction| {
+            let accounts: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .filter_map(|&i| keys.get(i as usize).copied())
+                .collect();
+            decode_instruction(program_id, &instruction.data, &accounts)
+        })
+        .collect();
+
+    Ok(TransactionRecord {
+        signature: signature.to_string(),
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        success: meta.as_ref().is_some_and(|meta| meta.err.is_none()),
+        logs: meta.and_then(|meta| Option::from(meta.log_messages)).unwrap_or_default(),
+        instructions,
+    })
+}
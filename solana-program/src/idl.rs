@@ -0,0 +1,575 @@
+// Shank-format IDL for the native program
+//
+// Instruction arguments, account layouts and error codes are read from the
+// Borsh schemas of the Rust types, so the IDL cannot silently disagree with
+// the program. Account lists come from the table below, which mirrors the
+// "Accounts expected" docs on `RemoteViewingInstruction`; the processor tests
+// check every instruction they send against it through
+// `instruction_accounts`.
+//
+// The generated IDL is checked in at `IDL_PATH`; a test fails when it is
+// stale. Regenerate with `UPDATE_IDL=1 cargo test idl`.
+
+use crate::{
+    Experiment, JudgeAuthorization, Judgement, PoolStats, ProgramConfig, RemoteViewingError, RemoteViewingInstruction,
+    Session, TargetPool, ViewerStats,
+};
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use borsh::BorshSchema;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Location of the checked-in IDL, relative to the crate root
+pub const IDL_PATH: &str = "idl/remote_viewing_verifier.json";
+
+/// (name, writable, signer, docs)
+pub type IdlAccount = (&'static str, bool, bool, &'static str);
+
+const INSTRUCTION_ACCOUNTS: &[(&str, &[IdlAccount])] = &[
+    (
+        "CreateTargetPool",
+        &[
+            ("pool", true, false, ""),
+            ("creator", false, true, ""),
+            ("systemProgram", false, false, ""),
+            ("poolStats", true, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "SubmitSession",
+        &[
+            ("session", true, false, ""),
+            ("pool", false, false, ""),
+            ("submitter", true, true, ""),
+            ("systemProgram", false, false, ""),
+            ("clock", false, false, ""),
+            ("viewer", false, true, "Viewer credited with the session, may be the submitter"),
+            ("viewerStats", true, false, "Created if missing"),
+            ("experiment", true, false, "Only when `experiment` is set"),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "FinalizeSession",
+        &[
+            ("session", true, false, ""),
+            ("pool", false, false, ""),
+            ("caller", true, true, "Receives the finalization reward"),
+            ("clock", false, false, ""),
+            ("slotHashes", false, false, ""),
+            ("viewerStats", true, false, ""),
+            ("poolStats", true, false, ""),
+        ],
+    ),
+    (
+        "AppendTargetsToPool",
+        &[
+            ("pool", true, false, ""),
+            ("creator", false, true, ""),
+            ("systemProgram", false, false, ""),
+            ("poolStats", true, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    ("FinalizePool", &[("pool", true, false, ""), ("creator", false, true, "")]),
+    (
+        "AmendSessionMedia",
+        &[
+            ("session", true, false, ""),
+            ("submitter", true, true, ""),
+            ("systemProgram", false, false, ""),
+            ("clock", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "SubmitSessionBatch",
+        &[
+            ("pool", false, false, ""),
+            ("submitter", true, true, ""),
+            ("systemProgram", false, false, ""),
+            ("clock", false, false, ""),
+            ("experiment", true, false, "Only when `experiment` is set"),
+            (
+                "sessions",
+                true,
+                false,
+                "Session account, its viewer (signer) and the viewer's stats account for each entry, as remaining accounts",
+            ),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "FinalizeSessionsBatch",
+        &[
+            ("pool", false, false, ""),
+            ("caller", true, true, "Receives the finalization rewards"),
+            ("clock", false, false, ""),
+            ("slotHashes", false, false, ""),
+            ("poolStats", true, false, ""),
+            (
+                "sessions",
+                true,
+                false,
+                "Session account then its viewer stats account for each session, as remaining accounts",
+            ),
+        ],
+    ),
+    (
+        "ReanchorSession",
+        &[
+            ("session", true, false, ""),
+            ("caller", false, true, ""),
+            ("clock", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "FinalizeLateSession",
+        &[
+            ("session", true, false, ""),
+            ("pool", false, false, ""),
+            ("caller", true, true, "Receives the finalization reward"),
+            ("clock", false, false, ""),
+            ("slotHashes", false, false, ""),
+            ("viewerStats", true, false, ""),
+            ("poolStats", true, false, ""),
+        ],
+    ),
+    (
+        "CreateAssociativePool",
+        &[
+            ("pool", true, false, ""),
+            ("creator", false, true, ""),
+            ("systemProgram", false, false, ""),
+            ("poolStats", true, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "ResolveOutcome",
+        &[
+            ("pool", true, false, ""),
+            ("resolver", false, true, ""),
+            ("clock", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "FinalizeAssociativeSession",
+        &[
+            ("session", true, false, ""),
+            ("pool", false, false, ""),
+            ("caller", true, true, "Receives the finalization reward"),
+            ("clock", false, false, ""),
+            ("viewerStats", true, false, ""),
+            ("poolStats", true, false, ""),
+        ],
+    ),
+    (
+        "AuthorizeJudge",
+        &[
+            ("authorization", true, false, ""),
+            ("pool", false, false, ""),
+            ("creator", false, true, ""),
+            ("systemProgram", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "CommitJudgement",
+        &[
+            ("judgement", true, false, ""),
+            ("session", true, false, ""),
+            ("authorization", false, false, ""),
+            ("judge", true, true, ""),
+            ("systemProgram", false, false, ""),
+            ("clock", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "JudgeSession",
+        &[
+            ("judgement", true, false, ""),
+            ("session", false, false, ""),
+            ("pool", false, false, ""),
+            ("authorization", false, false, ""),
+            ("judge", true, true, ""),
+            ("systemProgram", false, false, ""),
+            ("clock", false, false, ""),
+            ("viewerStats", true, false, ""),
+            ("poolStats", true, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "ForfeitJudgement",
+        &[
+            ("judgement", true, false, ""),
+            ("session", false, false, ""),
+            ("pool", false, false, ""),
+            ("caller", false, true, ""),
+            ("clock", false, false, ""),
+            ("viewerStats", true, false, ""),
+            ("poolStats", true, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "RevealTasking",
+        &[
+            ("session", true, false, ""),
+            ("submitter", false, true, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "CreateExperiment",
+        &[
+            ("experiment", true, false, ""),
+            ("creator", false, true, ""),
+            ("systemProgram", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "UpdateExperimentPlan",
+        &[
+            ("experiment", true, false, ""),
+            ("creator", false, true, ""),
+            ("clock", false, false, ""),
+            ("config", false, false, "Program config account (PDA), always last"),
+        ],
+    ),
+    (
+        "InitializeConfig",
+        &[
+            ("config", true, false, ""),
+            ("authority", true, true, "The program's current upgrade authority"),
+            ("programData", false, false, ""),
+            ("systemProgram", false, false, ""),
+        ],
+    ),
+    (
+        "SetFrozen",
+        &[
+            ("config", true, false, ""),
+            ("governance", false, true, ""),
+            ("clock", false, false, ""),
+        ],
+    ),
+    (
+        "AnnounceUpgrade",
+        &[
+            ("config", true, false, ""),
+            ("governance", false, true, ""),
+            ("clock", false, false, ""),
+        ],
+    ),
+    (
+        "ExecuteUpgrade",
+        &[
+            ("config", true, false, "The program's upgrade authority"),
+            ("governance", false, true, ""),
+            ("programData", true, false, ""),
+            ("program", true, false, ""),
+            ("buffer", true, false, ""),
+            ("spill", true, false, "Receives the buffer's lamports"),
+            ("rent", false, false, ""),
+            ("clock", false, false, ""),
+            ("bpfLoaderUpgradeable", false, false, ""),
+        ],
+    ),
+    ("SetGovernance", &[("config", true, false, ""), ("governance", false, true, "")]),
+    ("SetPaused", &[("config", true, false, ""), ("admin", false, true, "")]),
+    ("SetLimits", &[("config", true, false, ""), ("admin", false, true, "")]),
+    ("SetAdmin", &[("config", true, false, ""), ("governance", false, true, "")]),
+];
+
+// Collects type definitions from Borsh schemas and the user-defined types
+// they reference, in order of first use
+#[derive(Default)]
+struct Schemas {
+    definitions: HashMap<Declaration, Definition>,
+    defined_types: Vec<Declaration>,
+}
+
+impl Schemas {
+    fn add(&mut self, container: BorshSchemaContainer) -> Declaration {
+        self.definitions.extend(container.definitions);
+        container.declaration
+    }
+
+    fn definition(&self, declaration: &str) -> &Definition {
+        self.definitions
+            .get(declaration)
+            .unwrap_or_else(|| panic!("no schema for {}", declaration))
+    }
+
+    fn named_fields(&self, declaration: &str) -> Vec<(Declaration, Declaration)> {
+        match self.definition(declaration) {
+            Definition::Struct {
+                fields: Fields::NamedFields(fields),
+            } => fields.clone(),
+            Definition::Struct { fields: Fields::Empty } => Vec::new(),
+            other => panic!("{} is not a struct with named fields: {:?}", declaration, other),
+        }
+    }
+
+    fn variants(&self, declaration: &str) -> Vec<(Declaration, Declaration)> {
+        match self.definition(declaration) {
+            Definition::Enum { variants } => variants.clone(),
+            other => panic!("{} is not an enum: {:?}", declaration, other),
+        }
+    }
+
+    // IDL type of a field, recording user-defined types for the `types` section
+    fn idl_type(&mut self, declaration: &str) -> Value {
+        match declaration {
+            "u8" | "u16" | "u32" | "u64" | "i64" | "bool" | "string" => return json!(declaration),
+            "Pubkey" => return json!("publicKey"),
+            _ => {}
+        }
+
+        match self.definition(declaration).clone() {
+            Definition::Array { length, elements } => json!({ "array": [self.idl_type(&elements), length] }),
+            Definition::Sequence { elements } => json!({ "vec": self.idl_type(&elements) }),
+            Definition::Enum { variants } if declaration.starts_with("Option<") => {
+                json!({ "option": self.idl_type(&variants[1].1) })
+            }
+            _ => {
+                if !self.defined_types.iter().any(|name| name == declaration) {
+                    self.defined_types.push(declaration.to_string());
+                }
+                json!({ "defined": declaration })
+            }
+        }
+    }
+
+    fn fields(&mut self, declaration: &str) -> Value {
+        let fields = self
+            .named_fields(declaration)
+            .into_iter()
+            .map(|(name, field_type)| json!({ "name": camel_case(&name), "type": self.idl_type(&field_type) }))
+            .collect();
+        Value::Array(fields)
+    }
+
+    fn type_definition(&mut self, declaration: &str) -> Value {
+        let kind = match self.definition(declaration) {
+            Definition::Struct { .. } => json!({ "kind": "struct", "fields": self.fields(declaration) }),
+            Definition::Enum { .. } => {
+                let variants: Vec<Value> = self
+                    .variants(declaration)
+                    .into_iter()
+                    .map(|(name, variant)| match self.definition(&variant).clone() {
+                        Definition::Struct { fields: Fields::Empty } => json!({ "name": name }),
+                        // Tuple variants list bare field types
+                        Definition::Struct {
+                            fields: Fields::UnnamedFields(fields),
+                        } => {
+                            let fields: Vec<Value> = fields.iter().map(|field| self.idl_type(field)).collect();
+                            json!({ "name": name, "fields": fields })
+                        }
+                        _ => json!({ "name": name, "fields": self.fields(&variant) }),
+                    })
+                    .collect();
+                json!({ "kind": "enum", "variants": variants })
+            }
+            other => panic!("unsupported top-level type {}: {:?}", declaration, other),
+        };
+        json!({ "name": declaration, "type": kind })
+    }
+}
+
+/// An instruction's account list split around its optional and repeated
+/// accounts, which clients may or may not pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionAccounts {
+    /// Accounts always passed first, in order
+    pub leading: &'static [IdlAccount],
+    /// Accounts always passed last, in order, such as the config account
+    pub trailing: &'static [IdlAccount],
+    /// Whether anything may come between the two
+    pub variable: bool,
+}
+
+/// Account list of the instruction variant named `instruction`, as the IDL
+/// lists it
+pub fn instruction_accounts(instruction: &str) -> Option<InstructionAccounts> {
+    let (_, accounts) = INSTRUCTION_ACCOUNTS.iter().find(|(name, _)| *name == instruction)?;
+    // Remaining accounts (`sessions`) and accounts passed only with some
+    // arguments are the only variable entries
+    let is_variable = |&(name, _, _, docs): &IdlAccount| name == "sessions" || docs.starts_with("Only when");
+    let leading = accounts.iter().position(is_variable).unwrap_or(accounts.len());
+    let trailing = accounts.iter().rposition(is_variable).map_or(accounts.len(), |last| last + 1);
+    Some(InstructionAccounts {
+        leading: &accounts[..leading],
+        trailing: &accounts[trailing..],
+        variable: leading < accounts.len(),
+    })
+}
+
+/// Build the IDL as pretty-printed JSON
+pub fn generate_idl() -> String {
+    let mut schemas = Schemas::default();
+
+    let instruction_enum = schemas.add(RemoteViewingInstruction::schema_container());
+    let instructions = schemas
+        .variants(&instruction_enum)
+        .into_iter()
+        .enumerate()
+        .map(|(discriminant, (name, variant))| {
+            let accounts = INSTRUCTION_ACCOUNTS
+                .iter()
+                .find(|(instruction, _)| *instruction == name)
+                .unwrap_or_else(|| panic!("no account list for instruction {}", name))
+                .1
+                .iter()
+                .map(|&(account, writable, signer, docs)| {
+                    let mut meta = json!({ "name": account, "isMut": writable, "isSigner": signer });
+                    if !docs.is_empty() {
+                        meta["docs"] = json!([docs]);
+                    }
+                    meta
+                })
+                .collect::<Vec<Value>>();
+
+            json!({
+                "name": camel_case(&name),
+                "accounts": accounts,
+                "args": schemas.fields(&variant),
+                "discriminant": { "type": "u8", "value": discriminant },
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    let account_types = [
+        schemas.add(TargetPool::schema_container()),
+        schemas.add(Session::schema_container()),
+        schemas.add(JudgeAuthorization::schema_container()),
+        schemas.add(Judgement::schema_container()),
+        schemas.add(ViewerStats::schema_container()),
+        schemas.add(PoolStats::schema_container()),
+        schemas.add(Experiment::schema_container()),
+        schemas.add(ProgramConfig::schema_container()),
+    ];
+    let accounts: Vec<Value> = account_types
+        .iter()
+        .map(|declaration| schemas.type_definition(declaration))
+        .collect();
+
+    // Types referenced by accounts may in turn reference further types
+    let mut types = Vec::new();
+    let mut next = 0;
+    while next < schemas.defined_types.len() {
+        let declaration = schemas.defined_types[next].clone();
+        if !account_types.contains(&declaration) {
+            types.push(schemas.type_definition(&declaration));
+        }
+        next += 1;
+    }
+
+    let error_enum = schemas.add(RemoteViewingError::schema_container());
+    let errors = schemas
+        .variants(&error_enum)
+        .into_iter()
+        .enumerate()
+        .map(|(code, (name, _))| json!({ "code": code, "msg": sentence_case(&name), "name": name }))
+        .collect::<Vec<Value>>();
+
+    let idl = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "name": env!("CARGO_PKG_NAME"),
+        "instructions": instructions,
+        "accounts": accounts,
+        "types": types,
+        "errors": errors,
+        "metadata": { "origin": "shank", "address": crate::id().to_string() },
+    });
+
+    let mut out = serde_json::to_string_pretty(&idl).expect("IDL serializes");
+    out.push('\n');
+    out
+}
+
+// `pool_id` -> `poolId`, `CreateTargetPool` -> `createTargetPool`
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper_next = false;
+    for (i, c) in name.chars().enumerate() {
+        if c == '_' {
+            upper_next = true;
+        } else if i == 0 {
+            out.push(c.to_ascii_lowercase());
+        } else if upper_next {
+            out.push(c.to_ascii_uppercase());
+            upper_next = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// `PoolNotFound` -> `Pool not found`
+fn sentence_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            out.push(' ');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_idl_is_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(IDL_PATH);
+        let generated = generate_idl();
+        if std::env::var_os("UPDATE_IDL").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date with the Rust types; regenerate with `UPDATE_IDL=1 cargo test idl`",
+            IDL_PATH
+        );
+    }
+
+    #[test]
+    fn test_camel_and_sentence_case() {
+        assert_eq!(camel_case("pool_id"), "poolId");
+        assert_eq!(camel_case("CreateTargetPool"), "createTargetPool");
+        assert_eq!(sentence_case("PoolNotFound"), "Pool not found");
+    }
+
+    #[test]
+    fn test_instruction_accounts() {
+        let submit = instruction_accounts("SubmitSession").unwrap();
+        assert_eq!(submit.leading.len(), 7);
+        assert_eq!(submit.trailing.iter().map(|account| account.0).collect::<Vec<_>>(), ["config"]);
+        assert!(submit.variable);
+
+        let batch = instruction_accounts("FinalizeSessionsBatch").unwrap();
+        assert_eq!(batch.leading.len(), 5);
+        assert!(batch.trailing.is_empty() && batch.variable);
+
+        let reanchor = instruction_accounts("ReanchorSession").unwrap();
+        assert_eq!(reanchor.leading.len(), 4);
+        assert!(reanchor.trailing.is_empty() && !reanchor.variable);
+        assert_eq!(instruction_accounts("Unknown"), None);
+    }
+}
@@ -0,0 +1,437 @@
+// Program instructions
+
+use crate::{HashAlgorithm, JudgingMethod, RecordId};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{pubkey::Pubkey, slot_history::Slot};
+
+pub use variants::RemoteViewingInstruction;
+
+// The `BorshSchema` derive (used for the IDL) expands each variant into a
+// helper struct whose fields are never read. The enum sits in its own module
+// so the allow covers only it and those generated structs.
+#[allow(dead_code)]
+mod variants {
+    use super::*;
+
+    // Every instruction except session and pool finalization and the config
+    // instructions must also pass the program config account (PDA) as its last
+    // account, after any optional or remaining accounts. They are refused while
+    // the program is frozen, except re-anchoring and outcome resolution, which
+    // late and associative finalization depend on. Pool creation and session
+    // submission are also refused while it is paused.
+    #[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+    pub enum RemoteViewingInstruction {
+        /// Create a new target pool
+        /// The finalization delay bounds apply to every session submitted to the pool
+        /// and must satisfy `MIN_FINALIZATION_DELAY_SLOTS <= min <= max <= MAX_FINALIZATION_DELAY_SLOTS`
+        /// Accounts expected:
+        /// 1. `[writable]` Pool account (PDA)
+        /// 2. `[signer]` Pool creator
+        /// 3. `[]` System program
+        /// 4. `[writable]` Pool stats account (PDA)
+        CreateTargetPool {
+            pool_id: RecordId,
+            target_hashes: Vec<[u8; 32]>,
+            hash_algorithm: HashAlgorithm,
+            min_finalization_delay: u64,
+            max_finalization_delay: u64,
+        },
+
+        /// Submit a remote viewing session (initial submission without target assignment)
+        /// With `media_item_count` > 1, `session_media_hash` is the Merkle root of the
+        /// ordered media manifest (see `manifest::MediaManifest`)
+        /// A non-zero `decoy_count` draws that many decoys alongside the target at
+        /// finalization, for blind judging; standard pools only
+        /// A non-zero `tasking_commitment` (`tasking_commitment(salt)`) enables
+        /// tasker-blind mode: the viewer is given only the derived tasking code
+        /// A non-default `experiment` counts the session towards that experiment,
+        /// which must list the pool and be within its time window
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[]` Pool account
+        /// 3. `[signer, writable]` Session submitter (pays for the account)
+        /// 4. `[]` System program
+        /// 5. `[]` Clock sysvar
+        /// 6. `[signer]` Viewer credited with the session (may be the submitter)
+        /// 7. `[writable]` Viewer stats account (PDA), created if missing
+        /// 8. `[writable]` Experiment account, only when `experiment` is set
+        SubmitSession {
+            session_id: RecordId,
+            pool_id: RecordId,
+            session_media_hash: [u8; 32],
+            target_selector_program: Pubkey,
+            completed_target_indices: Vec<u16>,
+            media_hash_algorithm: HashAlgorithm,
+            media_item_count: u16,
+            finalization_reward: u64,
+            decoy_count: u8,
+            tasking_commitment: [u8; 32],
+            experiment: Pubkey,
+        },
+
+        /// Finalize a session with target assignment based on the hash of its
+        /// entropy slot (the submission slot plus the pool's minimum delay),
+        /// read from the SlotHashes sysvar
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[]` Pool account
+        /// 3. `[signer, writable]` Caller (can be anyone, receives the finalization reward)
+        /// 4. `[]` Clock sysvar
+        /// 5. `[]` SlotHashes sysvar
+        /// 6. `[writable]` Viewer stats account (PDA)
+        /// 7. `[writable]` Pool stats account (PDA)
+        FinalizeSession {
+            session_id: RecordId,
+        },
+
+        /// Append targets to an existing pool
+        /// Accounts expected:
+        /// 1. `[writable]` Pool account (PDA)
+        /// 2. `[signer]` Pool creator (must match original creator)
+        /// 3. `[]` System program
+        /// 4. `[writable]` Pool stats account (PDA)
+        AppendTargetsToPool {
+            pool_id: RecordId,
+            target_hashes: Vec<[u8; 32]>,
+        },
+
+        /// Finalize a pool to prevent further target additions
+        /// Accounts expected:
+        /// 1. `[writable]` Pool account (PDA)
+        /// 2. `[signer]` Pool creator (must match original creator)
+        FinalizePool {
+            pool_id: RecordId,
+        },
+
+        /// Replace a session's media hash before its entropy slot, so before the
+        /// slot hash that draws its target exists, recording the superseded hash
+        /// in the session's amendment history
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[signer, writable]` Session submitter (must match original submitter)
+        /// 3. `[]` System program
+        /// 4. `[]` Clock sysvar
+        AmendSessionMedia {
+            session_id: RecordId,
+            session_media_hash: [u8; 32],
+            media_item_count: u16,
+        },
+
+        /// Submit several sessions against one pool in a single instruction.
+        /// All sessions share the same submission slot, decoy count and experiment.
+        /// Accounts expected:
+        /// 1. `[]` Pool account
+        /// 2. `[signer, writable]` Session submitter
+        /// 3. `[]` System program
+        /// 4. `[]` Clock sysvar
+        /// 5. `[writable]` Experiment account, only when `experiment` is set
+        /// 6. `[writable]` Session account (PDA), `[signer]` its viewer and
+        ///    `[writable]` the viewer's stats account (PDA), for each entry in
+        ///    order, from here on
+        SubmitSessionBatch {
+            pool_id: RecordId,
+            target_selector_program: Pubkey,
+            media_hash_algorithm: HashAlgorithm,
+            finalization_reward: u64,
+            sessions: Vec<BatchSessionEntry>,
+            decoy_count: u8,
+            experiment: Pubkey,
+        },
+
+        /// Finalize sessions that share a submission slot, reading their shared
+        /// entropy slot's hash once from the SlotHashes sysvar. Permissionless: the caller collects each
+        /// session's finalization reward. Already finalized sessions are skipped.
+        /// Accounts expected:
+        /// 1. `[]` Pool account
+        /// 2. `[signer, writable]` Caller (can be anyone)
+        /// 3. `[]` Clock sysvar
+        /// 4. `[]` SlotHashes sysvar
+        /// 5. `[writable]` Pool stats account (PDA)
+        /// 6. `[writable]` Session account (PDA), then `[writable]` its viewer stats
+        ///    account (PDA), for each session, from here on
+        FinalizeSessionsBatch {
+            submission_slot: Slot,
+        },
+
+        /// Re-anchor an unfinalized session whose finalization window has passed.
+        /// Records the current slot; the session is then finalized with
+        /// FinalizeLateSession from the first slot hash after this slot. If
+        /// that window passes too, the session can be re-anchored again.
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[signer]` Caller (can be anyone)
+        /// 3. `[]` Clock sysvar
+        ReanchorSession {
+            session_id: RecordId,
+        },
+
+        /// Finalize a re-anchored session and flag it as late-finalized. It is
+        /// counted as late in the stats accounts and left out of their
+        /// assignment and judgement counters.
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[]` Pool account
+        /// 3. `[signer, writable]` Caller (can be anyone, receives the finalization reward)
+        /// 4. `[]` Clock sysvar
+        /// 5. `[]` SlotHashes sysvar
+        /// 6. `[writable]` Viewer stats account (PDA)
+        /// 7. `[writable]` Pool stats account (PDA)
+        FinalizeLateSession {
+            session_id: RecordId,
+        },
+
+        /// Create an Associative Remote Viewing pool for one future event. Target
+        /// `i` is associated with `outcome_labels[i]`. The pool is finalized on
+        /// creation so the target/outcome mapping cannot change.
+        /// At most `MAX_OUTCOMES` outcomes with labels of at most
+        /// `MAX_OUTCOME_LABEL_LEN` bytes. Sessions are accepted until
+        /// `submission_deadline`, which must be in the future and no later than
+        /// `event_time`; the outcome can be resolved from `event_time` on.
+        /// Accounts expected:
+        /// 1. `[writable]` Pool account (PDA)
+        /// 2. `[signer]` Pool creator
+        /// 3. `[]` System program
+        /// 4. `[writable]` Pool stats account (PDA)
+        CreateAssociativePool {
+            pool_id: RecordId,
+            target_hashes: Vec<[u8; 32]>,
+            hash_algorithm: HashAlgorithm,
+            outcome_labels: Vec<String>,
+            resolver: Pubkey,
+            event_time: i64,
+            submission_deadline: i64,
+        },
+
+        /// Record the real-world outcome of an associative pool's event, no
+        /// earlier than its `event_time`. Sessions can no longer be submitted to
+        /// the pool afterwards.
+        /// Accounts expected:
+        /// 1. `[writable]` Pool account (PDA)
+        /// 2. `[signer]` Resolver (must match the pool's resolver)
+        /// 3. `[]` Clock sysvar
+        ResolveOutcome {
+            pool_id: RecordId,
+            outcome_index: u16,
+        },
+
+        /// Assign an associative session the target of its pool's resolved outcome
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[]` Pool account
+        /// 3. `[signer, writable]` Caller (can be anyone, receives the finalization reward)
+        /// 4. `[]` Clock sysvar
+        /// 5. `[writable]` Viewer stats account (PDA)
+        /// 6. `[writable]` Pool stats account (PDA)
+        FinalizeAssociativeSession {
+            session_id: RecordId,
+        },
+
+        /// Authorize a judge to score sessions submitted to a pool
+        /// Accounts expected:
+        /// 1. `[writable]` Judge authorization account (PDA)
+        /// 2. `[]` Pool account
+        /// 3. `[signer]` Pool creator (must match original creator)
+        /// 4. `[]` System program
+        AuthorizeJudge {
+            pool_id: RecordId,
+            judge: Pubkey,
+        },
+
+        /// Commit to a blind judgement before the session's target is assigned.
+        /// `commitment` is `judgement_commitment(method, candidates, scores, salt)`.
+        /// A standard session needs at least `MIN_JUDGING_DECOYS` decoys, and the
+        /// judgement must be revealed within `JUDGEMENT_REVEAL_PERIOD` seconds of
+        /// finalization or it can be forfeited as a miss. The first judge to
+        /// commit becomes the session's designated judge.
+        /// Accounts expected:
+        /// 1. `[writable]` Judgement account (PDA)
+        /// 2. `[writable]` Session account
+        /// 3. `[]` Judge authorization account (PDA)
+        /// 4. `[signer, writable]` Judge
+        /// 5. `[]` System program
+        /// 6. `[]` Clock sysvar
+        CommitJudgement {
+            session_id: RecordId,
+            method: JudgingMethod,
+            commitment: [u8; 32],
+        },
+
+        /// Record a judge's scores for a finalized session. The target is scored
+        /// among the session's candidates, which a standard session needs
+        /// `MIN_JUDGING_DECOYS` decoys for, or among every outcome's target for
+        /// an associative session. If the judge committed beforehand, the scores and salt must
+        /// match the commitment and are only accepted until the reveal deadline.
+        /// Only the designated judge's blind judgement is added to the stats
+        /// accounts; open judgements are recorded but not counted.
+        /// Accounts expected:
+        /// 1. `[writable]` Judgement account (PDA)
+        /// 2. `[]` Session account
+        /// 3. `[]` Pool account
+        /// 4. `[]` Judge authorization account (PDA)
+        /// 5. `[signer, writable]` Judge
+        /// 6. `[]` System program
+        /// 7. `[]` Clock sysvar
+        /// 8. `[writable]` Viewer stats account (PDA)
+        /// 9. `[writable]` Pool stats account (PDA)
+        JudgeSession {
+            session_id: RecordId,
+            method: JudgingMethod,
+            candidates: Vec<u16>,
+            scores: Vec<u8>,
+            salt: [u8; 32],
+        },
+
+        /// Score a blind judgement that was not revealed by its deadline as a
+        /// miss: last place for a ranking, 1 for a rating. Counted in the stats
+        /// accounts like a revealed judgement.
+        /// Accounts expected:
+        /// 1. `[writable]` Judgement account (PDA)
+        /// 2. `[]` Session account
+        /// 3. `[]` Pool account
+        /// 4. `[signer]` Caller (can be anyone)
+        /// 5. `[]` Clock sysvar
+        /// 6. `[writable]` Viewer stats account (PDA)
+        /// 7. `[writable]` Pool stats account (PDA)
+        ForfeitJudgement {
+            session_id: RecordId,
+            judge: Pubkey,
+        },
+
+        /// Reveal the hidden salt of a tasker-blind session once it is finalized,
+        /// publicly linking its tasking code to the pool and assigned target
+        /// Accounts expected:
+        /// 1. `[writable]` Session account (PDA)
+        /// 2. `[signer]` Session submitter (must match original submitter)
+        RevealTasking {
+            session_id: RecordId,
+            salt: [u8; 32],
+        },
+
+        /// Pre-register an experiment grouping sessions across pools. Sessions
+        /// may only reference it between `start_time` and `end_time`. Plan
+        /// documents are hashed with `canonical_plan_hash`.
+        /// Accounts expected:
+        /// 1. `[writable]` Experiment account (PDA)
+        /// 2. `[signer]` Experiment creator
+        /// 3. `[]` System program
+        CreateExperiment {
+            experiment_id: String,
+            protocol_hash: [u8; 32],
+            analysis_method_hash: [u8; 32],
+            start_time: i64,
+            end_time: i64,
+            allowed_pools: Vec<Pubkey>,
+            planned_sample_size: u32,
+            hypothesis_hash: [u8; 32],
+        },
+
+        /// Replace an experiment's pre-registered plan. Refused once any session
+        /// references the experiment, locking the plan for data collection.
+        /// Accounts expected:
+        /// 1. `[writable]` Experiment account (PDA)
+        /// 2. `[signer]` Experiment creator (must match original creator)
+        /// 3. `[]` Clock sysvar
+        UpdateExperimentPlan {
+            experiment_id: String,
+            protocol_hash: [u8; 32],
+            hypothesis_hash: [u8; 32],
+            analysis_method_hash: [u8; 32],
+        },
+
+        /// Create the program config. Only the program's current upgrade
+        /// authority may do this, and only once. Hand the upgrade authority to the
+        /// config PDA afterwards so upgrades must go through `ExecuteUpgrade`.
+        /// `max_targets_per_pool` must be at least `MIN_MAX_TARGETS_PER_POOL`.
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer, writable]` Program upgrade authority (pays for the account)
+        /// 3. `[]` Program data account of this program
+        /// 4. `[]` System program
+        InitializeConfig {
+            governance: Pubkey,
+            upgrade_delay: i64,
+            admin: Pubkey,
+            max_targets_per_pool: u16,
+        },
+
+        /// Freeze or unfreeze the program. While frozen only finalization
+        /// instructions and the re-anchoring and outcome resolution they
+        /// depend on are accepted.
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Governance authority
+        /// 3. `[]` Clock sysvar
+        SetFrozen {
+            frozen: bool,
+        },
+
+        /// Announce the SHA-256 of a program binary to upgrade to, starting the
+        /// config's upgrade delay. Replaces any pending announcement; a zero hash
+        /// withdraws it.
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Governance authority
+        /// 3. `[]` Clock sysvar
+        AnnounceUpgrade {
+            program_hash: [u8; 32],
+        },
+
+        /// Upgrade the program from a buffer holding the announced binary once
+        /// the upgrade delay has passed. Hashing the buffer is expensive; request
+        /// extra compute units for large programs.
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA, the upgrade authority)
+        /// 2. `[signer]` Governance authority
+        /// 3. `[writable]` Program data account of this program
+        /// 4. `[writable]` This program's account
+        /// 5. `[writable]` Buffer account (its authority must be the config PDA)
+        /// 6. `[writable]` Spill account, receives the buffer's lamports
+        /// 7. `[]` Rent sysvar
+        /// 8. `[]` Clock sysvar
+        /// 9. `[]` BPF upgradeable loader
+        ExecuteUpgrade,
+
+        /// Hand governance to another authority
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Governance authority
+        SetGovernance {
+            governance: Pubkey,
+        },
+
+        /// Pause or resume pool creation (including appending targets) and
+        /// session submission. Finalization is never paused.
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Admin
+        SetPaused {
+            paused: bool,
+        },
+
+        /// Change the limits applied to pools created or extended from now on
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Admin
+        SetLimits {
+            max_targets_per_pool: u16,
+        },
+
+        /// Replace the admin
+        /// Accounts expected:
+        /// 1. `[writable]` Program config account (PDA)
+        /// 2. `[signer]` Governance authority
+        SetAdmin {
+            admin: Pubkey,
+        },
+    }
+}
+
+// Per-session fields of a SubmitSessionBatch instruction
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone)]
+pub struct BatchSessionEntry {
+    pub session_id: RecordId,
+    pub session_media_hash: [u8; 32],
+    pub completed_target_indices: Vec<u16>,
+    pub media_item_count: u16,
+    pub tasking_commitment: [u8; 32],
+}
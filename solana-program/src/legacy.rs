@@ -0,0 +1,208 @@
+// Off-chain decoding of accounts written before layouts were versioned
+//
+// The first deployment stored pools and sessions without a discriminator or
+// layout version, with plain text ids and none of the later fields. The
+// program refuses those accounts (`UnsupportedAccountLayout`), so they have to
+// be redeployed under a new program id, but the tools keep reading them: a
+// legacy account is converted to the current struct with `layout_version` 0
+// and defaults for everything it never recorded. Not compiled into the
+// on-chain program.
+
+use crate::{
+    HashAlgorithm, RecordId, Session, SessionKind, TargetPool, MAX_FINALIZATION_DELAY_SLOTS,
+    MIN_FINALIZATION_DELAY_SLOTS, POOL_DISCRIMINATOR, SESSION_DISCRIMINATOR,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{clock::Slot, pubkey::Pubkey};
+
+/// `TargetPool` as the first deployment stored it
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LegacyTargetPool {
+    pub pool_id: String,
+    pub creator: Pubkey,
+    pub target_count: u16,
+    pub targets: Vec<[u8; 32]>,
+    pub created_at: i64,
+    pub finalized: bool,
+}
+
+/// `Session` as the first deployment stored it. `submission_blockhash` was
+/// supplied by the finalizer and drove the assignment.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LegacySession {
+    pub session_id: String,
+    pub pool_id: String,
+    pub session_media_hash: [u8; 32],
+    pub submission_slot: Slot,
+    pub submission_blockhash: [u8; 32],
+    pub assigned_target_index: u16,
+    pub target_selector_program: Pubkey,
+    pub submitter: Pubkey,
+    pub submitted_at: i64,
+    pub finalized: bool,
+    pub finalized_at: i64,
+    pub completed_target_indices: Vec<u16>,
+}
+
+impl From<LegacyTargetPool> for TargetPool {
+    fn from(legacy: LegacyTargetPool) -> Self {
+        let mut pool = TargetPool::new_standard(
+            RecordId::Text(legacy.pool_id),
+            legacy.creator,
+            legacy.targets,
+            HashAlgorithm::Sha256,
+            MIN_FINALIZATION_DELAY_SLOTS,
+            MAX_FINALIZATION_DELAY_SLOTS,
+            legacy.created_at,
+        );
+        pool.layout_version = 0;
+        pool.target_count = legacy.target_count;
+        pool.finalized = legacy.finalized;
+        pool
+    }
+}
+
+impl From<LegacySession> for Session {
+    fn from(legacy: LegacySession) -> Self {
+        Session {
+            discriminator: SESSION_DISCRIMINATOR,
+            layout_version: 0,
+            session_id: RecordId::Text(legacy.session_id),
+            pool_id: RecordId::Text(legacy.pool_id),
+            session_media_hash: legacy.session_media_hash,
+            submission_slot: legacy.submission_slot,
+            submission_blockhash: legacy.submission_blockhash,
+            assigned_target_index: legacy.assigned_target_index,
+            target_selector_program: legacy.target_selector_program,
+            submitter: legacy.submitter,
+            submitted_at: legacy.submitted_at,
+            finalized: legacy.finalized,
+            finalized_at: legacy.finalized_at,
+            completed_target_indices: legacy.completed_target_indices,
+            media_hash_algorithm: HashAlgorithm::Sha256,
+            media_item_count: 1,
+            media_history: Vec::new(),
+            finalization_reward: 0,
+            // The assignment came from `submission_blockhash`, not a slot hash
+            entropy_slot: 0,
+            late_finalized: false,
+            reanchor_slot: 0,
+            reanchor_count: 0,
+            min_finalization_delay: MIN_FINALIZATION_DELAY_SLOTS,
+            max_finalization_delay: MAX_FINALIZATION_DELAY_SLOTS,
+            kind: SessionKind::Standard,
+            candidates: Vec::new(),
+            viewer: legacy.submitter,
+            tasking_commitment: [0; 32],
+            tasking_reference: [0; 8],
+            tasking_revealed: false,
+            tasking_salt: [0; 32],
+            experiment: Pubkey::default(),
+            decoy_count: 0,
+            designated_judge: Pubkey::default(),
+        }
+    }
+}
+
+/// Decode a pool account in the current layout or, failing that, the legacy
+/// one
+pub fn decode_pool(data: &[u8]) -> Option<TargetPool> {
+    if data.starts_with(&POOL_DISCRIMINATOR) {
+        return TargetPool::unpack(data).ok();
+    }
+    LegacyTargetPool::try_from_slice(data).ok().map(TargetPool::from)
+}
+
+/// Decode a session account in the current layout or, failing that, the
+/// legacy one
+pub fn decode_session(data: &[u8]) -> Option<Session> {
+    if data.starts_with(&SESSION_DISCRIMINATOR) {
+        return Session::unpack(data).ok();
+    }
+    LegacySession::try_from_slice(data).ok().map(Session::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool_address, session_address, test_fixtures, ACCOUNT_LAYOUT_VERSION};
+
+    fn legacy_pool() -> LegacyTargetPool {
+        LegacyTargetPool {
+            pool_id: "pool".to_string(),
+            creator: Pubkey::new_unique(),
+            target_count: 2,
+            targets: vec![[1; 32], [2; 32]],
+            created_at: 1_700_000_000,
+            finalized: true,
+        }
+    }
+
+    fn legacy_session() -> LegacySession {
+        LegacySession {
+            session_id: "session".to_string(),
+            pool_id: "pool".to_string(),
+            session_media_hash: [3; 32],
+            submission_slot: 100,
+            submission_blockhash: [4; 32],
+            assigned_target_index: 1,
+            target_selector_program: Pubkey::default(),
+            submitter: Pubkey::new_unique(),
+            submitted_at: 1_700_000_100,
+            finalized: true,
+            finalized_at: 1_700_000_200,
+            completed_target_indices: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_current_layout_round_trips() {
+        let pool = test_fixtures::pool(4);
+        let decoded = decode_pool(&pool.try_to_vec().unwrap()).unwrap();
+        assert_eq!(decoded.layout_version, ACCOUNT_LAYOUT_VERSION);
+        assert_eq!(decoded.targets, pool.targets);
+
+        let session = test_fixtures::session(RecordId::from("session"), &pool);
+        let decoded = decode_session(&session.try_to_vec().unwrap()).unwrap();
+        assert_eq!(decoded.layout_version, ACCOUNT_LAYOUT_VERSION);
+        assert_eq!(decoded.session_id, session.session_id);
+    }
+
+    #[test]
+    fn test_legacy_accounts_decode_off_chain_only() {
+        let program_id = Pubkey::new_unique();
+        let data = legacy_pool().try_to_vec().unwrap();
+        assert!(TargetPool::unpack(&data).is_err());
+        let pool = decode_pool(&data).unwrap();
+        assert_eq!(pool.layout_version, 0);
+        assert_eq!(pool.targets, vec![[1; 32], [2; 32]]);
+        assert!(pool.finalized);
+        // Text ids were seeded the same way, so legacy accounts keep their address
+        assert_eq!(pool_address(&program_id, &pool.pool_id), pool_address(&program_id, &RecordId::from("pool")));
+
+        let data = legacy_session().try_to_vec().unwrap();
+        assert!(Session::unpack(&data).is_err());
+        let session = decode_session(&data).unwrap();
+        assert_eq!(session.layout_version, 0);
+        assert_eq!(session.session_id, RecordId::from("session"));
+        assert_eq!(session.assigned_target_index, 1);
+        assert_eq!(session.submission_blockhash, [4; 32]);
+        assert_eq!(session.viewer, session.submitter);
+        assert_eq!(
+            session_address(&program_id, &session.session_id),
+            session_address(&program_id, &RecordId::from("session"))
+        );
+    }
+
+    #[test]
+    fn test_layouts_are_not_confused() {
+        let pool = test_fixtures::pool(4);
+        let session = test_fixtures::session(RecordId::from("session"), &pool);
+        assert!(decode_session(&pool.try_to_vec().unwrap()).is_none());
+        assert!(decode_pool(&session.try_to_vec().unwrap()).is_none());
+
+        let mut data = pool.try_to_vec().unwrap();
+        data[8] = ACCOUNT_LAYOUT_VERSION + 1;
+        assert!(decode_pool(&data).is_none());
+    }
+}
@@ -1,98 +1,287 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use bs58;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use borsh::schema::{Declaration, Definition, Fields};
+use std::collections::HashMap;
+use std::fmt;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
     entrypoint::ProgramResult,
     msg,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
-    sysvar::Sysvar,
+    sysvar::{self, Sysvar},
     clock::Clock,
+    slot_hashes,
     slot_history::Slot,
-    hash::hash,
+    hash::{hash, hashv},
 };
 
+mod instruction;
+pub use instruction::{BatchSessionEntry, RemoteViewingInstruction};
+
+#[cfg(not(target_os = "solana"))]
+pub mod manifest;
+#[cfg(not(target_os = "solana"))]
+pub mod verify;
+#[cfg(not(target_os = "solana"))]
+pub mod stats;
+#[cfg(not(target_os = "solana"))]
+pub mod idl;
+#[cfg(not(target_os = "solana"))]
+pub mod size;
+#[cfg(not(target_os = "solana"))]
+pub mod pool_builder;
+#[cfg(not(target_os = "solana"))]
+pub mod reconcile;
+#[cfg(not(target_os = "solana"))]
+pub mod report;
+#[cfg(not(target_os = "solana"))]
+pub mod legacy;
+#[cfg(test)]
+mod test_fixtures;
+
+/// Lowercase hex, as the off-chain tools print hashes and account data
+#[cfg(not(target_os = "solana"))]
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 // Define the program ID - this will be replaced with the actual program ID after deployment
 solana_program::declare_id!("AgdxtGStJsyCZAZvZChtnTtaK774e3Yf2QWdq8gSfLuc");
 
-// Program instructions
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub enum RemoteViewingInstruction {
-    /// Create a new target pool
-    /// Accounts expected:
-    /// 1. `[writable]` Pool account (PDA)
-    /// 2. `[signer]` Pool creator
-    /// 3. `[]` System program
-    CreateTargetPool {
-        pool_id: String,
-        target_hashes: Vec<[u8; 32]>,
-    },
-    
-    /// Submit a remote viewing session (initial submission without target assignment)
-    /// Accounts expected:
-    /// 1. `[writable]` Session account (PDA)
-    /// 2. `[]` Pool account
-    /// 3. `[signer]` Session submitter
-    /// 4. `[]` System program
-    /// 5. `[]` Clock sysvar
-    SubmitSession {
-        session_id: String,
-        pool_id: String,
-        session_media_hash: [u8; 32],
-        target_selector_program: Pubkey,
-        completed_target_indices: Vec<u16>,
-    },
-    
-    /// Finalize a session with target assignment based on submission block
-    /// Accounts expected:
-    /// 1. `[writable]` Session account (PDA)
-    /// 2. `[]` Pool account
-    /// 3. `[signer]` Caller (can be anyone)
-    /// 4. `[]` Clock sysvar
-    FinalizeSession {
-        session_id: String,
-        submission_blockhash: String, // Change to base58 string
-        completed_target_indices: Vec<u16>,
-    },
-    
-    /// Append targets to an existing pool
-    /// Accounts expected:
-    /// 1. `[writable]` Pool account (PDA)
-    /// 2. `[signer]` Pool creator (must match original creator)
-    /// 3. `[]` System program
-    AppendTargetsToPool {
-        pool_id: String,
-        target_hashes: Vec<[u8; 32]>,
-    },
-    
-    /// Finalize a pool to prevent further target additions
-    /// Accounts expected:
-    /// 1. `[writable]` Pool account (PDA)
-    /// 2. `[signer]` Pool creator (must match original creator)
-    FinalizePool {
-        pool_id: String,
-    },
+// Identifier of a pool or session. Text ids are bounded by `MAX_ID_LEN` and
+// hashed for PDA seeds; fixed ids (e.g. UUID bytes) are used as the seed
+// directly and take less space in every account that stores them.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RecordId {
+    Text(String),
+    Fixed([u8; 32]),
+}
+
+impl RecordId {
+    /// Fixed id holding a 16-byte UUID, zero-padded to 32 bytes
+    pub fn from_uuid(uuid: [u8; 16]) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&uuid);
+        RecordId::Fixed(bytes)
+    }
+
+    /// 32-byte PDA seed for this id
+    pub fn seed(&self) -> [u8; 32] {
+        match self {
+            RecordId::Text(id) => hash(id.as_bytes()).to_bytes(),
+            RecordId::Fixed(bytes) => *bytes,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            RecordId::Text(id) => !id.is_empty() && id.len() <= MAX_ID_LEN,
+            RecordId::Fixed(bytes) => *bytes != [0; 32],
+        }
+    }
+}
+
+// Written out by hand: the derive emits a helper struct per tuple variant
+// whose field is never read, which trips `dead_code`
+impl BorshSchema for RecordId {
+    fn declaration() -> Declaration {
+        "RecordId".to_string()
+    }
+
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let variants = [("Text", String::declaration()), ("Fixed", <[u8; 32]>::declaration())]
+            .into_iter()
+            .map(|(name, field)| {
+                let declaration = format!("RecordId{}", name);
+                let fields = Fields::UnnamedFields(vec![field]);
+                Self::add_definition(declaration.clone(), Definition::Struct { fields }, definitions);
+                (name.to_string(), declaration)
+            })
+            .collect();
+        Self::add_definition(Self::declaration(), Definition::Enum { variants }, definitions);
+        String::add_definitions_recursively(definitions);
+        <[u8; 32]>::add_definitions_recursively(definitions);
+    }
+}
+
+impl From<&str> for RecordId {
+    fn from(id: &str) -> Self {
+        RecordId::Text(id.to_string())
+    }
+}
+
+// Fixed ids print as a UUID when they hold one, otherwise as hex
+impl fmt::Display for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordId::Text(id) => f.write_str(id),
+            RecordId::Fixed(bytes) if bytes[16..] == [0; 16] => {
+                for (i, byte) in bytes[..16].iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        f.write_str("-")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            RecordId::Fixed(bytes) => bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte)),
+        }
+    }
+}
+
+// Algorithm used to produce a 32-byte content hash (target images or session media)
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Keccak256,
+    Blake3,
+    /// Perceptual image hash, left-aligned and zero-padded to 32 bytes.
+    /// Matched by Hamming distance rather than equality.
+    PerceptualHash,
+}
+
+// How a pool's sessions get their target
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionKind {
+    /// Target chosen from the hash of the slot the minimum delay after submission
+    #[default]
+    Standard,
+    /// Associative Remote Viewing: target is the one associated with the
+    /// outcome later recorded by the pool's resolver
+    AssociativePrediction,
+}
+
+/// First bytes of every pool account. Accounts written before layouts were
+/// versioned start with the u32 length of their text id instead, whose upper
+/// two bytes are always zero, so they can never match a discriminator.
+pub const POOL_DISCRIMINATOR: [u8; 8] = *b"rv-pool\0";
+/// First bytes of every session account
+pub const SESSION_DISCRIMINATOR: [u8; 8] = *b"rv-sessn";
+/// Layout of the pool and session accounts this program reads and writes.
+/// Version 0 is reserved for accounts decoded from the legacy layout
+/// off-chain (see `legacy`); the program itself refuses anything else.
+pub const ACCOUNT_LAYOUT_VERSION: u8 = 1;
+
+/// Check the discriminator and layout version at the start of `data`
+fn check_layout(data: &[u8], discriminator: &[u8; 8]) -> ProgramResult {
+    if data.len() < 9 || data[..8] != discriminator[..] || data[8] != ACCOUNT_LAYOUT_VERSION {
+        return Err(RemoteViewingError::UnsupportedAccountLayout.into());
+    }
+    Ok(())
 }
 
 // State structures
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 pub struct TargetPool {
-    pub pool_id: String,
+    pub discriminator: [u8; 8], // POOL_DISCRIMINATOR
+    pub layout_version: u8, // ACCOUNT_LAYOUT_VERSION, 0 for a decoded legacy account
+    pub pool_id: RecordId,
     pub creator: Pubkey,
     pub target_count: u16,
     pub targets: Vec<[u8; 32]>,
     pub created_at: i64,
     pub finalized: bool, // True when pool is closed to further additions
+    pub hash_algorithm: HashAlgorithm, // Algorithm used for every entry in `targets`
+    pub min_finalization_delay: u64, // Slots after submission before finalization opens
+    pub max_finalization_delay: u64, // Slots after submission before finalization closes
+    pub kind: SessionKind,
+    pub outcome_labels: Vec<String>, // Parallel to `targets` for associative pools, empty otherwise
+    pub resolver: Pubkey, // Account allowed to resolve the outcome (associative pools only)
+    pub resolved_outcome: u16, // u16::MAX until resolved
+    pub resolved_slot: Slot,
+    pub event_time: i64, // Unix time the outcome becomes known (associative pools only)
+    pub submission_deadline: i64, // Sessions are refused from this unix time on (associative pools only)
+}
+
+impl TargetPool {
+    /// Decode a pool account, refusing legacy or foreign layouts
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        check_layout(data, &POOL_DISCRIMINATOR)?;
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    /// A standard pool as created by `CreateTargetPool`: open for appends
+    /// until finalized, with targets drawn from slot hashes
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_standard(
+        pool_id: RecordId,
+        creator: Pubkey,
+        targets: Vec<[u8; 32]>,
+        hash_algorithm: HashAlgorithm,
+        min_finalization_delay: u64,
+        max_finalization_delay: u64,
+        created_at: i64,
+    ) -> Self {
+        TargetPool {
+            discriminator: POOL_DISCRIMINATOR,
+            layout_version: ACCOUNT_LAYOUT_VERSION,
+            pool_id,
+            creator,
+            target_count: targets.len() as u16,
+            targets,
+            created_at,
+            finalized: false,
+            hash_algorithm,
+            min_finalization_delay,
+            max_finalization_delay,
+            kind: SessionKind::Standard,
+            outcome_labels: Vec::new(),
+            resolver: Pubkey::default(),
+            resolved_outcome: u16::MAX,
+            resolved_slot: 0,
+            event_time: 0,
+            submission_deadline: 0,
+        }
+    }
+
+    /// An associative pool as created by `CreateAssociativePool`: one target
+    /// per outcome, fixed from the start
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_associative(
+        pool_id: RecordId,
+        creator: Pubkey,
+        targets: Vec<[u8; 32]>,
+        hash_algorithm: HashAlgorithm,
+        outcome_labels: Vec<String>,
+        resolver: Pubkey,
+        event_time: i64,
+        submission_deadline: i64,
+        created_at: i64,
+    ) -> Self {
+        TargetPool {
+            discriminator: POOL_DISCRIMINATOR,
+            layout_version: ACCOUNT_LAYOUT_VERSION,
+            pool_id,
+            creator,
+            target_count: targets.len() as u16,
+            targets,
+            created_at,
+            finalized: true,
+            hash_algorithm,
+            min_finalization_delay: MIN_FINALIZATION_DELAY_SLOTS,
+            max_finalization_delay: MAX_FINALIZATION_DELAY_SLOTS,
+            kind: SessionKind::AssociativePrediction,
+            outcome_labels,
+            resolver,
+            resolved_outcome: u16::MAX,
+            resolved_slot: 0,
+            event_time,
+            submission_deadline,
+        }
+    }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 pub struct Session {
-    pub session_id: String,
-    pub pool_id: String,
+    pub discriminator: [u8; 8], // SESSION_DISCRIMINATOR
+    pub layout_version: u8, // ACCOUNT_LAYOUT_VERSION, 0 for a decoded legacy account
+    pub session_id: RecordId,
+    pub pool_id: RecordId,
     pub session_media_hash: [u8; 32],
     pub submission_slot: Slot,
     pub submission_blockhash: [u8; 32],
@@ -103,10 +292,301 @@ pub struct Session {
     pub finalized: bool,
     pub finalized_at: i64,
     pub completed_target_indices: Vec<u16>,
+    pub media_hash_algorithm: HashAlgorithm,
+    pub media_item_count: u16, // 1 = single media hash, >1 = manifest Merkle root
+    pub media_history: Vec<MediaAmendment>, // Append-only, oldest first
+    pub finalization_reward: u64, // Lamports paid to whoever finalizes the session
+    pub entropy_slot: Slot, // Slot whose hash draws the target: submission slot + min delay, replaced by a late or associative draw
+    pub late_finalized: bool, // True when finalized via the re-anchor fallback
+    pub reanchor_slot: Slot, // Re-anchor slot, 0 if never re-anchored
+    pub reanchor_count: u16,
+    pub min_finalization_delay: u64, // Copied from the pool at submission
+    pub max_finalization_delay: u64, // Copied from the pool at submission
+    pub kind: SessionKind, // Copied from the pool at submission
+    pub candidates: Vec<u16>, // Shuffled target + decoys for judging, drawn at finalization
+    pub decoy_count: u8, // Decoys drawn alongside the target at finalization, 0 for none
+    pub viewer: Pubkey, // Identity whose track record the session counts towards
+    pub designated_judge: Pubkey, // First judge to commit blind; only their judgement is in the stats, default if none
+    pub tasking_commitment: [u8; 32], // Hash of the hidden tasking salt, zero when not tasker-blind
+    pub tasking_reference: [u8; 8], // Opaque reference handed to the viewer instead of the pool id
+    pub tasking_revealed: bool,
+    pub tasking_salt: [u8; 32], // Zero until revealed
+    pub experiment: Pubkey, // Experiment account the session counts towards, default if none
+}
+
+impl Session {
+    /// Decode a session account, refusing legacy or foreign layouts
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        check_layout(data, &SESSION_DISCRIMINATOR)?;
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    /// A session as recorded at submission, before its target is drawn
+    /// (`assigned_target_index` is `u16::MAX` until finalization). The
+    /// finalization bounds and kind are copied from `pool`, and the tasking
+    /// reference is derived from the session's own `address`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_submitted(
+        address: &Pubkey,
+        session_id: RecordId,
+        pool: &TargetPool,
+        submitter: Pubkey,
+        viewer: Pubkey,
+        clock: &Clock,
+        session_media_hash: [u8; 32],
+        media_hash_algorithm: HashAlgorithm,
+        media_item_count: u16,
+        completed_target_indices: Vec<u16>,
+        target_selector_program: Pubkey,
+        finalization_reward: u64,
+        decoy_count: u8,
+        tasking_commitment: [u8; 32],
+        experiment: Pubkey,
+    ) -> Self {
+        Session {
+            discriminator: SESSION_DISCRIMINATOR,
+            layout_version: ACCOUNT_LAYOUT_VERSION,
+            session_id,
+            pool_id: pool.pool_id.clone(),
+            session_media_hash,
+            submission_slot: clock.slot,
+            submission_blockhash: [0; 32],
+            assigned_target_index: u16::MAX,
+            target_selector_program,
+            submitter,
+            submitted_at: clock.unix_timestamp,
+            finalized: false,
+            finalized_at: 0,
+            completed_target_indices,
+            media_hash_algorithm,
+            media_item_count,
+            media_history: Vec::new(),
+            finalization_reward,
+            entropy_slot: clock.slot + pool.min_finalization_delay,
+            late_finalized: false,
+            reanchor_slot: 0,
+            reanchor_count: 0,
+            min_finalization_delay: pool.min_finalization_delay,
+            max_finalization_delay: pool.max_finalization_delay,
+            kind: pool.kind,
+            candidates: Vec::new(),
+            decoy_count,
+            viewer,
+            designated_judge: Pubkey::default(),
+            tasking_commitment,
+            tasking_reference: tasking_reference(address, &tasking_commitment),
+            tasking_revealed: false,
+            tasking_salt: [0; 32],
+            experiment,
+        }
+    }
+
+    // Space the account needs once finalized, including the candidate list
+    // drawn then. Its rent is escrowed at submission so permissionless
+    // finalizers never pay for it.
+    fn finalized_space(&self) -> Result<usize, ProgramError> {
+        let candidate_count = if self.decoy_count == 0 { 0 } else { self.decoy_count as usize + 1 };
+        Ok(self.try_to_vec()?.len() + 2 * candidate_count.saturating_sub(self.candidates.len()))
+    }
+}
+
+// A superseded media commitment, kept so amendments stay publicly visible
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct MediaAmendment {
+    pub previous_media_hash: [u8; 32],
+    pub previous_media_item_count: u16,
+    pub amended_slot: Slot,
+}
+
+// How a judge scores candidate targets against a session
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JudgingMethod {
+    /// Scores are ranks 1..=k (1 = best match) over the k candidates
+    Rank,
+    /// Scores are SRI-style ratings from 1 (no match) to 7 (excellent match)
+    Rating,
+}
+
+// Highest score on the SRI-style rating scale
+pub const MAX_RATING: u8 = 7;
+
+// Grants a judge the right to score a pool's sessions
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+pub struct JudgeAuthorization {
+    pub pool_id: RecordId,
+    pub judge: Pubkey,
+    pub authorized_at: i64,
+}
+
+// One judge's scores for one session
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+pub struct Judgement {
+    pub session: Pubkey, // Session account address
+    pub judge: Pubkey,
+    pub commitment: [u8; 32], // Zero unless judged blind
+    pub committed_slot: Slot,
+    pub method: JudgingMethod,
+    pub candidates: Vec<u16>, // Target indices judged, in the order scored
+    pub scores: Vec<u8>, // Parallel to `candidates`
+    pub target_score: u8, // Score given to the assigned target, 0 until judged
+    pub judged: bool,
+    pub judged_at: i64,
+    pub forfeited: bool, // Blind judgement not revealed in time, scored as a miss
+}
+
+// Pre-registered grouping of sessions across pools
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+pub struct Experiment {
+    pub experiment_id: String,
+    pub creator: Pubkey,
+    pub protocol_hash: [u8; 32], // Hash of the protocol description document
+    pub analysis_method_hash: [u8; 32], // Hash of the planned analysis method
+    pub start_time: i64,
+    pub end_time: i64,
+    pub allowed_pools: Vec<Pubkey>, // Pool account addresses sessions may use
+    pub planned_sample_size: u32,
+    pub session_count: u32, // Sessions submitted under the experiment so far
+    pub created_at: i64,
+    pub hypothesis_hash: [u8; 32], // Hash of the hypothesis statement
+    pub plan_updated_at: i64, // Last plan change, frozen once sessions exist
+}
+
+// Program-wide governance state, a single PDA. Once the program's upgrade
+// authority is handed to this account, upgrades only happen through
+// ExecuteUpgrade, after being announced here for `upgrade_delay` seconds.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramConfig {
+    pub governance: Pubkey, // Authority allowed to freeze, announce and execute upgrades
+    pub upgrade_delay: i64, // Seconds between announcing an upgrade and executing it
+    pub frozen: bool, // Only finalization, re-anchoring and outcome resolution are accepted while set
+    pub frozen_changed_at: i64,
+    pub pending_upgrade_hash: [u8; 32], // SHA-256 of the announced program binary, zero if none
+    pub upgrade_announced_at: i64,
+    pub upgrade_effective_at: i64, // Earliest time the announced upgrade may execute
+    pub last_upgrade_hash: [u8; 32], // Zero until an upgrade has executed
+    pub last_upgraded_at: i64,
+    pub admin: Pubkey, // Operational authority: pausing and limits
+    pub paused: bool, // Pool creation and session submission are refused while set
+    pub max_targets_per_pool: u16,
+}
+
+// Track-record counters, kept both overall and per pool
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatCounters {
+    pub sessions_submitted: u32,
+    pub sessions_finalized: u32,
+    pub sessions_late_finalized: u32, // Kept out of every other counter
+    pub ranked_judgements: u32,
+    pub rank_sum: u64, // Sum of the target's rank across ranked judgements
+    pub rank_candidate_sum: u64, // Sum of candidate counts, for the expected rank
+    pub first_place_hits: u32, // Ranked judgements placing the target first
+    pub rated_judgements: u32,
+    pub rating_sum: u64,
+}
+
+impl StatCounters {
+    // Fold one judgement's score for the assigned target into the counters
+    fn record_judgement(&mut self, method: JudgingMethod, target_score: u8, candidate_count: usize) {
+        match method {
+            JudgingMethod::Rank => {
+                self.ranked_judgements += 1;
+                self.rank_sum += target_score as u64;
+                self.rank_candidate_sum += candidate_count as u64;
+                if target_score == 1 {
+                    self.first_place_hits += 1;
+                }
+            }
+            JudgingMethod::Rating => {
+                self.rated_judgements += 1;
+                self.rating_sum += target_score as u64;
+            }
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct PoolStatCounters {
+    pub pool: Pubkey, // Pool account address
+    pub counters: StatCounters,
+}
+
+// On-chain track record of one viewer, updated only by the program
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+pub struct ViewerStats {
+    pub viewer: Pubkey,
+    pub totals: StatCounters,
+    pub pools: Vec<PoolStatCounters>,
+}
+
+// Aggregate evidence for one pool, created alongside it and updated only by
+// the program. The rank sums are kept in integer form so the null
+// distribution of the sum of ranks can be rebuilt exactly off-chain.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct PoolStats {
+    pub pool: Pubkey, // Pool account address
+    pub sessions_finalized: u32,
+    pub sessions_late_finalized: u32, // Not in the assignment counts or judgement sums
+    pub assignment_counts: Vec<u32>, // Finalized sessions per target index
+    pub ranked_judgements: u32,
+    pub rank_sum: u64,
+    pub rank_mean_sum_x2: u64, // Sum of (n + 1): twice the expected rank sum by chance
+    pub rank_variance_sum_x12: u64, // Sum of (n^2 - 1): 12x the rank sum's variance by chance
+    pub first_place_hits: u32,
+    pub rated_judgements: u32,
+    pub rating_sum: u64,
+}
+
+impl PoolStats {
+    /// Empty stats for a pool of `target_count` targets
+    pub fn new(pool: Pubkey, target_count: usize) -> Self {
+        PoolStats {
+            pool,
+            sessions_finalized: 0,
+            sessions_late_finalized: 0,
+            assignment_counts: vec![0; target_count],
+            ranked_judgements: 0,
+            rank_sum: 0,
+            rank_mean_sum_x2: 0,
+            rank_variance_sum_x12: 0,
+            first_place_hits: 0,
+            rated_judgements: 0,
+            rating_sum: 0,
+        }
+    }
+
+    fn record_assignment(&mut self, target_index: u16) -> ProgramResult {
+        let count = self
+            .assignment_counts
+            .get_mut(target_index as usize)
+            .ok_or(RemoteViewingError::PoolStatsMismatch)?;
+        *count += 1;
+        self.sessions_finalized += 1;
+        Ok(())
+    }
+
+    // `n` is the number of candidates the target was ranked among
+    fn record_judgement(&mut self, method: JudgingMethod, target_score: u8, candidate_count: usize) {
+        match method {
+            JudgingMethod::Rank => {
+                let n = candidate_count as u64;
+                self.ranked_judgements += 1;
+                self.rank_sum += target_score as u64;
+                self.rank_mean_sum_x2 += n + 1;
+                self.rank_variance_sum_x12 += n * n - 1;
+                if target_score == 1 {
+                    self.first_place_hits += 1;
+                }
+            }
+            JudgingMethod::Rating => {
+                self.rated_judgements += 1;
+                self.rating_sum += target_score as u64;
+            }
+        }
+    }
 }
 
 // Error types
-#[derive(Debug, Clone)]
+#[derive(BorshSchema, Debug, Clone)]
 pub enum RemoteViewingError {
     InvalidInstruction,
     InvalidPoolId,
@@ -122,6 +602,50 @@ pub enum RemoteViewingError {
     InvalidSlotHash,
     AllTargetsCompleted,
     PoolAlreadyFinalized,
+    InvalidMediaItemCount,
+    AmendmentWindowClosed,
+    InvalidBatchSize,
+    SessionSlotMismatch,
+    FinalizationWindowOpen,
+    SessionNotReanchored,
+    InvalidFinalizationWindow,
+    InvalidSessionKind,
+    InvalidOutcome,
+    OutcomeNotResolved,
+    OutcomeAlreadyResolved,
+    JudgeNotAuthorized,
+    InvalidScores,
+    JudgementAlreadySubmitted,
+    CommitmentMismatch,
+    SessionNotFinalized,
+    RevealDeadlinePassed,
+    RevealDeadlineNotReached,
+    InvalidDecoyCount,
+    NotEnoughCandidates,
+    ViewerStatsMismatch,
+    PoolStatsMismatch,
+    NotTaskerBlind,
+    TaskingAlreadyRevealed,
+    InvalidExperiment,
+    ExperimentMismatch,
+    OutsideExperimentWindow,
+    PoolNotInExperiment,
+    PlanLocked,
+    ProgramFrozen,
+    ConfigMismatch,
+    NotGovernance,
+    InvalidUpgradeDelay,
+    UpgradeNotAnnounced,
+    UpgradeTimelocked,
+    UpgradeHashMismatch,
+    ProgramPaused,
+    NotAdmin,
+    InvalidLimits,
+    InvalidResolver,
+    InvalidEventTime,
+    SubmissionDeadlinePassed,
+    EventNotReached,
+    UnsupportedAccountLayout,
 }
 
 impl From<RemoteViewingError> for ProgramError {
@@ -130,8 +654,97 @@ impl From<RemoteViewingError> for ProgramError {
     }
 }
 
+// Longest text id (pool, session or experiment) in bytes
+pub const MAX_ID_LEN: usize = 64;
+
+// Smallest finalization delay a pool may configure. Ensures the submission
+// block is settled before its hash is used.
+pub const MIN_FINALIZATION_DELAY_SLOTS: u64 = 2;
+
+// Largest finalization window a pool may configure. Keeps the anchor slot
+// within SlotHashes retention so its hash can still be read.
+pub const MAX_FINALIZATION_DELAY_SLOTS: u64 = slot_hashes::MAX_ENTRIES as u64 - 1;
+
+// Seconds after finalization within which a blind judgement must be
+// revealed. Unrevealed ones can then be forfeited and count as misses, so a
+// judge cannot withhold the scores that turned out badly.
+pub const JUDGEMENT_REVEAL_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+// Bounds on an associative pool's outcomes, keeping the pool account small
+pub const MAX_OUTCOMES: usize = 32;
+pub const MAX_OUTCOME_LABEL_LEN: usize = 64;
+
+// Maximum decoys per session, bounding the compute spent on selection
+pub const MAX_DECOY_COUNT: u8 = 15;
+
+// Decoys a standard session needs to be judged. Judging is against the drawn
+// candidates, and a lone target would score a hit every time. Associative
+// sessions are judged among all their pool's outcomes instead.
+pub const MIN_JUDGING_DECOYS: u8 = 1;
+
+// Maximum sessions per batch instruction, bounded by transaction account limits
+pub const MAX_SESSION_BATCH_SIZE: usize = 20;
+
+// Shortest notice, in seconds, the config may require before an upgrade
+pub const MIN_UPGRADE_DELAY: i64 = 3 * 24 * 60 * 60;
+
+// Target limit per pool until a config sets its own
+pub const DEFAULT_MAX_TARGETS_PER_POOL: u16 = 10000;
+
+// Lowest target limit a config may set; associative pools need two outcomes
+pub const MIN_MAX_TARGETS_PER_POOL: u16 = 2;
+
+// Account addresses, derived the same way as in the processors below, for
+// clients building instructions
+
+/// Pool account of `pool_id`
+pub fn pool_address(program_id: &Pubkey, pool_id: &RecordId) -> Pubkey {
+    Pubkey::find_program_address(&[b"target_pool", pool_id.seed().as_ref()], program_id).0
+}
+
+/// Session account of `session_id`
+pub fn session_address(program_id: &Pubkey, session_id: &RecordId) -> Pubkey {
+    Pubkey::find_program_address(&[b"session", session_id.seed().as_ref()], program_id).0
+}
+
+/// Stats account of the pool at `pool`
+pub fn pool_stats_address(program_id: &Pubkey, pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_stats", pool.as_ref()], program_id).0
+}
+
+/// Stats account of `viewer`
+pub fn viewer_stats_address(program_id: &Pubkey, viewer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"viewer_stats", viewer.as_ref()], program_id).0
+}
+
+/// Experiment account of `experiment_id`
+pub fn experiment_address(program_id: &Pubkey, experiment_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"experiment", hash(experiment_id.as_bytes()).as_ref()], program_id).0
+}
+
+/// The program config account
+pub fn config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+/// Program data account holding this program's upgrade authority
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Authorization of `judge` to score sessions in the pool at `pool`
+pub fn judge_authorization_address(program_id: &Pubkey, pool: &Pubkey, judge: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"judge", pool.as_ref(), judge.as_ref()], program_id).0
+}
+
+/// Judgement by `judge` of the session at `session`
+pub fn judgement_address(program_id: &Pubkey, session: &Pubkey, judge: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"judgement", session.as_ref(), judge.as_ref()], program_id).0
+}
+
 // Entry point
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -141,9 +754,74 @@ pub fn process_instruction(
     let instruction = RemoteViewingInstruction::try_from_slice(instruction_data)
         .map_err(|_| RemoteViewingError::InvalidInstruction)?;
 
+    // Finalization must never be blocked, and governance has to be able to
+    // unfreeze; everything else carries the config account last
+    let (config, accounts) = match instruction {
+        RemoteViewingInstruction::FinalizeSession { .. }
+        | RemoteViewingInstruction::FinalizePool { .. }
+        | RemoteViewingInstruction::FinalizeSessionsBatch { .. }
+        | RemoteViewingInstruction::FinalizeLateSession { .. }
+        | RemoteViewingInstruction::FinalizeAssociativeSession { .. }
+        | RemoteViewingInstruction::InitializeConfig { .. }
+        | RemoteViewingInstruction::SetFrozen { .. }
+        | RemoteViewingInstruction::AnnounceUpgrade { .. }
+        | RemoteViewingInstruction::ExecuteUpgrade
+        | RemoteViewingInstruction::SetGovernance { .. }
+        | RemoteViewingInstruction::SetPaused { .. }
+        | RemoteViewingInstruction::SetLimits { .. }
+        | RemoteViewingInstruction::SetAdmin { .. } => (None, accounts),
+        _ => split_config(program_id, accounts)?,
+    };
+
+    if let Some(config) = &config {
+        // Late and associative sessions can only be finalized after a
+        // re-anchor or a resolved outcome, so those stay open as well
+        if config.frozen
+            && !matches!(
+                instruction,
+                RemoteViewingInstruction::ReanchorSession { .. } | RemoteViewingInstruction::ResolveOutcome { .. }
+            )
+        {
+            msg!("Program is frozen; only finalization is accepted");
+            return Err(RemoteViewingError::ProgramFrozen.into());
+        }
+        // Pausing only stops new pools and sessions; existing ones carry on
+        if config.paused
+            && matches!(
+                instruction,
+                RemoteViewingInstruction::CreateTargetPool { .. }
+                    | RemoteViewingInstruction::AppendTargetsToPool { .. }
+                    | RemoteViewingInstruction::CreateAssociativePool { .. }
+                    | RemoteViewingInstruction::SubmitSession { .. }
+                    | RemoteViewingInstruction::SubmitSessionBatch { .. }
+            )
+        {
+            msg!("Pool creation and session submission are paused");
+            return Err(RemoteViewingError::ProgramPaused.into());
+        }
+    }
+    let max_targets_per_pool = config
+        .as_ref()
+        .map_or(DEFAULT_MAX_TARGETS_PER_POOL, |config| config.max_targets_per_pool);
+
     match instruction {
-        RemoteViewingInstruction::CreateTargetPool { pool_id, target_hashes } => {
-            process_create_target_pool(program_id, accounts, pool_id, target_hashes)
+        RemoteViewingInstruction::CreateTargetPool {
+            pool_id,
+            target_hashes,
+            hash_algorithm,
+            min_finalization_delay,
+            max_finalization_delay,
+        } => {
+            process_create_target_pool(
+                program_id,
+                accounts,
+                pool_id,
+                target_hashes,
+                hash_algorithm,
+                min_finalization_delay,
+                max_finalization_delay,
+                max_targets_per_pool,
+            )
         }
         RemoteViewingInstruction::SubmitSession {
             session_id,
@@ -151,6 +829,12 @@ pub fn process_instruction(
             session_media_hash,
             target_selector_program,
             completed_target_indices,
+            media_hash_algorithm,
+            media_item_count,
+            finalization_reward,
+            decoy_count,
+            tasking_commitment,
+            experiment,
         } => {
             process_submit_session(
                 program_id,
@@ -160,56 +844,206 @@ pub fn process_instruction(
                 session_media_hash,
                 target_selector_program,
                 completed_target_indices,
+                media_hash_algorithm,
+                media_item_count,
+                finalization_reward,
+                decoy_count,
+                tasking_commitment,
+                experiment,
             )
         }
-        RemoteViewingInstruction::FinalizeSession { session_id, submission_blockhash, completed_target_indices } => {
-            process_finalize_session(program_id, accounts, session_id, submission_blockhash, completed_target_indices)
+        RemoteViewingInstruction::FinalizeSession { session_id } => {
+            process_finalize_session(program_id, accounts, session_id)
         }
         RemoteViewingInstruction::AppendTargetsToPool { pool_id, target_hashes } => {
-            process_append_targets_to_pool(program_id, accounts, pool_id, target_hashes)
+            process_append_targets_to_pool(program_id, accounts, pool_id, target_hashes, max_targets_per_pool)
         }
         RemoteViewingInstruction::FinalizePool { pool_id } => {
             process_finalize_pool(program_id, accounts, pool_id)
         }
+        RemoteViewingInstruction::AmendSessionMedia { session_id, session_media_hash, media_item_count } => {
+            process_amend_session_media(program_id, accounts, session_id, session_media_hash, media_item_count)
+        }
+        RemoteViewingInstruction::SubmitSessionBatch {
+            pool_id,
+            target_selector_program,
+            media_hash_algorithm,
+            finalization_reward,
+            sessions,
+            decoy_count,
+            experiment,
+        } => {
+            process_submit_session_batch(
+                program_id,
+                accounts,
+                pool_id,
+                target_selector_program,
+                media_hash_algorithm,
+                finalization_reward,
+                sessions,
+                decoy_count,
+                experiment,
+            )
+        }
+        RemoteViewingInstruction::FinalizeSessionsBatch { submission_slot } => {
+            process_finalize_sessions_batch(program_id, accounts, submission_slot)
+        }
+        RemoteViewingInstruction::ReanchorSession { session_id } => {
+            process_reanchor_session(program_id, accounts, session_id)
+        }
+        RemoteViewingInstruction::FinalizeLateSession { session_id } => {
+            process_finalize_late_session(program_id, accounts, session_id)
+        }
+        RemoteViewingInstruction::CreateAssociativePool {
+            pool_id,
+            target_hashes,
+            hash_algorithm,
+            outcome_labels,
+            resolver,
+            event_time,
+            submission_deadline,
+        } => {
+            process_create_associative_pool(
+                program_id,
+                accounts,
+                pool_id,
+                target_hashes,
+                hash_algorithm,
+                outcome_labels,
+                resolver,
+                event_time,
+                submission_deadline,
+                max_targets_per_pool,
+            )
+        }
+        RemoteViewingInstruction::ResolveOutcome { pool_id, outcome_index } => {
+            process_resolve_outcome(program_id, accounts, pool_id, outcome_index)
+        }
+        RemoteViewingInstruction::FinalizeAssociativeSession { session_id } => {
+            process_finalize_associative_session(program_id, accounts, session_id)
+        }
+        RemoteViewingInstruction::AuthorizeJudge { pool_id, judge } => {
+            process_authorize_judge(program_id, accounts, pool_id, judge)
+        }
+        RemoteViewingInstruction::CommitJudgement { session_id, method, commitment } => {
+            process_commit_judgement(program_id, accounts, session_id, method, commitment)
+        }
+        RemoteViewingInstruction::JudgeSession { session_id, method, candidates, scores, salt } => {
+            process_judge_session(program_id, accounts, session_id, method, candidates, scores, salt)
+        }
+        RemoteViewingInstruction::ForfeitJudgement { session_id, judge } => {
+            process_forfeit_judgement(program_id, accounts, session_id, judge)
+        }
+        RemoteViewingInstruction::RevealTasking { session_id, salt } => {
+            process_reveal_tasking(program_id, accounts, session_id, salt)
+        }
+        RemoteViewingInstruction::CreateExperiment {
+            experiment_id,
+            protocol_hash,
+            analysis_method_hash,
+            start_time,
+            end_time,
+            allowed_pools,
+            planned_sample_size,
+            hypothesis_hash,
+        } => {
+            process_create_experiment(
+                program_id,
+                accounts,
+                experiment_id,
+                protocol_hash,
+                analysis_method_hash,
+                start_time,
+                end_time,
+                allowed_pools,
+                planned_sample_size,
+                hypothesis_hash,
+            )
+        }
+        RemoteViewingInstruction::UpdateExperimentPlan {
+            experiment_id,
+            protocol_hash,
+            hypothesis_hash,
+            analysis_method_hash,
+        } => process_update_experiment_plan(
+            program_id,
+            accounts,
+            experiment_id,
+            protocol_hash,
+            hypothesis_hash,
+            analysis_method_hash,
+        ),
+        RemoteViewingInstruction::InitializeConfig { governance, upgrade_delay, admin, max_targets_per_pool } => {
+            process_initialize_config(program_id, accounts, governance, upgrade_delay, admin, max_targets_per_pool)
+        }
+        RemoteViewingInstruction::SetFrozen { frozen } => {
+            process_set_frozen(program_id, accounts, frozen)
+        }
+        RemoteViewingInstruction::AnnounceUpgrade { program_hash } => {
+            process_announce_upgrade(program_id, accounts, program_hash)
+        }
+        RemoteViewingInstruction::ExecuteUpgrade => {
+            process_execute_upgrade(program_id, accounts)
+        }
+        RemoteViewingInstruction::SetGovernance { governance } => {
+            process_set_governance(program_id, accounts, governance)
+        }
+        RemoteViewingInstruction::SetPaused { paused } => {
+            process_set_paused(program_id, accounts, paused)
+        }
+        RemoteViewingInstruction::SetLimits { max_targets_per_pool } => {
+            process_set_limits(program_id, accounts, max_targets_per_pool)
+        }
+        RemoteViewingInstruction::SetAdmin { admin } => {
+            process_set_admin(program_id, accounts, admin)
+        }
+    }
+}
+
+// Split off the trailing config account and load it. `None` until the config
+// is initialized, in which case nothing is frozen or paused and the default
+// limits apply.
+fn split_config<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(Option<ProgramConfig>, &'a [AccountInfo<'b>]), ProgramError> {
+    let (config_account, accounts) = accounts.split_last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (config_pda, _bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        return Err(RemoteViewingError::ConfigMismatch.into());
+    }
+
+    if config_account.owner != program_id || config_account.data_len() == 0 {
+        return Ok((None, accounts));
     }
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())?;
+    Ok((Some(config), accounts))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_create_target_pool(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    pool_id: String,
+    pool_id: RecordId,
     target_hashes: Vec<[u8; 32]>,
+    hash_algorithm: HashAlgorithm,
+    min_finalization_delay: u64,
+    max_finalization_delay: u64,
+    max_targets_per_pool: u16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let pool_account = next_account_info(account_info_iter)?;
     let creator_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
 
     // Verify creator is signer
     if !creator_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Derive PDA for pool
-    // Hash the pool_id to ensure it fits within the 32-byte seed limit
-    let pool_id_hash = hash(pool_id.as_bytes());
-    let (pool_pda, bump) = Pubkey::find_program_address(
-        &[b"target_pool", pool_id_hash.as_ref()],
-        program_id,
-    );
-
-    // Verify PDA matches
-    if pool_pda != *pool_account.key {
-        return Err(ProgramError::InvalidArgument);
-    }
-
-    // Check if pool already exists
-    if pool_account.data_len() > 0 {
-        return Err(RemoteViewingError::PoolAlreadyExists.into());
-    }
-
     // Validate input parameters
-    if pool_id.is_empty() {
+    if !pool_id.is_valid() {
         return Err(RemoteViewingError::InvalidPoolId.into());
     }
     
@@ -219,396 +1053,1370 @@ fn process_create_target_pool(
     // }
 
     // Validate reasonable limits (prevent excessive storage costs)
-    if target_hashes.len() > 10000 {
+    if target_hashes.len() > max_targets_per_pool as usize {
         return Err(RemoteViewingError::InvalidTargetCount.into());
     }
 
+    if min_finalization_delay < MIN_FINALIZATION_DELAY_SLOTS
+        || max_finalization_delay > MAX_FINALIZATION_DELAY_SLOTS
+        || min_finalization_delay > max_finalization_delay
+    {
+        return Err(RemoteViewingError::InvalidFinalizationWindow.into());
+    }
+
     // Get current time
     let clock = Clock::get()?;
 
     // Create the pool data
-    let pool = TargetPool {
-        pool_id: pool_id.clone(),
-        creator: *creator_account.key,
-        target_count: target_hashes.len() as u16,
-        targets: target_hashes,
-        created_at: clock.unix_timestamp,
-        finalized: false, // Pool starts unfinalised, allowing target additions
-    };
-
-    // Calculate required space
-    let space = pool.try_to_vec()?.len();
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(space);
-
-    // Create the account using invoke_signed for PDA
-    invoke_signed(
-        &system_instruction::create_account(
-            creator_account.key,
-            pool_account.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
-        &[creator_account.clone(), pool_account.clone(), system_program.clone()],
-        &[&[b"target_pool", pool_id_hash.as_ref(), &[bump]]],
-    )?;
+    let pool = TargetPool::new_standard(
+        pool_id.clone(),
+        *creator_account.key,
+        target_hashes,
+        hash_algorithm,
+        min_finalization_delay,
+        max_finalization_delay,
+        clock.unix_timestamp,
+    );
 
-    // Write data to account
-    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+    create_pool_account(program_id, pool_account, creator_account, system_program, &pool)?;
+    create_pool_stats_account(program_id, pool_stats_account, pool_account.key, creator_account, system_program, &pool)?;
 
     msg!("Created target pool: {}", pool_id);
     Ok(())
 }
 
-fn process_submit_session(
+#[allow(clippy::too_many_arguments)]
+fn process_create_associative_pool(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    session_id: String,
-    pool_id: String,
-    session_media_hash: [u8; 32],
-    target_selector_program: Pubkey,
-    completed_target_indices: Vec<u16>,
+    pool_id: RecordId,
+    target_hashes: Vec<[u8; 32]>,
+    hash_algorithm: HashAlgorithm,
+    outcome_labels: Vec<String>,
+    resolver: Pubkey,
+    event_time: i64,
+    submission_deadline: i64,
+    max_targets_per_pool: u16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let session_account = next_account_info(account_info_iter)?;
     let pool_account = next_account_info(account_info_iter)?;
-    let submitter_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
-    let clock_sysvar = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
 
-    // Verify submitter is signer
-    if !submitter_account.is_signer {
+    // Verify creator is signer
+    if !creator_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Derive PDA for session
-    // Hash the session_id to ensure it fits within the 32-byte seed limit
-    let session_id_hash = hash(session_id.as_bytes());
-    let (session_pda, bump) = Pubkey::find_program_address(
-        &[b"session", session_id_hash.as_ref()],
-        program_id,
-    );
+    // Validate input parameters
+    if !pool_id.is_valid() {
+        return Err(RemoteViewingError::InvalidPoolId.into());
+    }
 
-    // Verify PDA matches
-    if session_pda != *session_account.key {
-        return Err(ProgramError::InvalidArgument);
+    // Need at least two outcomes, each with exactly one target
+    if target_hashes.len() < 2
+        || target_hashes.len() > MAX_OUTCOMES
+        || target_hashes.len() > max_targets_per_pool as usize
+    {
+        return Err(RemoteViewingError::InvalidTargetCount.into());
     }
-
-    // Check if session already exists
-    if session_account.data_len() > 0 {
-        return Err(RemoteViewingError::SessionAlreadyExists.into());
+    if outcome_labels.len() != target_hashes.len()
+        || outcome_labels.iter().any(|label| label.is_empty() || label.len() > MAX_OUTCOME_LABEL_LEN)
+    {
+        return Err(RemoteViewingError::InvalidOutcome.into());
     }
 
-    // Validate input parameters
-    if session_id.is_empty() {
-        return Err(RemoteViewingError::InvalidSessionId.into());
-    }
-    
-    if pool_id.is_empty() {
-        return Err(RemoteViewingError::InvalidPoolId.into());
+    // Nobody could ever resolve the pool
+    if resolver == Pubkey::default() {
+        return Err(RemoteViewingError::InvalidResolver.into());
     }
 
-    // Load pool data to verify it exists
-    let pool = TargetPool::try_from_slice(&pool_account.data.borrow())?;
-    
-    // Verify pool ID matches
-    if pool.pool_id != pool_id {
-        return Err(RemoteViewingError::PoolNotFound.into());
+    // Sessions must close no later than the event itself
+    let clock = Clock::get()?;
+    if submission_deadline <= clock.unix_timestamp || submission_deadline > event_time {
+        return Err(RemoteViewingError::InvalidEventTime.into());
     }
 
-    // Get current time and slot
-    let clock = Clock::from_account_info(clock_sysvar)?;
+    let pool = TargetPool::new_associative(
+        pool_id.clone(),
+        *creator_account.key,
+        target_hashes,
+        hash_algorithm,
+        outcome_labels,
+        resolver,
+        event_time,
+        submission_deadline,
+        clock.unix_timestamp,
+    );
 
-    // Create the session data - note that assigned_target_index is set to u16::MAX
-    // and submission_blockhash is empty until finalization
-    let session = Session {
-        session_id: session_id.clone(),
-        pool_id,
-        session_media_hash,
-        submission_slot: clock.slot,
-        submission_blockhash: [0; 32], // Will be filled during finalization
-        assigned_target_index: u16::MAX, // Placeholder until finalization
-        target_selector_program,
-        submitter: *submitter_account.key,
-        submitted_at: clock.unix_timestamp,
-        finalized: false,
-        finalized_at: 0,
-        completed_target_indices: completed_target_indices,
-    };
+    create_pool_account(program_id, pool_account, creator_account, system_program, &pool)?;
+    create_pool_stats_account(program_id, pool_stats_account, pool_account.key, creator_account, system_program, &pool)?;
+
+    msg!("Created associative pool: {} with {} outcomes", pool_id, pool.target_count);
+    Ok(())
+}
+
+// Create and populate the PDA for a new pool, funded by the creator
+fn create_pool_account<'a>(
+    program_id: &Pubkey,
+    pool_account: &AccountInfo<'a>,
+    creator_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    pool: &TargetPool,
+) -> ProgramResult {
+    // Derive PDA for pool
+    // Text ids are hashed to fit within the 32-byte seed limit
+    let pool_id_seed = pool.pool_id.seed();
+    let (pool_pda, bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if pool_pda != *pool_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if pool already exists
+    if pool_account.data_len() > 0 {
+        return Err(RemoteViewingError::PoolAlreadyExists.into());
+    }
 
     // Calculate required space
-    let space = session.try_to_vec()?.len();
+    let space = pool.try_to_vec()?.len();
     let rent = Rent::get()?;
     let lamports = rent.minimum_balance(space);
 
     // Create the account using invoke_signed for PDA
     invoke_signed(
         &system_instruction::create_account(
-            submitter_account.key,
-            session_account.key,
+            creator_account.key,
+            pool_account.key,
             lamports,
             space as u64,
             program_id,
         ),
-        &[submitter_account.clone(), session_account.clone(), system_program.clone()],
-        &[&[b"session", session_id_hash.as_ref(), &[bump]]],
+        &[creator_account.clone(), pool_account.clone(), system_program.clone()],
+        &[&[b"target_pool", pool_id_seed.as_ref(), &[bump]]],
     )?;
 
     // Write data to account
-    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
-
-    msg!("Submitted session: {} at slot: {}", session_id, clock.slot);
+    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
     Ok(())
 }
 
-fn process_finalize_session(
-    _program_id: &Pubkey,
+fn process_resolve_outcome(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    session_id: String,
-    submission_blockhash: String,
-    completed_target_indices: Vec<u16>,
+    pool_id: RecordId,
+    outcome_index: u16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let session_account = next_account_info(account_info_iter)?;
     let pool_account = next_account_info(account_info_iter)?;
-    let caller_account = next_account_info(account_info_iter)?;
+    let resolver_account = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
-    // Verify caller is signer (anyone can finalize)
-    if !caller_account.is_signer {
+    // Verify resolver is signer
+    if !resolver_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Load session data
-    let mut session = Session::try_from_slice(&session_account.data.borrow())?;
-    
-    // Verify session ID matches
-    if session.session_id != session_id {
-        return Err(RemoteViewingError::SessionNotFound.into());
-    }
+    // Derive PDA for pool
+    let pool_id_seed = pool_id.seed();
+    let (pool_pda, _bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
 
-    // Check if already finalized
-    if session.finalized {
-        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    // Verify PDA matches
+    if pool_pda != *pool_account.key {
+        return Err(ProgramError::InvalidArgument);
     }
 
-    // Get current clock
-    let clock = Clock::from_account_info(clock_sysvar)?;
-    
-    // Ensure at least 2 slots have passed since submission
-    // This ensures the submission block is finalized and prevents manipulation
-    if clock.slot < session.submission_slot + 2 {
-        return Err(RemoteViewingError::TooEarlyToFinalize.into());
+    // Check if pool exists
+    if pool_account.data_len() == 0 {
+        return Err(RemoteViewingError::PoolNotFound.into());
     }
 
-    // Prevent finalization too long after submission to avoid slot hash expiry
-    // SlotHashes sysvar typically keeps ~300 recent slots
-    if clock.slot > session.submission_slot + 150 {
-        return Err(RemoteViewingError::InvalidSlotHash.into());
-    }
+    let mut pool = TargetPool::unpack(&pool_account.data.borrow())?;
 
-    // Load pool data
-    let pool = TargetPool::try_from_slice(&pool_account.data.borrow())?;
+    if pool.kind != SessionKind::AssociativePrediction {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
 
-    // Validate that the provided blockhash is not empty (basic sanity check)
-    if submission_blockhash.is_empty() {
-        return Err(RemoteViewingError::InvalidSlotHash.into());
+    // Only the designated resolver may record the outcome
+    if pool.resolver != *resolver_account.key {
+        return Err(ProgramError::InvalidAccountData);
     }
-    
-    // Convert base58 string to [u8; 32]
-    let submission_blockhash_bytes = bs58::decode(&submission_blockhash)
-        .into_vec()
-        .map_err(|_| RemoteViewingError::InvalidSlotHash)?;
-    
-    if submission_blockhash_bytes.len() != 32 {
-        return Err(RemoteViewingError::InvalidSlotHash.into());
+
+    if pool.resolved_outcome != u16::MAX {
+        return Err(RemoteViewingError::OutcomeAlreadyResolved.into());
     }
-    
-    let mut blockhash_array = [0u8; 32];
-    blockhash_array.copy_from_slice(&submission_blockhash_bytes);
-    
-    // Create a list of available target indices (excluding completed ones)
-    let mut available_indices: Vec<u16> = (0..pool.target_count).collect();
-    
-    // Filter out completed target indices
-    available_indices.retain(|&index| !completed_target_indices.contains(&index));
-    
-    // Ensure we have at least one available target
-    if available_indices.is_empty() {
-        return Err(RemoteViewingError::AllTargetsCompleted.into());
+
+    if outcome_index >= pool.target_count {
+        return Err(RemoteViewingError::InvalidOutcome.into());
     }
-    
-    // Calculate target index from the filtered available targets
-    let filtered_index = calculate_target_index(&blockhash_array, available_indices.len() as u16);
-    let assigned_target_index = available_indices[filtered_index as usize];
 
-    // Update session with finalization data
-    session.submission_blockhash = blockhash_array;
-    session.assigned_target_index = assigned_target_index;
-    session.finalized = true;
-    session.finalized_at = clock.unix_timestamp;
-    session.completed_target_indices = completed_target_indices;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    if clock.unix_timestamp < pool.event_time {
+        return Err(RemoteViewingError::EventNotReached.into());
+    }
+    pool.resolved_outcome = outcome_index;
+    pool.resolved_slot = clock.slot;
 
-    // Write updated data back to account
-    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+    // Write updated data to account
+    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
 
     msg!(
-        "Finalized session: {} with target index: {} using blockhash: {} from slot: {}", 
-        session_id, 
-        assigned_target_index,
-        submission_blockhash,
-        session.submission_slot
+        "Resolved pool: {} with outcome: {} ({})",
+        pool_id,
+        outcome_index,
+        pool.outcome_labels[outcome_index as usize]
     );
     Ok(())
 }
 
-fn calculate_target_index(blockhash: &[u8; 32], target_count: u16) -> u16 {
-    // Use first 8 bytes of blockhash as u64
-    let mut bytes = [0u8; 8];
-    bytes.copy_from_slice(&blockhash[0..8]);
-    let value = u64::from_be_bytes(bytes);
-    
-    // Modulo to get index
-    (value % target_count as u64) as u16
-}
-
-fn process_append_targets_to_pool(
+#[allow(clippy::too_many_arguments)]
+fn process_submit_session(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    pool_id: String,
-    target_hashes: Vec<[u8; 32]>,
+    session_id: RecordId,
+    pool_id: RecordId,
+    session_media_hash: [u8; 32],
+    target_selector_program: Pubkey,
+    completed_target_indices: Vec<u16>,
+    media_hash_algorithm: HashAlgorithm,
+    media_item_count: u16,
+    finalization_reward: u64,
+    decoy_count: u8,
+    tasking_commitment: [u8; 32],
+    experiment: Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
     let pool_account = next_account_info(account_info_iter)?;
-    let creator_account = next_account_info(account_info_iter)?;
-    let _system_program = next_account_info(account_info_iter)?;
+    let submitter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let viewer_account = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
 
-    // Verify creator is signer
-    if !creator_account.is_signer {
+    // Verify submitter is signer
+    if !submitter_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Derive PDA for pool
-    let pool_id_hash = hash(pool_id.as_bytes());
-    let (pool_pda, _bump) = Pubkey::find_program_address(
-        &[b"target_pool", pool_id_hash.as_ref()],
-        program_id,
-    );
-
-    // Verify PDA matches
-    if pool_pda != *pool_account.key {
-        return Err(ProgramError::InvalidArgument);
+    // Sessions count towards the viewer's track record, so only the viewer
+    // can put them there
+    if !viewer_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Check if pool exists
-    if pool_account.data_len() == 0 {
-        return Err(RemoteViewingError::PoolNotFound.into());
+    // Validate input parameters
+    if !session_id.is_valid() {
+        return Err(RemoteViewingError::InvalidSessionId.into());
     }
-
-    // Deserialize existing pool
-    let mut pool = TargetPool::try_from_slice(&pool_account.data.borrow())?;
-
-    // Verify the creator matches
-    if pool.creator != *creator_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    
+    if !pool_id.is_valid() {
+        return Err(RemoteViewingError::InvalidPoolId.into());
     }
 
-    // Check if pool is finalized
-    if pool.finalized {
-        return Err(RemoteViewingError::PoolAlreadyFinalized.into());
+    if media_item_count == 0 {
+        return Err(RemoteViewingError::InvalidMediaItemCount.into());
     }
 
-    // Validate input parameters
-    if target_hashes.is_empty() {
-        return Err(RemoteViewingError::InvalidTargetCount.into());
+    // Load pool data to verify it exists
+    let pool = load_pool(program_id, pool_account)?;
+    
+    // Verify pool ID matches
+    if pool.pool_id != pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
     }
 
-    // Check total target limit after addition
-    let target_hashes_len = target_hashes.len();
-    if pool.targets.len() + target_hashes_len > 10000 {
-        return Err(RemoteViewingError::InvalidTargetCount.into());
+    // Associative sessions must be submitted before the event is resolved
+    if pool.resolved_outcome != u16::MAX {
+        return Err(RemoteViewingError::OutcomeAlreadyResolved.into());
     }
 
-    // Calculate new space required BEFORE extending targets
-    // Each target hash is 32 bytes, so we can calculate the additional space needed
-    let additional_target_bytes = target_hashes_len * 32;
-    let current_space = pool_account.data_len();
-    let new_space = current_space + additional_target_bytes;
-
-    // If we need more space, reallocate the account
-    if new_space > current_space {
-        let rent = Rent::get()?;
-        let new_lamports = rent.minimum_balance(new_space);
-        let current_lamports = pool_account.lamports();
+    check_decoy_count(&pool, decoy_count)?;
 
-        // If we need more lamports, transfer them using system program
-        if new_lamports > current_lamports {
-            let additional_lamports = new_lamports - current_lamports;
-            
-            // Transfer additional lamports from creator to pool account using system program
-            invoke(
-                &system_instruction::transfer(
-                    creator_account.key,
-                    pool_account.key,
-                    additional_lamports,
-                ),
-                &[creator_account.clone(), pool_account.clone(), _system_program.clone()],
-            )?;
-        }
+    // Get current time and slot
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    check_submission_deadline(&pool, &clock)?;
 
-        // Reallocate the account data
-        pool_account.realloc(new_space, false)?;
+    if experiment != Pubkey::default() {
+        let experiment_account = next_account_info(account_info_iter)?;
+        record_experiment_sessions(program_id, experiment_account, &experiment, pool_account.key, &clock, 1)?;
     }
 
-    // Now that we have enough space, append the new targets
-    pool.targets.extend(target_hashes);
-    pool.target_count = pool.targets.len() as u16;
+    let session = Session::new_submitted(
+        session_account.key,
+        session_id.clone(),
+        &pool,
+        *submitter_account.key,
+        *viewer_account.key,
+        &clock,
+        session_media_hash,
+        media_hash_algorithm,
+        media_item_count,
+        completed_target_indices,
+        target_selector_program,
+        finalization_reward,
+        decoy_count,
+        tasking_commitment,
+        experiment,
+    );
 
-    // Write updated data to account
-    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+    create_session_account(program_id, session_account, submitter_account, system_program, &session)?;
 
-    msg!("Appended {} targets to pool: {}", target_hashes_len, pool_id);
+    record_viewer_submission(
+        program_id,
+        viewer_stats_account,
+        submitter_account,
+        system_program,
+        viewer_account.key,
+        pool_account.key,
+    )?;
+
+    if session.tasking_commitment != [0; 32] {
+        msg!("Tasking code: {}", tasking_code(&session.tasking_reference));
+    }
+
+    msg!("Submitted session: {} at slot: {}", session_id, clock.slot);
     Ok(())
 }
 
-fn process_finalize_pool(
+#[allow(clippy::too_many_arguments)]
+fn process_submit_session_batch(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    pool_id: String,
+    pool_id: RecordId,
+    target_selector_program: Pubkey,
+    media_hash_algorithm: HashAlgorithm,
+    finalization_reward: u64,
+    sessions: Vec<BatchSessionEntry>,
+    decoy_count: u8,
+    experiment: Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let pool_account = next_account_info(account_info_iter)?;
-    let creator_account = next_account_info(account_info_iter)?;
+    let submitter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
 
-    // Verify creator is signer
-    if !creator_account.is_signer {
+    // Verify submitter is signer
+    if !submitter_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Derive PDA for pool
-    let pool_id_hash = hash(pool_id.as_bytes());
-    let (pool_pda, _bump) = Pubkey::find_program_address(
-        &[b"target_pool", pool_id_hash.as_ref()],
-        program_id,
-    );
+    // Validate input parameters
+    if sessions.is_empty() || sessions.len() > MAX_SESSION_BATCH_SIZE {
+        return Err(RemoteViewingError::InvalidBatchSize.into());
+    }
 
-    // Verify PDA matches
-    if pool_pda != *pool_account.key {
-        return Err(ProgramError::InvalidArgument);
+    if !pool_id.is_valid() {
+        return Err(RemoteViewingError::InvalidPoolId.into());
     }
 
-    // Check if pool exists
-    if pool_account.data_len() == 0 {
+    // Load pool data to verify it exists
+    let pool = load_pool(program_id, pool_account)?;
+
+    // Verify pool ID matches
+    if pool.pool_id != pool_id {
         return Err(RemoteViewingError::PoolNotFound.into());
     }
 
-    // Deserialize existing pool
-    let mut pool = TargetPool::try_from_slice(&pool_account.data.borrow())?;
-
-    // Verify the creator matches
-    if pool.creator != *creator_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    // Associative sessions must be submitted before the event is resolved
+    if pool.resolved_outcome != u16::MAX {
+        return Err(RemoteViewingError::OutcomeAlreadyResolved.into());
+    }
+
+    check_decoy_count(&pool, decoy_count)?;
+
+    // Every session in the batch shares the same submission slot
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    check_submission_deadline(&pool, &clock)?;
+    let session_count = sessions.len();
+
+    if experiment != Pubkey::default() {
+        let experiment_account = next_account_info(account_info_iter)?;
+        record_experiment_sessions(
+            program_id,
+            experiment_account,
+            &experiment,
+            pool_account.key,
+            &clock,
+            session_count as u32,
+        )?;
+    }
+
+    for entry in sessions {
+        let session_account = next_account_info(account_info_iter)?;
+        let viewer_account = next_account_info(account_info_iter)?;
+        let viewer_stats_account = next_account_info(account_info_iter)?;
+
+        if !viewer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !entry.session_id.is_valid() {
+            return Err(RemoteViewingError::InvalidSessionId.into());
+        }
+
+        if entry.media_item_count == 0 {
+            return Err(RemoteViewingError::InvalidMediaItemCount.into());
+        }
+
+        let session = Session::new_submitted(
+            session_account.key,
+            entry.session_id,
+            &pool,
+            *submitter_account.key,
+            *viewer_account.key,
+            &clock,
+            entry.session_media_hash,
+            media_hash_algorithm,
+            entry.media_item_count,
+            entry.completed_target_indices,
+            target_selector_program,
+            finalization_reward,
+            decoy_count,
+            entry.tasking_commitment,
+            experiment,
+        );
+
+        create_session_account(program_id, session_account, submitter_account, system_program, &session)?;
+
+        record_viewer_submission(
+            program_id,
+            viewer_stats_account,
+            submitter_account,
+            system_program,
+            viewer_account.key,
+            pool_account.key,
+        )?;
+    }
+
+    msg!("Submitted {} sessions to pool: {} at slot: {}", session_count, pool_id, clock.slot);
+    Ok(())
+}
+
+// Create and populate the PDA for a new session, funded by the submitter.
+// The finalization reward is escrowed in the session account on top of rent.
+fn create_session_account<'a>(
+    program_id: &Pubkey,
+    session_account: &AccountInfo<'a>,
+    submitter_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    session: &Session,
+) -> ProgramResult {
+    // Derive PDA for session
+    // Text ids are hashed to fit within the 32-byte seed limit
+    let session_id_seed = session.session_id.seed();
+    let (session_pda, bump) = Pubkey::find_program_address(
+        &[b"session", session_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if session_pda != *session_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if session already exists
+    if session_account.data_len() > 0 {
+        return Err(RemoteViewingError::SessionAlreadyExists.into());
+    }
+
+    // Calculate required space
+    let space = session.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let lamports = rent
+        .minimum_balance(session.finalized_space()?)
+        .checked_add(session.finalization_reward)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            submitter_account.key,
+            session_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[submitter_account.clone(), session_account.clone(), system_program.clone()],
+        &[&[b"session", session_id_seed.as_ref(), &[bump]]],
+    )?;
+
+    // Write data to account
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+fn process_finalize_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let slot_hashes_sysvar = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can finalize)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Load session data
+    let mut session = Session::unpack(&session_account.data.borrow())?;
+    
+    // Verify session ID matches
+    if session.session_id != session_id {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+
+    // Check if already finalized
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    // Associative sessions are finalized from the resolved outcome instead
+    if session.kind != SessionKind::Standard {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+
+    // Get current clock
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    
+    // Enforce the pool's finalization window around the submission slot
+    check_finalization_window(
+        clock.slot,
+        session.submission_slot,
+        session.min_finalization_delay,
+        session.max_finalization_delay,
+    )?;
+
+    // Load pool data
+    let pool = load_pool(program_id, pool_account)?;
+    if pool.pool_id != session.pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    // Entropy is the hash of the slot fixed at submission, read from
+    // SlotHashes so the caller has no say in the assignment
+    if *slot_hashes_sysvar.key != sysvar::slot_hashes::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let entropy_slot = session.entropy_slot;
+    let slot_hash = find_slot_hash(&slot_hashes_sysvar.data.borrow(), entropy_slot)
+        .ok_or(RemoteViewingError::InvalidSlotHash)?;
+
+    // Update session with finalization data
+    draw_assignment(&mut session, &pool, slot_hash, entropy_slot)?;
+    let assigned_target_index = session.assigned_target_index;
+    session.finalized = true;
+    session.finalized_at = clock.unix_timestamp;
+
+    // Write updated data back to account
+    write_finalized_session(session_account, &session)?;
+
+    pay_finalization_reward(session_account, caller_account, session.finalization_reward)?;
+
+    update_viewer_stats(program_id, viewer_stats_account, &session.viewer, pool_account.key, |counters| {
+        counters.sessions_finalized += 1;
+    })?;
+    update_pool_stats(program_id, pool_stats_account, pool_account.key, |stats| {
+        stats.record_assignment(session.assigned_target_index)
+    })?;
+
+    msg!(
+        "Finalized session: {} with target index: {} using slot hash: {} of slot: {}",
+        session_id,
+        assigned_target_index,
+        bs58::encode(slot_hash).into_string(),
+        entropy_slot
+    );
+    Ok(())
+}
+
+fn process_finalize_sessions_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    submission_slot: Slot,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let slot_hashes_sysvar = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can finalize)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Remaining accounts come in (session, viewer stats) pairs
+    let session_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    if session_accounts.is_empty()
+        || session_accounts.len() % 2 != 0
+        || session_accounts.len() / 2 > MAX_SESSION_BATCH_SIZE
+    {
+        return Err(RemoteViewingError::InvalidBatchSize.into());
+    }
+
+    let pool = load_pool(program_id, pool_account)?;
+
+    // Associative sessions are finalized from the resolved outcome instead
+    if pool.kind != SessionKind::Standard {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+
+    // Pool delay bounds are fixed at creation and copied into every session,
+    // so one check covers the shared submission slot
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    check_finalization_window(
+        clock.slot,
+        submission_slot,
+        pool.min_finalization_delay,
+        pool.max_finalization_delay,
+    )?;
+
+    // Single SlotHashes lookup shared by every session in the batch, which
+    // all draw from the same slot after their shared submission slot
+    if *slot_hashes_sysvar.key != sysvar::slot_hashes::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let entropy_slot = submission_slot + pool.min_finalization_delay;
+    let slot_hash = find_slot_hash(&slot_hashes_sysvar.data.borrow(), entropy_slot)
+        .ok_or(RemoteViewingError::InvalidSlotHash)?;
+
+    let mut finalized_count = 0;
+    for pair in session_accounts.chunks(2) {
+        let (session_account, viewer_stats_account) = (pair[0], pair[1]);
+
+        if session_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut session = Session::unpack(&session_account.data.borrow())?;
+
+        if session.pool_id != pool.pool_id {
+            return Err(RemoteViewingError::PoolNotFound.into());
+        }
+        if session.submission_slot != submission_slot || session.entropy_slot != entropy_slot {
+            return Err(RemoteViewingError::SessionSlotMismatch.into());
+        }
+
+        // Another crank may have got there first
+        if session.finalized {
+            continue;
+        }
+
+        draw_assignment(&mut session, &pool, slot_hash, entropy_slot)?;
+        session.finalized = true;
+        session.finalized_at = clock.unix_timestamp;
+
+        write_finalized_session(session_account, &session)?;
+
+        pay_finalization_reward(session_account, caller_account, session.finalization_reward)?;
+
+        update_viewer_stats(program_id, viewer_stats_account, &session.viewer, pool_account.key, |counters| {
+            counters.sessions_finalized += 1;
+        })?;
+        update_pool_stats(program_id, pool_stats_account, pool_account.key, |stats| {
+            stats.record_assignment(session.assigned_target_index)
+        })?;
+        finalized_count += 1;
+    }
+
+    msg!(
+        "Finalized {} sessions from slot: {} using slot hash: {} of slot: {}",
+        finalized_count,
+        submission_slot,
+        bs58::encode(slot_hash).into_string(),
+        entropy_slot
+    );
+    Ok(())
+}
+
+fn process_reanchor_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can re-anchor)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if session_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut session = Session::unpack(&session_account.data.borrow())?;
+
+    // Verify session ID matches
+    if session.session_id != session_id {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    // Associative sessions are finalized from the resolved outcome instead
+    if session.kind != SessionKind::Standard {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+
+    // Only allowed once the current anchor's window has closed, so a
+    // re-anchor can never replace entropy that is still usable. Repeating it
+    // after each missed window keeps every session finalizable; the caller
+    // picks when, which is why late sessions stay out of the hit-rate stats.
+    let anchor_slot = if session.reanchor_count == 0 {
+        session.submission_slot
+    } else {
+        session.reanchor_slot
+    };
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    if clock.slot <= anchor_slot + session.max_finalization_delay {
+        return Err(RemoteViewingError::FinalizationWindowOpen.into());
+    }
+
+    session.reanchor_slot = clock.slot;
+    session.reanchor_count += 1;
+
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Re-anchored session: {} at slot: {} (re-anchor #{})",
+        session_id,
+        clock.slot,
+        session.reanchor_count
+    );
+    Ok(())
+}
+
+fn process_finalize_late_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let slot_hashes_sysvar = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can finalize)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if session_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut session = Session::unpack(&session_account.data.borrow())?;
+
+    // Verify session ID matches
+    if session.session_id != session_id {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    // Associative sessions are finalized from the resolved outcome instead
+    if session.kind != SessionKind::Standard {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+
+    if session.reanchor_count == 0 {
+        return Err(RemoteViewingError::SessionNotReanchored.into());
+    }
+
+    // Same window as FinalizeSession, measured from the re-anchor slot
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    check_finalization_window(
+        clock.slot,
+        session.reanchor_slot,
+        session.min_finalization_delay,
+        session.max_finalization_delay,
+    )?;
+
+    // Entropy is the hash of the first slot produced after the re-anchor
+    if *slot_hashes_sysvar.key != sysvar::slot_hashes::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (entropy_slot, slot_hash) =
+        find_first_slot_hash_after(&slot_hashes_sysvar.data.borrow(), session.reanchor_slot)
+            .ok_or(RemoteViewingError::InvalidSlotHash)?;
+
+    let pool = load_pool(program_id, pool_account)?;
+    if session.pool_id != pool.pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    draw_assignment(&mut session, &pool, slot_hash, entropy_slot)?;
+    let assigned_target_index = session.assigned_target_index;
+    session.late_finalized = true;
+    session.finalized = true;
+    session.finalized_at = clock.unix_timestamp;
+
+    write_finalized_session(session_account, &session)?;
+
+    pay_finalization_reward(session_account, caller_account, session.finalization_reward)?;
+
+    // Counted apart from on-time sessions: the caller chose when to
+    // re-anchor, so these draws stay out of the assignment and hit-rate
+    // statistics
+    update_viewer_stats(program_id, viewer_stats_account, &session.viewer, pool_account.key, |counters| {
+        counters.sessions_late_finalized += 1;
+    })?;
+    update_pool_stats(program_id, pool_stats_account, pool_account.key, |stats| {
+        stats.sessions_late_finalized += 1;
+        Ok(())
+    })?;
+
+    msg!(
+        "Late-finalized session: {} with target index: {} using slot hash of slot: {}",
+        session_id,
+        assigned_target_index,
+        entropy_slot
+    );
+    Ok(())
+}
+
+fn process_finalize_associative_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can finalize)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if session_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut session = Session::unpack(&session_account.data.borrow())?;
+
+    // Verify session ID matches
+    if session.session_id != session_id {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    if session.kind != SessionKind::AssociativePrediction {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+
+    let pool = load_pool(program_id, pool_account)?;
+    if session.pool_id != pool.pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    if pool.resolved_outcome == u16::MAX {
+        return Err(RemoteViewingError::OutcomeNotResolved.into());
+    }
+
+    // The target the viewer should have seen is the one tied to the real outcome
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    session.assigned_target_index = pool.resolved_outcome;
+    session.entropy_slot = pool.resolved_slot;
+    session.finalized = true;
+    session.finalized_at = clock.unix_timestamp;
+
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+
+    pay_finalization_reward(session_account, caller_account, session.finalization_reward)?;
+
+    update_viewer_stats(program_id, viewer_stats_account, &session.viewer, pool_account.key, |counters| {
+        counters.sessions_finalized += 1;
+    })?;
+    update_pool_stats(program_id, pool_stats_account, pool_account.key, |stats| {
+        stats.record_assignment(session.assigned_target_index)
+    })?;
+
+    msg!(
+        "Finalized associative session: {} with outcome: {} (target index: {})",
+        session_id,
+        pool.outcome_labels[pool.resolved_outcome as usize],
+        session.assigned_target_index
+    );
+    Ok(())
+}
+
+// Decoys are drawn at finalization, so their number is fixed at submission,
+// before anyone knows the target
+fn check_decoy_count(pool: &TargetPool, decoy_count: u8) -> ProgramResult {
+    if decoy_count == 0 {
+        return Ok(());
+    }
+    // Associative sessions have no slot hash entropy; the other outcomes' targets act as decoys
+    if pool.kind != SessionKind::Standard {
+        return Err(RemoteViewingError::InvalidSessionKind.into());
+    }
+    // Pools only grow, so enough targets now means enough at finalization
+    if decoy_count > MAX_DECOY_COUNT || decoy_count as u16 >= pool.target_count {
+        return Err(RemoteViewingError::InvalidDecoyCount.into());
+    }
+    Ok(())
+}
+
+// Reject sessions for an associative pool once its submission deadline has
+// passed, when the outcome may already be known
+fn check_submission_deadline(pool: &TargetPool, clock: &Clock) -> ProgramResult {
+    if pool.kind == SessionKind::AssociativePrediction && clock.unix_timestamp >= pool.submission_deadline {
+        return Err(RemoteViewingError::SubmissionDeadlinePassed.into());
+    }
+    Ok(())
+}
+
+// Assign a standard session its target from `slot_hash`, along with its
+// judging candidates when it was submitted with decoys
+fn draw_assignment(session: &mut Session, pool: &TargetPool, slot_hash: [u8; 32], entropy_slot: Slot) -> ProgramResult {
+    // Pick from the targets not yet completed by this viewer
+    session.assigned_target_index =
+        select_target_index(&slot_hash, pool.target_count, &session.completed_target_indices)
+            .ok_or(RemoteViewingError::AllTargetsCompleted)?;
+
+    if session.decoy_count > 0 {
+        session.candidates = select_candidates(
+            &slot_hash,
+            pool.target_count,
+            session.assigned_target_index,
+            session.decoy_count,
+        )
+        .ok_or(RemoteViewingError::InvalidDecoyCount)?;
+    }
+
+    session.submission_blockhash = slot_hash;
+    session.entropy_slot = entropy_slot;
+    Ok(())
+}
+
+// Write a finalized session, growing the account into the rent escrowed at
+// submission for its candidate list
+fn write_finalized_session(session_account: &AccountInfo, session: &Session) -> ProgramResult {
+    let space = session.try_to_vec()?.len();
+    if space > session_account.data_len() {
+        session_account.realloc(space, false)?;
+    }
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Reject finalization outside `(anchor_slot + min_delay, anchor_slot + max_delay]`
+fn check_finalization_window(
+    current_slot: Slot,
+    anchor_slot: Slot,
+    min_delay: u64,
+    max_delay: u64,
+) -> ProgramResult {
+    // Too early: the slot `min_delay` after the anchor, whose hash draws an
+    // on-time session's target, has no hash yet
+    if current_slot <= anchor_slot + min_delay {
+        return Err(RemoteViewingError::TooEarlyToFinalize.into());
+    }
+
+    // Too late: the anchor slot hash may have left SlotHashes
+    if current_slot > anchor_slot + max_delay {
+        return Err(RemoteViewingError::InvalidSlotHash.into());
+    }
+    Ok(())
+}
+
+// Look up the hash of `slot` in raw SlotHashes sysvar data without
+// deserializing all entries. Layout: u64 length, then (u64 slot, [u8; 32] hash)
+// entries ordered from newest to oldest slot. Public so off-chain tools can
+// read a fetched sysvar the same way.
+pub fn find_slot_hash(data: &[u8], slot: Slot) -> Option<[u8; 32]> {
+    const ENTRY_SIZE: usize = 8 + 32;
+
+    let len = u64::from_le_bytes(data.get(0..8)?.try_into().ok()?) as usize;
+    let entries = data.get(8..8 + len.checked_mul(ENTRY_SIZE)?)?;
+
+    let slot_at = |i: usize| -> Slot {
+        let start = i * ENTRY_SIZE;
+        u64::from_le_bytes(entries[start..start + 8].try_into().unwrap())
+    };
+
+    // Binary search over descending slots
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = (low + high) / 2;
+        let mid_slot = slot_at(mid);
+        if mid_slot == slot {
+            let start = mid * ENTRY_SIZE + 8;
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&entries[start..start + 32]);
+            return Some(hash);
+        } else if mid_slot > slot {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    None
+}
+
+// Find the oldest SlotHashes entry strictly newer than `slot`. Skipped slots
+// have no entry, so this is the first slot actually produced after `slot`.
+fn find_first_slot_hash_after(data: &[u8], slot: Slot) -> Option<(Slot, [u8; 32])> {
+    const ENTRY_SIZE: usize = 8 + 32;
+
+    let len = u64::from_le_bytes(data.get(0..8)?.try_into().ok()?) as usize;
+    let entries = data.get(8..8 + len.checked_mul(ENTRY_SIZE)?)?;
+
+    // Entries run newest to oldest, so scan from the end
+    entries.chunks_exact(ENTRY_SIZE).rev().find_map(|entry| {
+        let entry_slot = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+        if entry_slot > slot {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&entry[8..]);
+            Some((entry_slot, hash))
+        } else {
+            None
+        }
+    })
+}
+
+// Move the escrowed finalization reward from a session account to the caller
+fn pay_finalization_reward(
+    session_account: &AccountInfo,
+    caller_account: &AccountInfo,
+    reward: u64,
+) -> ProgramResult {
+    if reward == 0 {
+        return Ok(());
+    }
+
+    **session_account.try_borrow_mut_lamports()? = session_account
+        .lamports()
+        .checked_sub(reward)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **caller_account.try_borrow_mut_lamports()? = caller_account
+        .lamports()
+        .checked_add(reward)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Deserialize a program-owned pool and check the account is the PDA of the id
+// it holds, so no other account can stand in for the pool
+fn load_pool(program_id: &Pubkey, pool_account: &AccountInfo) -> Result<TargetPool, ProgramError> {
+    if pool_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let pool = TargetPool::unpack(&pool_account.data.borrow())?;
+    let (pool_pda, _bump) =
+        Pubkey::find_program_address(&[b"target_pool", pool.pool_id.seed().as_ref()], program_id);
+    if pool_pda != *pool_account.key {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+    Ok(pool)
+}
+
+fn process_amend_session_media(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+    session_media_hash: [u8; 32],
+    media_item_count: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let submitter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify submitter is signer
+    if !submitter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for session
+    let session_id_seed = session_id.seed();
+    let (session_pda, _bump) = Pubkey::find_program_address(
+        &[b"session", session_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if session_pda != *session_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if session exists
+    if session_account.data_len() == 0 {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+
+    let mut session = Session::unpack(&session_account.data.borrow())?;
+
+    // Only the original submitter may amend
+    if session.submitter != *submitter_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    if media_item_count == 0 {
+        return Err(RemoteViewingError::InvalidMediaItemCount.into());
+    }
+
+    // The target is drawn from the hash of the entropy slot, fixed at
+    // submission, so amendments must land before that slot is produced
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    if clock.slot >= session.entropy_slot {
+        return Err(RemoteViewingError::AmendmentWindowClosed.into());
+    }
+
+    // Record the superseded commitment before replacing it
+    session.media_history.push(MediaAmendment {
+        previous_media_hash: session.session_media_hash,
+        previous_media_item_count: session.media_item_count,
+        amended_slot: clock.slot,
+    });
+    session.session_media_hash = session_media_hash;
+    session.media_item_count = media_item_count;
+
+    // Grow the account to fit the new history entry
+    let new_space = session.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let new_lamports = rent.minimum_balance(session.finalized_space()?) + session.finalization_reward;
+    let current_lamports = session_account.lamports();
+
+    if new_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(
+                submitter_account.key,
+                session_account.key,
+                new_lamports - current_lamports,
+            ),
+            &[submitter_account.clone(), session_account.clone(), system_program.clone()],
+        )?;
+    }
+    session_account.realloc(new_space, false)?;
+
+    // Write updated data back to account
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Amended session: {} media at slot: {} ({} amendments)",
+        session_id,
+        clock.slot,
+        session.media_history.len()
+    );
+    Ok(())
+}
+
+/// Select a target from `0..target_count`, skipping `completed_target_indices`.
+/// Returns `None` when every target has been completed. Shared with the
+/// off-chain verifier so both sides compute the same assignment.
+pub fn select_target_index(
+    blockhash: &[u8; 32],
+    target_count: u16,
+    completed_target_indices: &[u16],
+) -> Option<u16> {
+    // Create a list of available target indices (excluding completed ones)
+    let mut available_indices: Vec<u16> = (0..target_count).collect();
+    available_indices.retain(|&index| !completed_target_indices.contains(&index));
+
+    if available_indices.is_empty() {
+        return None;
+    }
+
+    // Calculate target index from the filtered available targets
+    let filtered_index = calculate_target_index(blockhash, available_indices.len() as u16);
+    Some(available_indices[filtered_index as usize])
+}
+
+pub fn calculate_target_index(blockhash: &[u8; 32], target_count: u16) -> u16 {
+    // Use first 8 bytes of blockhash as u64
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&blockhash[0..8]);
+    let value = u64::from_be_bytes(bytes);
+    
+    // Modulo to get index
+    (value % target_count as u64) as u16
+}
+
+fn process_append_targets_to_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    pool_id: RecordId,
+    target_hashes: Vec<[u8; 32]>,
+    max_targets_per_pool: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let _system_program = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify creator is signer
+    if !creator_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for pool
+    let pool_id_seed = pool_id.seed();
+    let (pool_pda, _bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if pool_pda != *pool_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if pool exists
+    if pool_account.data_len() == 0 {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    // Deserialize existing pool
+    let mut pool = TargetPool::unpack(&pool_account.data.borrow())?;
+
+    // Verify the creator matches
+    if pool.creator != *creator_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if pool is finalized
+    if pool.finalized {
+        return Err(RemoteViewingError::PoolAlreadyFinalized.into());
+    }
+
+    // Validate input parameters
+    if target_hashes.is_empty() {
+        return Err(RemoteViewingError::InvalidTargetCount.into());
+    }
+
+    // Check total target limit after addition
+    let target_hashes_len = target_hashes.len();
+    if pool.targets.len() + target_hashes_len > max_targets_per_pool as usize {
+        return Err(RemoteViewingError::InvalidTargetCount.into());
+    }
+
+    // Calculate new space required BEFORE extending targets
+    // Each target hash is 32 bytes, so we can calculate the additional space needed
+    let additional_target_bytes = target_hashes_len * 32;
+    let current_space = pool_account.data_len();
+    let new_space = current_space + additional_target_bytes;
+
+    // If we need more space, reallocate the account
+    if new_space > current_space {
+        let rent = Rent::get()?;
+        let new_lamports = rent.minimum_balance(new_space);
+        let current_lamports = pool_account.lamports();
+
+        // If we need more lamports, transfer them using system program
+        if new_lamports > current_lamports {
+            let additional_lamports = new_lamports - current_lamports;
+            
+            // Transfer additional lamports from creator to pool account using system program
+            invoke(
+                &system_instruction::transfer(
+                    creator_account.key,
+                    pool_account.key,
+                    additional_lamports,
+                ),
+                &[creator_account.clone(), pool_account.clone(), _system_program.clone()],
+            )?;
+        }
+
+        // Reallocate the account data
+        pool_account.realloc(new_space, false)?;
+    }
+
+    // Now that we have enough space, append the new targets
+    pool.targets.extend(target_hashes);
+    pool.target_count = pool.targets.len() as u16;
+
+    // Write updated data to account
+    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
+    grow_pool_stats_account(program_id, pool_stats_account, pool_account.key, creator_account, _system_program, &pool)?;
+
+    msg!("Appended {} targets to pool: {}", target_hashes_len, pool_id);
+    Ok(())
+}
+
+fn process_finalize_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    pool_id: RecordId,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+
+    // Verify creator is signer
+    if !creator_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for pool
+    let pool_id_seed = pool_id.seed();
+    let (pool_pda, _bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if pool_pda != *pool_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if pool exists
+    if pool_account.data_len() == 0 {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    // Deserialize existing pool
+    let mut pool = TargetPool::unpack(&pool_account.data.borrow())?;
+
+    // Verify the creator matches
+    if pool.creator != *creator_account.key {
+        return Err(ProgramError::InvalidAccountData);
     }
 
     // Check if pool is already finalized
@@ -616,18 +2424,1403 @@ fn process_finalize_pool(
         return Err(RemoteViewingError::PoolAlreadyFinalized.into());
     }
 
-    // Check that pool has at least one target
-    if pool.targets.is_empty() {
-        return Err(RemoteViewingError::InvalidTargetCount.into());
+    // Check that pool has at least one target
+    if pool.targets.is_empty() {
+        return Err(RemoteViewingError::InvalidTargetCount.into());
+    }
+
+    // Mark pool as finalized
+    pool.finalized = true;
+
+    // Write updated data to account
+    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
+    msg!("Finalized pool: {} with {} targets", pool_id, pool.targets.len());
+    Ok(())
+}
+
+fn process_authorize_judge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    pool_id: RecordId,
+    judge: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authorization_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify creator is signer
+    if !creator_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for pool
+    let pool_id_seed = pool_id.seed();
+    let (pool_pda, _bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if pool_pda != *pool_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let pool = TargetPool::unpack(&pool_account.data.borrow())?;
+
+    // Only the pool creator may appoint judges
+    if pool.creator != *creator_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Derive PDA for the authorization
+    let (authorization_pda, bump) = Pubkey::find_program_address(
+        &[b"judge", pool_pda.as_ref(), judge.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if authorization_pda != *authorization_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Authorizing twice is a no-op error rather than an overwrite
+    if authorization_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let clock = Clock::get()?;
+    let authorization = JudgeAuthorization {
+        pool_id: pool_id.clone(),
+        judge,
+        authorized_at: clock.unix_timestamp,
+    };
+
+    // Calculate required space
+    let space = authorization.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(space);
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_account.key,
+            authorization_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[creator_account.clone(), authorization_account.clone(), system_program.clone()],
+        &[&[b"judge", pool_pda.as_ref(), judge.as_ref(), &[bump]]],
+    )?;
+
+    // Write data to account
+    authorization.serialize(&mut &mut authorization_account.data.borrow_mut()[..])?;
+
+    msg!("Authorized judge: {} for pool: {}", judge, pool_id);
+    Ok(())
+}
+
+fn process_commit_judgement(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+    method: JudgingMethod,
+    commitment: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let judgement_account = next_account_info(account_info_iter)?;
+    let session_account = next_account_info(account_info_iter)?;
+    let authorization_account = next_account_info(account_info_iter)?;
+    let judge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify judge is signer
+    if !judge_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut session = load_session(program_id, session_account, &session_id)?;
+    check_judge_authorization(program_id, authorization_account, judge_account.key, &session.pool_id)?;
+
+    // Blind judgements must be locked in before the target is known
+    if session.finalized {
+        return Err(RemoteViewingError::SessionAlreadyFinalized.into());
+    }
+
+    if session.kind == SessionKind::Standard && session.decoy_count < MIN_JUDGING_DECOYS {
+        return Err(RemoteViewingError::NotEnoughCandidates.into());
+    }
+
+    if judgement_account.data_len() > 0 {
+        return Err(RemoteViewingError::JudgementAlreadySubmitted.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let judgement = Judgement {
+        session: *session_account.key,
+        judge: *judge_account.key,
+        commitment,
+        committed_slot: clock.slot,
+        method, // Also bound by the commitment; needed to score a forfeit
+        candidates: Vec::new(),
+        scores: Vec::new(),
+        target_score: 0,
+        judged: false,
+        judged_at: 0,
+        forfeited: false,
+    };
+
+    create_judgement_account(program_id, judgement_account, judge_account, system_program, &judgement)?;
+
+    // The first judge to commit is the one whose judgement the stats count
+    if session.designated_judge == Pubkey::default() {
+        session.designated_judge = *judge_account.key;
+        session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+    }
+
+    msg!("Committed blind judgement for session: {} by judge: {}", session_id, judge_account.key);
+    Ok(())
+}
+
+fn process_judge_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+    method: JudgingMethod,
+    candidates: Vec<u16>,
+    scores: Vec<u8>,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let judgement_account = next_account_info(account_info_iter)?;
+    let session_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let authorization_account = next_account_info(account_info_iter)?;
+    let judge_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify judge is signer
+    if !judge_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let session = load_session(program_id, session_account, &session_id)?;
+    check_judge_authorization(program_id, authorization_account, judge_account.key, &session.pool_id)?;
+
+    // Scores are tied to the assigned target, so it must be known
+    if !session.finalized {
+        return Err(RemoteViewingError::SessionNotFinalized.into());
+    }
+
+    let pool = load_pool(program_id, pool_account)?;
+    if pool.pool_id != session.pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    let judged_candidates = judging_candidates(&session, &pool);
+    if judged_candidates.len() < 1 + MIN_JUDGING_DECOYS as usize {
+        return Err(RemoteViewingError::NotEnoughCandidates.into());
+    }
+
+    validate_scores(method, &candidates, &scores, pool.target_count)?;
+
+    // Blind judges commit before the session's candidates are drawn, so they
+    // may score more targets than that; the target is scored among the
+    // session's candidates only
+    let (target_score, candidate_count) = score_target(
+        method,
+        &candidates,
+        &scores,
+        &judged_candidates,
+        session.assigned_target_index,
+    )
+    .ok_or(RemoteViewingError::InvalidScores)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if judgement_account.data_len() == 0 {
+        // Open (non-blind) judgement
+        let judgement = Judgement {
+            session: *session_account.key,
+            judge: *judge_account.key,
+            commitment: [0; 32],
+            committed_slot: 0,
+            method,
+            candidates,
+            scores,
+            target_score,
+            judged: true,
+            judged_at: clock.unix_timestamp,
+            forfeited: false,
+        };
+        create_judgement_account(program_id, judgement_account, judge_account, system_program, &judgement)?;
+    } else {
+        // Reveal of a blind judgement
+        if judgement_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut judgement = Judgement::try_from_slice(&judgement_account.data.borrow())?;
+
+        if judgement.session != *session_account.key || judgement.judge != *judge_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if judgement.judged {
+            return Err(RemoteViewingError::JudgementAlreadySubmitted.into());
+        }
+        if clock.unix_timestamp > session.finalized_at + JUDGEMENT_REVEAL_PERIOD {
+            return Err(RemoteViewingError::RevealDeadlinePassed.into());
+        }
+        if judgement.commitment != judgement_commitment(method, &candidates, &scores, &salt) {
+            return Err(RemoteViewingError::CommitmentMismatch.into());
+        }
+
+        judgement.method = method;
+        judgement.candidates = candidates;
+        judgement.scores = scores;
+        judgement.target_score = target_score;
+        judgement.judged = true;
+        judgement.judged_at = clock.unix_timestamp;
+
+        // Grow the account to fit the revealed scores
+        let new_space = judgement.try_to_vec()?.len();
+        let rent = Rent::get()?;
+        let new_lamports = rent.minimum_balance(new_space);
+        let current_lamports = judgement_account.lamports();
+
+        if new_lamports > current_lamports {
+            invoke(
+                &system_instruction::transfer(
+                    judge_account.key,
+                    judgement_account.key,
+                    new_lamports - current_lamports,
+                ),
+                &[judge_account.clone(), judgement_account.clone(), system_program.clone()],
+            )?;
+        }
+        judgement_account.realloc(new_space, false)?;
+
+        judgement.serialize(&mut &mut judgement_account.data.borrow_mut()[..])?;
+    }
+
+    record_judgement_stats(
+        program_id,
+        &session,
+        judge_account.key,
+        pool_account,
+        viewer_stats_account,
+        pool_stats_account,
+        method,
+        target_score,
+        candidate_count,
+    )?;
+
+    msg!(
+        "Judged session: {} by judge: {} with target score: {}",
+        session_id,
+        judge_account.key,
+        target_score
+    );
+    Ok(())
+}
+
+fn process_forfeit_judgement(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+    judge: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let judgement_account = next_account_info(account_info_iter)?;
+    let session_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let caller_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let viewer_stats_account = next_account_info(account_info_iter)?;
+    let pool_stats_account = next_account_info(account_info_iter)?;
+
+    // Verify caller is signer (anyone can forfeit)
+    if !caller_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let session = load_session(program_id, session_account, &session_id)?;
+    if !session.finalized {
+        return Err(RemoteViewingError::SessionNotFinalized.into());
+    }
+
+    if judgement_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut judgement = Judgement::try_from_slice(&judgement_account.data.borrow())?;
+    if judgement.session != *session_account.key || judgement.judge != judge {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if judgement.judged {
+        return Err(RemoteViewingError::JudgementAlreadySubmitted.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    if clock.unix_timestamp <= session.finalized_at + JUDGEMENT_REVEAL_PERIOD {
+        return Err(RemoteViewingError::RevealDeadlineNotReached.into());
+    }
+
+    let pool = load_pool(program_id, pool_account)?;
+    if pool.pool_id != session.pool_id {
+        return Err(RemoteViewingError::PoolNotFound.into());
+    }
+
+    // Scored as the worst result among the session's candidates, so
+    // withholding a reveal can never do better than revealing
+    let method = judgement.method;
+    let candidate_count = judging_candidates(&session, &pool).len();
+    let target_score = miss_score(method, candidate_count);
+    judgement.target_score = target_score;
+    judgement.judged = true;
+    judgement.judged_at = clock.unix_timestamp;
+    judgement.forfeited = true;
+
+    judgement.serialize(&mut &mut judgement_account.data.borrow_mut()[..])?;
+
+    record_judgement_stats(
+        program_id,
+        &session,
+        &judge,
+        pool_account,
+        viewer_stats_account,
+        pool_stats_account,
+        method,
+        target_score,
+        candidate_count,
+    )?;
+
+    msg!("Forfeited judgement of session: {} by judge: {}", session_id, judge);
+    Ok(())
+}
+
+/// Deterministically pick `decoy_count` distinct decoys (never the target)
+/// from `0..target_count` and return them shuffled together with the target.
+/// Returns `None` if the pool has too few targets.
+pub fn select_candidates(
+    entropy: &[u8; 32],
+    target_count: u16,
+    target_index: u16,
+    decoy_count: u8,
+) -> Option<Vec<u16>> {
+    if target_index >= target_count || decoy_count as u16 >= target_count {
+        return None;
+    }
+
+    // Domain-separated stream of values derived from the assignment entropy
+    let mut counter: u64 = 0;
+    let mut next_value = || {
+        let digest = hashv(&[b"decoys", entropy, &counter.to_le_bytes()]).to_bytes();
+        counter += 1;
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[0..8]);
+        u64::from_be_bytes(bytes)
+    };
+
+    let mut candidates = vec![target_index];
+    while candidates.len() <= decoy_count as usize {
+        let index = (next_value() % target_count as u64) as u16;
+        if !candidates.contains(&index) {
+            candidates.push(index);
+        }
+    }
+
+    // Fisher-Yates shuffle so the target's position reveals nothing
+    for i in (1..candidates.len()).rev() {
+        let j = (next_value() % (i as u64 + 1)) as usize;
+        candidates.swap(i, j);
+    }
+
+    Some(candidates)
+}
+
+fn process_reveal_tasking(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: RecordId,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let session_account = next_account_info(account_info_iter)?;
+    let submitter_account = next_account_info(account_info_iter)?;
+
+    // Verify submitter is signer
+    if !submitter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut session = load_session(program_id, session_account, &session_id)?;
+
+    // Only the tasker who chose the salt reveals it
+    if session.submitter != *submitter_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if session.tasking_commitment == [0; 32] {
+        return Err(RemoteViewingError::NotTaskerBlind.into());
+    }
+
+    // Revealing earlier would unblind the viewer before the target is fixed
+    if !session.finalized {
+        return Err(RemoteViewingError::SessionNotFinalized.into());
+    }
+
+    if session.tasking_revealed {
+        return Err(RemoteViewingError::TaskingAlreadyRevealed.into());
+    }
+
+    if tasking_commitment(&salt) != session.tasking_commitment {
+        return Err(RemoteViewingError::CommitmentMismatch.into());
+    }
+
+    session.tasking_revealed = true;
+    session.tasking_salt = salt;
+    session.serialize(&mut &mut session_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "Tasking code {} revealed: pool {} target {}",
+        tasking_code(&session.tasking_reference),
+        session.pool_id,
+        session.assigned_target_index
+    );
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_create_experiment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    experiment_id: String,
+    protocol_hash: [u8; 32],
+    analysis_method_hash: [u8; 32],
+    start_time: i64,
+    end_time: i64,
+    allowed_pools: Vec<Pubkey>,
+    planned_sample_size: u32,
+    hypothesis_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let experiment_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify creator is signer
+    if !creator_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Validate input parameters
+    if experiment_id.is_empty()
+        || experiment_id.len() > MAX_ID_LEN
+        || start_time >= end_time
+        || allowed_pools.is_empty()
+        || planned_sample_size == 0
+    {
+        return Err(RemoteViewingError::InvalidExperiment.into());
+    }
+
+    // Derive PDA for experiment
+    let experiment_id_hash = hash(experiment_id.as_bytes());
+    let (experiment_pda, bump) = Pubkey::find_program_address(
+        &[b"experiment", experiment_id_hash.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if experiment_pda != *experiment_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if experiment_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let clock = Clock::get()?;
+    let experiment = Experiment {
+        experiment_id: experiment_id.clone(),
+        creator: *creator_account.key,
+        protocol_hash,
+        analysis_method_hash,
+        start_time,
+        end_time,
+        allowed_pools,
+        planned_sample_size,
+        session_count: 0,
+        created_at: clock.unix_timestamp,
+        hypothesis_hash,
+        plan_updated_at: clock.unix_timestamp,
+    };
+
+    // Calculate required space
+    let space = experiment.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(space);
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_account.key,
+            experiment_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[creator_account.clone(), experiment_account.clone(), system_program.clone()],
+        &[&[b"experiment", experiment_id_hash.as_ref(), &[bump]]],
+    )?;
+
+    experiment.serialize(&mut &mut experiment_account.data.borrow_mut()[..])?;
+
+    msg!("Created experiment: {}", experiment_id);
+    Ok(())
+}
+
+fn process_update_experiment_plan(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    experiment_id: String,
+    protocol_hash: [u8; 32],
+    hypothesis_hash: [u8; 32],
+    analysis_method_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let experiment_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify creator is signer
+    if !creator_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for experiment
+    let experiment_id_hash = hash(experiment_id.as_bytes());
+    let (experiment_pda, _bump) = Pubkey::find_program_address(
+        &[b"experiment", experiment_id_hash.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if experiment_pda != *experiment_account.key || experiment_account.owner != program_id {
+        return Err(RemoteViewingError::ExperimentMismatch.into());
+    }
+
+    let mut experiment = Experiment::try_from_slice(&experiment_account.data.borrow())?;
+
+    // Verify the creator matches
+    if experiment.creator != *creator_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The plan is locked as soon as data collection has started
+    if experiment.session_count > 0 {
+        return Err(RemoteViewingError::PlanLocked.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    experiment.protocol_hash = protocol_hash;
+    experiment.hypothesis_hash = hypothesis_hash;
+    experiment.analysis_method_hash = analysis_method_hash;
+    experiment.plan_updated_at = clock.unix_timestamp;
+
+    experiment.serialize(&mut &mut experiment_account.data.borrow_mut()[..])?;
+
+    msg!("Updated plan for experiment: {}", experiment_id);
+    Ok(())
+}
+
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    governance: Pubkey,
+    upgrade_delay: i64,
+    admin: Pubkey,
+    max_targets_per_pool: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let authority_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive PDA for config
+    let (config_pda, bump) = Pubkey::find_program_address(&[b"config"], program_id);
+
+    // Verify PDA matches
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if config_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Only whoever can already upgrade the program may put governance in place
+    if *program_data_account.key != program_data_address(program_id)
+        || *program_data_account.owner != bpf_loader_upgradeable::id()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if program_upgrade_authority(&program_data_account.data.borrow()) != Some(*authority_account.key) {
+        return Err(RemoteViewingError::NotGovernance.into());
+    }
+
+    if upgrade_delay < MIN_UPGRADE_DELAY {
+        return Err(RemoteViewingError::InvalidUpgradeDelay.into());
+    }
+    if max_targets_per_pool < MIN_MAX_TARGETS_PER_POOL {
+        return Err(RemoteViewingError::InvalidLimits.into());
+    }
+
+    let config = ProgramConfig {
+        governance,
+        upgrade_delay,
+        frozen: false,
+        frozen_changed_at: 0,
+        pending_upgrade_hash: [0; 32],
+        upgrade_announced_at: 0,
+        upgrade_effective_at: 0,
+        last_upgrade_hash: [0; 32],
+        last_upgraded_at: 0,
+        admin,
+        paused: false,
+        max_targets_per_pool,
+    };
+
+    // Calculate required space
+    let space = config.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(space);
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            authority_account.key,
+            config_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority_account.clone(), config_account.clone(), system_program.clone()],
+        &[&[b"config", &[bump]]],
+    )?;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Initialized program config, governance {}, admin {}", governance, admin);
+    Ok(())
+}
+
+fn process_set_frozen(program_id: &Pubkey, accounts: &[AccountInfo], frozen: bool) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_governance(program_id, config_account, governance_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    config.frozen = frozen;
+    config.frozen_changed_at = clock.unix_timestamp;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Program {}", if frozen { "frozen" } else { "unfrozen" });
+    Ok(())
+}
+
+fn process_announce_upgrade(program_id: &Pubkey, accounts: &[AccountInfo], program_hash: [u8; 32]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_governance(program_id, config_account, governance_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    config.pending_upgrade_hash = program_hash;
+    if program_hash == [0; 32] {
+        config.upgrade_announced_at = 0;
+        config.upgrade_effective_at = 0;
+        msg!("Withdrew pending upgrade");
+    } else {
+        // A new announcement always restarts the full delay
+        config.upgrade_announced_at = clock.unix_timestamp;
+        config.upgrade_effective_at = clock
+            .unix_timestamp
+            .checked_add(config.upgrade_delay)
+            .ok_or(RemoteViewingError::InvalidUpgradeDelay)?;
+        msg!(
+            "Announced upgrade to {}, effective at {}",
+            bs58::encode(program_hash).into_string(),
+            config.upgrade_effective_at
+        );
+    }
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+fn process_execute_upgrade(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+    let program_account = next_account_info(account_info_iter)?;
+    let buffer_account = next_account_info(account_info_iter)?;
+    let spill_account = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let bpf_loader_upgradeable = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_governance(program_id, config_account, governance_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if config.pending_upgrade_hash == [0; 32] {
+        return Err(RemoteViewingError::UpgradeNotAnnounced.into());
+    }
+    if clock.unix_timestamp < config.upgrade_effective_at {
+        return Err(RemoteViewingError::UpgradeTimelocked.into());
+    }
+
+    if *program_account.key != *program_id || *program_data_account.key != program_data_address(program_id) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The buffer must hold exactly the announced binary
+    if *buffer_account.owner != bpf_loader_upgradeable::id()
+        || buffer_program_hash(&buffer_account.data.borrow()) != Some(config.pending_upgrade_hash)
+    {
+        return Err(RemoteViewingError::UpgradeHashMismatch.into());
+    }
+
+    let (_config_pda, bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    invoke_signed(
+        &bpf_loader_upgradeable::upgrade(program_id, buffer_account.key, config_account.key, spill_account.key),
+        &[
+            program_data_account.clone(),
+            program_account.clone(),
+            buffer_account.clone(),
+            spill_account.clone(),
+            rent_sysvar.clone(),
+            clock_sysvar.clone(),
+            config_account.clone(),
+            bpf_loader_upgradeable.clone(),
+        ],
+        &[&[b"config", &[bump]]],
+    )?;
+
+    config.last_upgrade_hash = config.pending_upgrade_hash;
+    config.last_upgraded_at = clock.unix_timestamp;
+    config.pending_upgrade_hash = [0; 32];
+    config.upgrade_announced_at = 0;
+    config.upgrade_effective_at = 0;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Upgraded program to {}", bs58::encode(config.last_upgrade_hash).into_string());
+    Ok(())
+}
+
+fn process_set_governance(program_id: &Pubkey, accounts: &[AccountInfo], governance: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_governance(program_id, config_account, governance_account)?;
+    config.governance = governance;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Governance handed to {}", governance);
+    Ok(())
+}
+
+fn process_set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let admin_account = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_admin(program_id, config_account, admin_account)?;
+    config.paused = paused;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Pool creation and session submission {}", if paused { "paused" } else { "resumed" });
+    Ok(())
+}
+
+fn process_set_limits(program_id: &Pubkey, accounts: &[AccountInfo], max_targets_per_pool: u16) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let admin_account = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_admin(program_id, config_account, admin_account)?;
+
+    // Only applies to pools created or extended from now on
+    if max_targets_per_pool < MIN_MAX_TARGETS_PER_POOL {
+        return Err(RemoteViewingError::InvalidLimits.into());
+    }
+    config.max_targets_per_pool = max_targets_per_pool;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Max targets per pool set to {}", max_targets_per_pool);
+    Ok(())
+}
+
+fn process_set_admin(program_id: &Pubkey, accounts: &[AccountInfo], admin: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+
+    let mut config = load_config_for_governance(program_id, config_account, governance_account)?;
+    config.admin = admin;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    msg!("Admin set to {}", admin);
+    Ok(())
+}
+
+// Load the config, checking that `admin_account` signed as its admin
+fn load_config_for_admin(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    admin_account: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    if !admin_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Mark pool as finalized
-    pool.finalized = true;
+    let (config_pda, _bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key || config_account.owner != program_id {
+        return Err(RemoteViewingError::ConfigMismatch.into());
+    }
 
-    // Write updated data to account
-    pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())?;
+    if config.admin != *admin_account.key {
+        return Err(RemoteViewingError::NotAdmin.into());
+    }
+    Ok(config)
+}
 
-    msg!("Finalized pool: {} with {} targets", pool_id, pool.targets.len());
+// Load the config, checking that `governance_account` signed as its
+// governance authority
+fn load_config_for_governance(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    governance_account: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    if !governance_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (config_pda, _bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key || config_account.owner != program_id {
+        return Err(RemoteViewingError::ConfigMismatch.into());
+    }
+
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())?;
+    if config.governance != *governance_account.key {
+        return Err(RemoteViewingError::NotGovernance.into());
+    }
+    Ok(config)
+}
+
+// Read the upgrade authority out of raw program data account data. Layout:
+// u32 state tag (3 = ProgramData), u64 deployment slot, then an
+// `Option<Pubkey>` authority, `None` once the program is immutable.
+fn program_upgrade_authority(data: &[u8]) -> Option<Pubkey> {
+    let metadata = data.get(..UpgradeableLoaderState::size_of_programdata_metadata())?;
+    if metadata[0..4] != 3u32.to_le_bytes() || metadata[12] != 1 {
+        return None;
+    }
+    Some(Pubkey::new_from_array(metadata[13..45].try_into().ok()?))
+}
+
+// SHA-256 of the program binary held in raw loader buffer account data.
+// Layout: u32 state tag (1 = Buffer), `Option<Pubkey>` authority, then the
+// binary, so the hash matches `sha256sum` of the deployed file.
+fn buffer_program_hash(data: &[u8]) -> Option<[u8; 32]> {
+    let metadata_len = UpgradeableLoaderState::size_of_buffer_metadata();
+    if data.len() <= metadata_len || data[0..4] != 1u32.to_le_bytes() {
+        return None;
+    }
+    Some(hash(&data[metadata_len..]).to_bytes())
+}
+
+/// Canonical SHA-256 of a plan document (protocol, hypothesis or analysis
+/// plan) so the same text always yields the same on-chain hash: line endings
+/// are normalized to `\n`, trailing whitespace is stripped from every line,
+/// leading and trailing blank lines are dropped, and the text ends with a
+/// single newline.
+pub fn canonical_plan_hash(document: &str) -> [u8; 32] {
+    let normalized = document.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = normalized.lines().map(str::trim_end).collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+
+    let mut canonical = String::new();
+    if let (Some(first), Some(last)) = (first, last) {
+        for line in &lines[first..=last] {
+            canonical.push_str(line);
+            canonical.push('\n');
+        }
+    }
+    hash(canonical.as_bytes()).to_bytes()
+}
+
+// Count `count` new sessions in `pool` towards an experiment, rejecting them
+// outside the experiment's window or for pools it does not list
+fn record_experiment_sessions(
+    program_id: &Pubkey,
+    experiment_account: &AccountInfo,
+    experiment_key: &Pubkey,
+    pool_key: &Pubkey,
+    clock: &Clock,
+    count: u32,
+) -> ProgramResult {
+    if experiment_account.key != experiment_key || experiment_account.owner != program_id {
+        return Err(RemoteViewingError::ExperimentMismatch.into());
+    }
+
+    let mut experiment = Experiment::try_from_slice(&experiment_account.data.borrow())?;
+
+    if clock.unix_timestamp < experiment.start_time || clock.unix_timestamp > experiment.end_time {
+        return Err(RemoteViewingError::OutsideExperimentWindow.into());
+    }
+
+    if !experiment.allowed_pools.contains(pool_key) {
+        return Err(RemoteViewingError::PoolNotInExperiment.into());
+    }
+
+    experiment.session_count += count;
+    experiment.serialize(&mut &mut experiment_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Commitment to a tasker's hidden salt, passed as `tasking_commitment`
+pub fn tasking_commitment(salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[b"tasking_salt", salt]).to_bytes()
+}
+
+/// Opaque tasking reference of a session. Bound to the session PDA so it is
+/// unique per session, and to the salt commitment so the viewer cannot work
+/// back from a guessed session id.
+pub fn tasking_reference(session: &Pubkey, tasking_commitment: &[u8; 32]) -> [u8; 8] {
+    if *tasking_commitment == [0; 32] {
+        return [0; 8];
+    }
+    let digest = hashv(&[b"tasking", session.as_ref(), tasking_commitment]).to_bytes();
+    let mut reference = [0u8; 8];
+    reference.copy_from_slice(&digest[..8]);
+    reference
+}
+
+/// Render a tasking reference as the classic eight-digit coordinate, e.g. `4821-0375`
+pub fn tasking_code(reference: &[u8; 8]) -> String {
+    let number = u64::from_le_bytes(*reference) % 100_000_000;
+    format!("{:04}-{:04}", number / 10_000, number % 10_000)
+}
+
+// Count a new submission in the viewer's stats, creating the stats account
+// or the pool entry on first use. The submitter pays for any growth.
+fn record_viewer_submission<'a>(
+    program_id: &Pubkey,
+    viewer_stats_account: &AccountInfo<'a>,
+    payer_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    viewer: &Pubkey,
+    pool: &Pubkey,
+) -> ProgramResult {
+    // Derive PDA for viewer stats
+    let (stats_pda, bump) = Pubkey::find_program_address(&[b"viewer_stats", viewer.as_ref()], program_id);
+
+    // Verify PDA matches
+    if stats_pda != *viewer_stats_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let rent = Rent::get()?;
+    let mut stats = if viewer_stats_account.data_len() == 0 {
+        ViewerStats {
+            viewer: *viewer,
+            totals: StatCounters::default(),
+            pools: Vec::new(),
+        }
+    } else {
+        ViewerStats::try_from_slice(&viewer_stats_account.data.borrow())?
+    };
+
+    if !stats.pools.iter().any(|entry| entry.pool == *pool) {
+        stats.pools.push(PoolStatCounters {
+            pool: *pool,
+            counters: StatCounters::default(),
+        });
+    }
+    stats.totals.sessions_submitted += 1;
+    for entry in stats.pools.iter_mut().filter(|entry| entry.pool == *pool) {
+        entry.counters.sessions_submitted += 1;
+    }
+
+    let space = stats.try_to_vec()?.len();
+
+    if viewer_stats_account.data_len() == 0 {
+        // Create the account using invoke_signed for PDA
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account.key,
+                viewer_stats_account.key,
+                rent.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer_account.clone(), viewer_stats_account.clone(), system_program.clone()],
+            &[&[b"viewer_stats", viewer.as_ref(), &[bump]]],
+        )?;
+    } else if space > viewer_stats_account.data_len() {
+        // New pool entry: top up rent and grow the account
+        let new_lamports = rent.minimum_balance(space);
+        let current_lamports = viewer_stats_account.lamports();
+        if new_lamports > current_lamports {
+            invoke(
+                &system_instruction::transfer(payer_account.key, viewer_stats_account.key, new_lamports - current_lamports),
+                &[payer_account.clone(), viewer_stats_account.clone(), system_program.clone()],
+            )?;
+        }
+        viewer_stats_account.realloc(space, false)?;
+    }
+
+    stats.serialize(&mut &mut viewer_stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Apply `update` to the viewer's overall and per-pool counters in place.
+// The pool entry always exists because it is added at submission.
+fn update_viewer_stats(
+    program_id: &Pubkey,
+    viewer_stats_account: &AccountInfo,
+    viewer: &Pubkey,
+    pool: &Pubkey,
+    update: impl Fn(&mut StatCounters),
+) -> ProgramResult {
+    let (stats_pda, _bump) = Pubkey::find_program_address(&[b"viewer_stats", viewer.as_ref()], program_id);
+    if stats_pda != *viewer_stats_account.key || viewer_stats_account.owner != program_id {
+        return Err(RemoteViewingError::ViewerStatsMismatch.into());
+    }
+
+    let mut stats = ViewerStats::try_from_slice(&viewer_stats_account.data.borrow())?;
+    let entry = stats
+        .pools
+        .iter_mut()
+        .find(|entry| entry.pool == *pool)
+        .ok_or(RemoteViewingError::ViewerStatsMismatch)?;
+
+    update(&mut entry.counters);
+    update(&mut stats.totals);
+
+    stats.serialize(&mut &mut viewer_stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Create the stats PDA for a new pool with one histogram bucket per target
+fn create_pool_stats_account<'a>(
+    program_id: &Pubkey,
+    pool_stats_account: &AccountInfo<'a>,
+    pool_key: &Pubkey,
+    creator_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    pool: &TargetPool,
+) -> ProgramResult {
+    // Derive PDA for pool stats
+    let (stats_pda, bump) = Pubkey::find_program_address(&[b"pool_stats", pool_key.as_ref()], program_id);
+
+    // Verify PDA matches
+    if stats_pda != *pool_stats_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let stats = PoolStats::new(*pool_key, pool.targets.len());
+
+    let space = stats.try_to_vec()?.len();
+    let rent = Rent::get()?;
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_account.key,
+            pool_stats_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[creator_account.clone(), pool_stats_account.clone(), system_program.clone()],
+        &[&[b"pool_stats", pool_key.as_ref(), &[bump]]],
+    )?;
+
+    stats.serialize(&mut &mut pool_stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Extend the assignment histogram to cover targets appended to the pool,
+// with the creator paying for the extra rent
+fn grow_pool_stats_account<'a>(
+    program_id: &Pubkey,
+    pool_stats_account: &AccountInfo<'a>,
+    pool_key: &Pubkey,
+    creator_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    pool: &TargetPool,
+) -> ProgramResult {
+    let mut stats = load_pool_stats(program_id, pool_stats_account, pool_key)?;
+    stats.assignment_counts.resize(pool.targets.len(), 0);
+
+    let space = stats.try_to_vec()?.len();
+    if space > pool_stats_account.data_len() {
+        let rent = Rent::get()?;
+        let new_lamports = rent.minimum_balance(space);
+        let current_lamports = pool_stats_account.lamports();
+        if new_lamports > current_lamports {
+            invoke(
+                &system_instruction::transfer(creator_account.key, pool_stats_account.key, new_lamports - current_lamports),
+                &[creator_account.clone(), pool_stats_account.clone(), system_program.clone()],
+            )?;
+        }
+        pool_stats_account.realloc(space, false)?;
+    }
+
+    stats.serialize(&mut &mut pool_stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Add a judgement's score to the viewer and pool stats if it is by the
+// session's designated judge, the first to commit blind before finalization.
+// Judgements of one session share its viewer, target and candidates, so
+// counting each would weigh the session once per judge and overstate the
+// significance of the pool's sum of ranks. Counting only a judgement fixed
+// before the draw, revealed or forfeited as a miss, leaves no choice of which
+// score to count once the target is known. Open judgements and late-finalized
+// sessions are recorded but never counted.
+#[allow(clippy::too_many_arguments)]
+fn record_judgement_stats(
+    program_id: &Pubkey,
+    session: &Session,
+    judge: &Pubkey,
+    pool_account: &AccountInfo,
+    viewer_stats_account: &AccountInfo,
+    pool_stats_account: &AccountInfo,
+    method: JudgingMethod,
+    target_score: u8,
+    candidate_count: usize,
+) -> ProgramResult {
+    if session.late_finalized || *judge != session.designated_judge {
+        return Ok(());
+    }
+
+    update_viewer_stats(program_id, viewer_stats_account, &session.viewer, pool_account.key, |counters| {
+        counters.record_judgement(method, target_score, candidate_count);
+    })?;
+    update_pool_stats(program_id, pool_stats_account, pool_account.key, |stats| {
+        stats.record_judgement(method, target_score, candidate_count);
+        Ok(())
+    })?;
+    Ok(())
+}
+
+// Apply `update` to a pool's stats in place
+fn update_pool_stats(
+    program_id: &Pubkey,
+    pool_stats_account: &AccountInfo,
+    pool_key: &Pubkey,
+    update: impl FnOnce(&mut PoolStats) -> ProgramResult,
+) -> ProgramResult {
+    let mut stats = load_pool_stats(program_id, pool_stats_account, pool_key)?;
+    update(&mut stats)?;
+    stats.serialize(&mut &mut pool_stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+fn load_pool_stats(program_id: &Pubkey, pool_stats_account: &AccountInfo, pool_key: &Pubkey) -> Result<PoolStats, ProgramError> {
+    let (stats_pda, _bump) = Pubkey::find_program_address(&[b"pool_stats", pool_key.as_ref()], program_id);
+    if stats_pda != *pool_stats_account.key || pool_stats_account.owner != program_id {
+        return Err(RemoteViewingError::PoolStatsMismatch.into());
+    }
+    Ok(PoolStats::try_from_slice(&pool_stats_account.data.borrow())?)
+}
+
+/// Hash binding a judge to their scores before the target is revealed.
+/// Computed off-chain when committing and recomputed on-chain when judging.
+pub fn judgement_commitment(method: JudgingMethod, candidates: &[u16], scores: &[u8], salt: &[u8; 32]) -> [u8; 32] {
+    let candidate_bytes: Vec<u8> = candidates.iter().flat_map(|index| index.to_le_bytes()).collect();
+    hashv(&[&[method as u8], &candidate_bytes, scores, salt]).to_bytes()
+}
+
+// Targets a session's judgements are scored among: the target and decoys
+// drawn at finalization, or every outcome's target for an associative session
+fn judging_candidates(session: &Session, pool: &TargetPool) -> Vec<u16> {
+    match session.kind {
+        SessionKind::Standard => session.candidates.clone(),
+        SessionKind::AssociativePrediction => (0..pool.target_count).collect(),
+    }
+}
+
+// Score of `target` among the session's `session_candidates`, with ranks
+// renumbered within that set. Returns the score and the size of the set, or
+// `None` unless the judge scored the whole set and it holds the target.
+fn score_target(
+    method: JudgingMethod,
+    candidates: &[u16],
+    scores: &[u8],
+    session_candidates: &[u16],
+    target: u16,
+) -> Option<(u8, usize)> {
+    if !session_candidates.contains(&target) {
+        return None;
+    }
+
+    let score_of = |index: &u16| candidates.iter().position(|candidate| candidate == index).map(|i| scores[i]);
+    let judged_scores = session_candidates.iter().map(score_of).collect::<Option<Vec<u8>>>()?;
+    let target_score = score_of(&target)?;
+
+    let score = match method {
+        JudgingMethod::Rank => 1 + judged_scores.iter().filter(|&&rank| rank < target_score).count() as u8,
+        JudgingMethod::Rating => target_score,
+    };
+    Some((score, session_candidates.len()))
+}
+
+// Worst score for the method among `candidate_count` candidates, recorded
+// for a forfeited judgement
+fn miss_score(method: JudgingMethod, candidate_count: usize) -> u8 {
+    match method {
+        JudgingMethod::Rank => candidate_count as u8,
+        JudgingMethod::Rating => 1,
+    }
+}
+
+// Check candidate indices are distinct and in range, and scores fit the method
+fn validate_scores(method: JudgingMethod, candidates: &[u16], scores: &[u8], target_count: u16) -> ProgramResult {
+    if candidates.is_empty() || candidates.len() != scores.len() {
+        return Err(RemoteViewingError::InvalidScores.into());
+    }
+
+    for (i, &index) in candidates.iter().enumerate() {
+        if index >= target_count || candidates[..i].contains(&index) {
+            return Err(RemoteViewingError::InvalidScores.into());
+        }
+    }
+
+    let valid = match method {
+        // Ranks must be a permutation of 1..=k
+        JudgingMethod::Rank => {
+            let mut sorted = scores.to_vec();
+            sorted.sort_unstable();
+            sorted.iter().enumerate().all(|(i, &rank)| rank as usize == i + 1)
+        }
+        JudgingMethod::Rating => scores.iter().all(|&rating| (1..=MAX_RATING).contains(&rating)),
+    };
+
+    if !valid {
+        return Err(RemoteViewingError::InvalidScores.into());
+    }
+    Ok(())
+}
+
+// Deserialize a program-owned session and check it is the one named
+fn load_session(program_id: &Pubkey, session_account: &AccountInfo, session_id: &RecordId) -> Result<Session, ProgramError> {
+    if session_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let session = Session::unpack(&session_account.data.borrow())?;
+
+    // Verify session ID matches
+    if session.session_id != *session_id {
+        return Err(RemoteViewingError::SessionNotFound.into());
+    }
+    Ok(session)
+}
+
+// Verify `authorization_account` is the judge authorization for `judge` on `pool_id`
+fn check_judge_authorization(
+    program_id: &Pubkey,
+    authorization_account: &AccountInfo,
+    judge: &Pubkey,
+    pool_id: &RecordId,
+) -> ProgramResult {
+    let pool_id_seed = pool_id.seed();
+    let (pool_pda, _bump) = Pubkey::find_program_address(
+        &[b"target_pool", pool_id_seed.as_ref()],
+        program_id,
+    );
+    let (authorization_pda, _bump) = Pubkey::find_program_address(
+        &[b"judge", pool_pda.as_ref(), judge.as_ref()],
+        program_id,
+    );
+
+    if authorization_pda != *authorization_account.key
+        || authorization_account.owner != program_id
+        || authorization_account.data_len() == 0
+    {
+        return Err(RemoteViewingError::JudgeNotAuthorized.into());
+    }
+    Ok(())
+}
+
+// Create and populate the PDA for a judge's judgement of a session
+fn create_judgement_account<'a>(
+    program_id: &Pubkey,
+    judgement_account: &AccountInfo<'a>,
+    judge_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    judgement: &Judgement,
+) -> ProgramResult {
+    // Derive PDA for judgement
+    let (judgement_pda, bump) = Pubkey::find_program_address(
+        &[b"judgement", judgement.session.as_ref(), judgement.judge.as_ref()],
+        program_id,
+    );
+
+    // Verify PDA matches
+    if judgement_pda != *judgement_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Calculate required space
+    let space = judgement.try_to_vec()?.len();
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(space);
+
+    // Create the account using invoke_signed for PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            judge_account.key,
+            judgement_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[judge_account.clone(), judgement_account.clone(), system_program.clone()],
+        &[&[b"judgement", judgement.session.as_ref(), judgement.judge.as_ref(), &[bump]]],
+    )?;
+
+    // Write data to account
+    judgement.serialize(&mut &mut judgement_account.data.borrow_mut()[..])?;
     Ok(())
 }
 
@@ -642,4 +3835,188 @@ mod tests {
         let index = calculate_target_index(&blockhash, target_count);
         assert!(index < target_count);
     }
+
+    #[test]
+    fn test_select_target_index_skips_completed() {
+        let blockhash = [1u8; 32];
+        let completed = [0u16, 2, 4];
+        let index = select_target_index(&blockhash, 5, &completed).unwrap();
+        assert!(index == 1 || index == 3);
+        assert_eq!(select_target_index(&blockhash, 2, &[0, 1]), None);
+    }
+
+    #[test]
+    fn test_find_slot_hash() {
+        // SlotHashes layout: newest slot first
+        let entries: [(Slot, [u8; 32]); 3] = [(105, [5; 32]), (103, [3; 32]), (100, [1; 32])];
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries.iter() {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+
+        assert_eq!(find_slot_hash(&data, 105), Some([5; 32]));
+        assert_eq!(find_slot_hash(&data, 103), Some([3; 32]));
+        assert_eq!(find_slot_hash(&data, 100), Some([1; 32]));
+        assert_eq!(find_slot_hash(&data, 104), None);
+        assert_eq!(find_slot_hash(&data, 99), None);
+        assert_eq!(find_slot_hash(&data[..20], 105), None);
+
+        // Slot 101 and 102 were skipped
+        assert_eq!(find_first_slot_hash_after(&data, 100), Some((103, [3; 32])));
+        assert_eq!(find_first_slot_hash_after(&data, 103), Some((105, [5; 32])));
+        assert_eq!(find_first_slot_hash_after(&data, 105), None);
+    }
+
+    #[test]
+    fn test_select_candidates() {
+        let entropy = [7u8; 32];
+        let candidates = select_candidates(&entropy, 20, 3, 4).unwrap();
+
+        assert_eq!(candidates.len(), 5);
+        assert!(candidates.contains(&3));
+        let mut unique = candidates.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 5);
+
+        // Deterministic for the same entropy
+        assert_eq!(select_candidates(&entropy, 20, 3, 4), Some(candidates));
+
+        // Not enough targets for the requested decoys
+        assert_eq!(select_candidates(&entropy, 4, 0, 4), None);
+        assert_eq!(select_candidates(&entropy, 4, 0, 3).map(|c| c.len()), Some(4));
+    }
+
+    #[test]
+    fn test_stat_counters_record_judgement() {
+        let mut counters = StatCounters::default();
+        counters.record_judgement(JudgingMethod::Rank, 1, 4);
+        counters.record_judgement(JudgingMethod::Rank, 3, 4);
+        counters.record_judgement(JudgingMethod::Rating, 6, 1);
+
+        assert_eq!(counters.ranked_judgements, 2);
+        assert_eq!(counters.rank_sum, 4);
+        assert_eq!(counters.rank_candidate_sum, 8);
+        assert_eq!(counters.first_place_hits, 1);
+        assert_eq!(counters.rated_judgements, 1);
+        assert_eq!(counters.rating_sum, 6);
+    }
+
+    #[test]
+    fn test_pool_stats_record() {
+        let mut stats = PoolStats::new(Pubkey::default(), 3);
+        stats.record_assignment(2).unwrap();
+        assert!(stats.record_assignment(3).is_err());
+        stats.record_judgement(JudgingMethod::Rank, 1, 4);
+
+        assert_eq!(stats.assignment_counts, vec![0, 0, 1]);
+        assert_eq!(stats.sessions_finalized, 1);
+        assert_eq!(stats.rank_mean_sum_x2, 5);
+        assert_eq!(stats.rank_variance_sum_x12, 15);
+        assert_eq!(stats.first_place_hits, 1);
+    }
+
+    #[test]
+    fn test_tasking_reference() {
+        let session = Pubkey::new_unique();
+        let commitment = tasking_commitment(&[7; 32]);
+
+        let reference = tasking_reference(&session, &commitment);
+        assert_ne!(reference, [0; 8]);
+        assert_ne!(reference, tasking_reference(&Pubkey::new_unique(), &commitment));
+        assert_eq!(tasking_reference(&session, &[0; 32]), [0; 8]);
+
+        let code = tasking_code(&reference);
+        assert_eq!(code.len(), 9);
+        assert_eq!(&code[4..5], "-");
+        assert_eq!(tasking_code(&[0; 8]), "0000-0000");
+    }
+
+    #[test]
+    fn test_canonical_plan_hash() {
+        let plan = "H1: hits exceed chance\nTest: sum of ranks, one-tailed\n";
+        assert_eq!(canonical_plan_hash(plan), hash(plan.as_bytes()).to_bytes());
+        assert_eq!(
+            canonical_plan_hash("\r\nH1: hits exceed chance  \r\nTest: sum of ranks, one-tailed\r\n\r\n"),
+            canonical_plan_hash(plan)
+        );
+        assert_ne!(canonical_plan_hash("H1: hits exceed chance\n"), canonical_plan_hash(plan));
+        assert_eq!(canonical_plan_hash("\n  \n"), hash(b"").to_bytes());
+    }
+
+    #[test]
+    fn test_record_id() {
+        assert!(RecordId::from("pool-1").is_valid());
+        assert!(!RecordId::from("").is_valid());
+        assert!(!RecordId::Text("x".repeat(MAX_ID_LEN + 1)).is_valid());
+        assert!(!RecordId::Fixed([0; 32]).is_valid());
+
+        assert_eq!(RecordId::from("pool-1").seed(), hash(b"pool-1").to_bytes());
+        assert_eq!(RecordId::Fixed([5; 32]).seed(), [5; 32]);
+
+        let uuid = RecordId::from_uuid([
+            0x12, 0x3e, 0x45, 0x67, 0xe8, 0x9b, 0x12, 0xd3, 0xa4, 0x56, 0x42, 0x66, 0x14, 0x17, 0x40, 0x00,
+        ]);
+        assert_eq!(uuid.to_string(), "123e4567-e89b-12d3-a456-426614174000");
+        assert_eq!(RecordId::Fixed([0xab; 32]).to_string(), "ab".repeat(32));
+
+        // A fixed id is shorter than the same UUID stored as text
+        let text = RecordId::from("123e4567-e89b-12d3-a456-426614174000");
+        assert!(uuid.try_to_vec().unwrap().len() < text.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn test_validate_scores() {
+        assert!(validate_scores(JudgingMethod::Rank, &[4, 0, 2], &[2, 1, 3], 5).is_ok());
+        assert!(validate_scores(JudgingMethod::Rank, &[4, 0, 2], &[1, 1, 3], 5).is_err());
+        assert!(validate_scores(JudgingMethod::Rating, &[4, 0], &[7, 1], 5).is_ok());
+        assert!(validate_scores(JudgingMethod::Rating, &[4, 0], &[8, 1], 5).is_err());
+        // Duplicate or out-of-range candidates
+        assert!(validate_scores(JudgingMethod::Rating, &[4, 4], &[3, 3], 5).is_err());
+        assert!(validate_scores(JudgingMethod::Rating, &[5], &[3], 5).is_err());
+    }
+
+    #[test]
+    fn test_score_target() {
+        // Ranks are renumbered within the session's candidates
+        assert_eq!(score_target(JudgingMethod::Rank, &[4, 0, 2], &[2, 1, 3], &[4, 2], 4), Some((1, 2)));
+        assert_eq!(score_target(JudgingMethod::Rating, &[4, 0, 2], &[6, 7, 1], &[2, 4], 4), Some((6, 2)));
+        // Every candidate must be scored, and the target must be among them
+        assert_eq!(score_target(JudgingMethod::Rank, &[4, 0], &[2, 1], &[4, 2], 4), None);
+        assert_eq!(score_target(JudgingMethod::Rank, &[4, 0, 2], &[2, 1, 3], &[0, 3], 3), None);
+    }
+
+    #[test]
+    fn test_judgement_commitment_binds_scores() {
+        let salt = [9u8; 32];
+        let commitment = judgement_commitment(JudgingMethod::Rank, &[1, 2], &[1, 2], &salt);
+        assert_eq!(commitment, judgement_commitment(JudgingMethod::Rank, &[1, 2], &[1, 2], &salt));
+        assert_ne!(commitment, judgement_commitment(JudgingMethod::Rank, &[1, 2], &[2, 1], &salt));
+        assert_ne!(commitment, judgement_commitment(JudgingMethod::Rating, &[1, 2], &[1, 2], &salt));
+    }
+
+    #[test]
+    fn test_loader_account_parsing() {
+        let authority = Pubkey::new_unique();
+        let mut program_data = 3u32.to_le_bytes().to_vec();
+        program_data.extend_from_slice(&42u64.to_le_bytes());
+        program_data.push(1);
+        program_data.extend_from_slice(authority.as_ref());
+        program_data.extend_from_slice(b"\x7fELF");
+        assert_eq!(program_upgrade_authority(&program_data), Some(authority));
+
+        // Immutable programs have no authority
+        program_data[12] = 0;
+        assert_eq!(program_upgrade_authority(&program_data), None);
+        assert_eq!(program_upgrade_authority(&program_data[..20]), None);
+
+        let binary = b"\x7fELF program bytes";
+        let mut buffer = 1u32.to_le_bytes().to_vec();
+        buffer.push(1);
+        buffer.extend_from_slice(authority.as_ref());
+        buffer.extend_from_slice(binary);
+        assert_eq!(buffer_program_hash(&buffer), Some(hash(binary).to_bytes()));
+        assert_eq!(buffer_program_hash(&program_data), None);
+    }
 } 
\ No newline at end of file
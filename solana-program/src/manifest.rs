@@ -0,0 +1,190 @@
+// Media manifests for multi-item sessions
+//
+// A session bundle (sketch pages, typed transcript, ...) is committed on-chain
+// as the Merkle root of an ordered list of item hashes. Individual items can
+// later be revealed with an inclusion proof without disclosing the rest.
+//
+// Item hashes use the session's `HashAlgorithm`; tree nodes always use SHA-256
+// with domain-separated leaf/node prefixes. A single-item manifest's root is
+// the item hash itself, so it matches the plain `session_media_hash` mode.
+
+use crate::HashAlgorithm;
+use solana_program::hash::hashv;
+
+const LEAF_PREFIX: &[u8] = &[0x00];
+const NODE_PREFIX: &[u8] = &[0x01];
+
+/// Ordered list of media item hashes for one session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaManifest {
+    pub items: Vec<[u8; 32]>,
+}
+
+/// Proof that an item hash sits at `index` in a manifest of `item_count` items
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub index: u16,
+    pub item_count: u16,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MediaManifest {
+    pub fn new(items: Vec<[u8; 32]>) -> Self {
+        Self { items }
+    }
+
+    /// Hash each media item in order with `algorithm`. Returns `None` for
+    /// algorithms that cannot be computed from raw bytes (perceptual hashes).
+    pub fn from_media(algorithm: HashAlgorithm, media: &[&[u8]]) -> Option<Self> {
+        let items = media
+            .iter()
+            .map(|data| algorithm.digest(data))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::new(items))
+    }
+
+    /// Value to pass as `media_item_count` in `SubmitSession`
+    pub fn item_count(&self) -> u16 {
+        self.items.len() as u16
+    }
+
+    /// Value to pass as `session_media_hash` in `SubmitSession`
+    pub fn root(&self) -> [u8; 32] {
+        match self.items.len() {
+            0 => [0; 32],
+            1 => self.items[0],
+            _ => {
+                let mut level = self.leaves();
+                while level.len() > 1 {
+                    level = next_level(&level);
+                }
+                level[0]
+            }
+        }
+    }
+
+    /// Build an inclusion proof for the item at `index`
+    pub fn proof(&self, index: u16) -> Option<InclusionProof> {
+        if index as usize >= self.items.len() {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        if self.items.len() > 1 {
+            let mut level = self.leaves();
+            let mut position = index as usize;
+            while level.len() > 1 {
+                if let Some(sibling) = level.get(position ^ 1) {
+                    siblings.push(*sibling);
+                }
+                level = next_level(&level);
+                position /= 2;
+            }
+        }
+
+        Some(InclusionProof {
+            index,
+            item_count: self.item_count(),
+            siblings,
+        })
+    }
+
+    fn leaves(&self) -> Vec<[u8; 32]> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| leaf_hash(index as u16, item))
+            .collect()
+    }
+}
+
+/// Check that `item_hash` is committed by `root` at the proof's position
+pub fn verify_inclusion(root: &[u8; 32], item_hash: &[u8; 32], proof: &InclusionProof) -> bool {
+    if proof.index >= proof.item_count {
+        return false;
+    }
+    if proof.item_count == 1 {
+        return proof.siblings.is_empty() && item_hash == root;
+    }
+
+    let mut siblings = proof.siblings.iter();
+    let mut current = leaf_hash(proof.index, item_hash);
+    let mut position = proof.index as usize;
+    let mut width = proof.item_count as usize;
+
+    while width > 1 {
+        // The last node of an odd-width level is promoted without a sibling
+        if position ^ 1 < width {
+            let sibling = match siblings.next() {
+                Some(sibling) => sibling,
+                None => return false,
+            };
+            current = if position % 2 == 0 {
+                node_hash(&current, sibling)
+            } else {
+                node_hash(sibling, &current)
+            };
+        }
+        position /= 2;
+        width = width.div_ceil(2);
+    }
+
+    siblings.next().is_none() && current == *root
+}
+
+fn leaf_hash(index: u16, item: &[u8; 32]) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, &index.to_le_bytes(), item]).to_bytes()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| [i; 32]).collect()
+    }
+
+    #[test]
+    fn test_proofs_verify_for_every_item() {
+        for n in 1..=9 {
+            let manifest = MediaManifest::new(items(n));
+            let root = manifest.root();
+            for index in 0..n as u16 {
+                let proof = manifest.proof(index).unwrap();
+                assert!(verify_inclusion(&root, &manifest.items[index as usize], &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_rejects_wrong_item_or_position() {
+        let manifest = MediaManifest::new(items(5));
+        let root = manifest.root();
+        let mut proof = manifest.proof(2).unwrap();
+        assert!(!verify_inclusion(&root, &[9; 32], &proof));
+        proof.index = 3;
+        assert!(!verify_inclusion(&root, &manifest.items[2], &proof));
+    }
+
+    #[test]
+    fn test_single_item_root_is_item_hash() {
+        let manifest = MediaManifest::new(vec![[7; 32]]);
+        assert_eq!(manifest.root(), [7; 32]);
+        assert!(manifest.proof(1).is_none());
+    }
+}
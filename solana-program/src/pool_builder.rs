@@ -0,0 +1,350 @@
+// Reproducible target pools from a directory of images
+//
+// Our pipeline and independent verifiers must derive exactly the same target
+// list from the same files, so the directory walk and ordering are fixed here:
+// every regular file below the root is included except hidden entries (names
+// starting with `.`), identified by its `/`-separated path relative to the
+// root, and ordered by the bytes of that path. Names are not Unicode
+// normalized.
+//
+// The resulting `PoolManifest` is the public artifact auditors check against
+// the on-chain pool. Its canonical JSON (compact, keys sorted) is what the
+// pool creator signs; `signed_json` wraps it with the signer and signature.
+// Not compiled into the on-chain program.
+
+use crate::{hex, HashAlgorithm, RecordId, RemoteViewingInstruction, TargetPool};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Version of the manifest format, recorded in the manifest itself
+pub const MANIFEST_VERSION: u64 = 1;
+
+/// Target hashes per `CreateTargetPool`/`AppendTargetsToPool` instruction,
+/// keeping either one with a 64-byte text id in a single transaction
+pub const TARGETS_PER_INSTRUCTION: usize = 24;
+
+// Largest pool a target count can describe; the program config may set a
+// lower limit
+const MAX_TARGETS: usize = u16::MAX as usize;
+
+#[derive(Debug)]
+pub enum PoolBuildError {
+    Io(PathBuf, std::io::Error),
+    /// Paths must be valid UTF-8 to have a canonical order
+    NonUtf8Path(PathBuf),
+    NoFiles,
+    TooManyTargets(usize),
+    /// Perceptual hashes need an image decoder
+    DigestUnavailable,
+}
+
+impl fmt::Display for PoolBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PoolBuildError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            PoolBuildError::NonUtf8Path(path) => write!(f, "{}: path is not valid UTF-8", path.display()),
+            PoolBuildError::NoFiles => f.write_str("no target files found"),
+            PoolBuildError::TooManyTargets(count) => {
+                write!(f, "{} targets found, a pool holds at most {}", count, MAX_TARGETS)
+            }
+            PoolBuildError::DigestUnavailable => f.write_str("hash algorithm cannot be computed from file bytes"),
+        }
+    }
+}
+
+impl std::error::Error for PoolBuildError {}
+
+/// One target of a pool manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestTarget {
+    /// Path relative to the pool directory, `/`-separated
+    pub file: String,
+    pub hash: [u8; 32],
+}
+
+/// Ordered list of a pool's target files and their hashes. The position of
+/// a target in `targets` is its on-chain target index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolManifest {
+    pub pool_id: RecordId,
+    pub hash_algorithm: HashAlgorithm,
+    pub targets: Vec<ManifestTarget>,
+}
+
+/// Ways a manifest can disagree with an on-chain pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    PoolId,
+    HashAlgorithm,
+    TargetCount { manifest: usize, pool: usize },
+    TargetHash { index: u16 },
+}
+
+/// Relative paths of the files that make up a pool in `dir`, in canonical order
+pub fn collect_target_files(dir: &Path) -> Result<Vec<String>, PoolBuildError> {
+    let mut files = Vec::new();
+    walk(dir, "", &mut files)?;
+    files.sort_unstable_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+    Ok(files)
+}
+
+fn walk(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), PoolBuildError> {
+    let io_error = |err| PoolBuildError::Io(dir.to_path_buf(), err);
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| PoolBuildError::NonUtf8Path(entry.path()))?;
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let relative = format!("{}{}", prefix, name);
+        let file_type = entry.file_type().map_err(io_error)?;
+        if file_type.is_dir() {
+            walk(&entry.path(), &format!("{}/", relative), files)?;
+        } else if file_type.is_file() {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Walk `dir` and hash every target file with `hash_algorithm`
+pub fn build_manifest(
+    pool_id: RecordId,
+    dir: &Path,
+    hash_algorithm: HashAlgorithm,
+) -> Result<PoolManifest, PoolBuildError> {
+    let files = collect_target_files(dir)?;
+    if files.is_empty() {
+        return Err(PoolBuildError::NoFiles);
+    }
+    if files.len() > MAX_TARGETS {
+        return Err(PoolBuildError::TooManyTargets(files.len()));
+    }
+
+    let targets = files
+        .into_iter()
+        .map(|file| {
+            let path = dir.join(&file);
+            let data = std::fs::read(&path).map_err(|err| PoolBuildError::Io(path, err))?;
+            let hash = hash_algorithm.digest(&data).ok_or(PoolBuildError::DigestUnavailable)?;
+            Ok(ManifestTarget { file, hash })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(PoolManifest {
+        pool_id,
+        hash_algorithm,
+        targets,
+    })
+}
+
+impl PoolManifest {
+    pub fn target_hashes(&self) -> Vec<[u8; 32]> {
+        self.targets.iter().map(|target| target.hash).collect()
+    }
+
+    /// `CreateTargetPool` with the first batch of targets, then one
+    /// `AppendTargetsToPool` per further batch, in order
+    pub fn instructions(&self, min_finalization_delay: u64, max_finalization_delay: u64) -> Vec<RemoteViewingInstruction> {
+        let hashes = self.target_hashes();
+        let mut batches = hashes.chunks(TARGETS_PER_INSTRUCTION);
+        let create = RemoteViewingInstruction::CreateTargetPool {
+            pool_id: self.pool_id.clone(),
+            target_hashes: batches.next().unwrap_or_default().to_vec(),
+            hash_algorithm: self.hash_algorithm,
+            min_finalization_delay,
+            max_finalization_delay,
+        };
+
+        std::iter::once(create)
+            .chain(batches.map(|batch| RemoteViewingInstruction::AppendTargetsToPool {
+                pool_id: self.pool_id.clone(),
+                target_hashes: batch.to_vec(),
+            }))
+            .collect()
+    }
+
+    /// Check that `pool` holds exactly this manifest's targets
+    pub fn check_pool(&self, pool: &TargetPool) -> Result<(), ManifestMismatch> {
+        if pool.pool_id != self.pool_id {
+            return Err(ManifestMismatch::PoolId);
+        }
+        if pool.hash_algorithm != self.hash_algorithm {
+            return Err(ManifestMismatch::HashAlgorithm);
+        }
+        if pool.targets.len() != self.targets.len() {
+            return Err(ManifestMismatch::TargetCount {
+                manifest: self.targets.len(),
+                pool: pool.targets.len(),
+            });
+        }
+        match self.targets.iter().zip(&pool.targets).position(|(target, hash)| target.hash != *hash) {
+            Some(index) => Err(ManifestMismatch::TargetHash { index: index as u16 }),
+            None => Ok(()),
+        }
+    }
+
+    /// Compact JSON with sorted keys; the bytes the pool creator signs
+    pub fn canonical_json(&self) -> String {
+        self.to_value().to_string()
+    }
+
+    /// Published manifest: the canonical manifest pretty-printed, plus the
+    /// signer and their ed25519 signature over `canonical_json`
+    pub fn signed_json(&self, signer: &Pubkey, signature: &[u8; 64]) -> String {
+        let document = json!({
+            "manifest": self.to_value(),
+            "signature": bs58::encode(signature).into_string(),
+            "signer": signer.to_string(),
+        });
+        let mut out = serde_json::to_string_pretty(&document).expect("manifest serializes");
+        out.push('\n');
+        out
+    }
+
+    // Keys are inserted in sorted order; with `preserve_order` that is also
+    // the order they are written in
+    fn to_value(&self) -> Value {
+        let targets: Vec<Value> = self
+            .targets
+            .iter()
+            .enumerate()
+            .map(|(index, target)| json!({ "file": target.file, "hash": hex(&target.hash), "index": index }))
+            .collect();
+        json!({
+            "hash_algorithm": format!("{:?}", self.hash_algorithm),
+            "pool_id": pool_id_string(&self.pool_id),
+            "target_count": self.targets.len(),
+            "targets": targets,
+            "version": MANIFEST_VERSION,
+        })
+    }
+}
+
+/// How a pool id is written in a manifest: text ids as-is, fixed ids as
+/// `hex:` followed by the 32 bytes
+pub fn pool_id_string(pool_id: &RecordId) -> String {
+    match pool_id {
+        RecordId::Text(id) => id.clone(),
+        RecordId::Fixed(bytes) => format!("hex:{}", hex(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rv-pool-builder-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_canonical_order_and_hashes() {
+        let dir = temp_dir("order");
+        std::fs::create_dir_all(dir.join("b/nested")).unwrap();
+        std::fs::create_dir_all(dir.join(".cache")).unwrap();
+        for (file, data) in [
+            ("B.jpg", "upper"),
+            ("a.jpg", "lower"),
+            ("b/nested/c.jpg", "nested"),
+            ("b.jpg", "sibling"),
+            (".DS_Store", "hidden"),
+            (".cache/x.jpg", "hidden"),
+        ] {
+            std::fs::write(dir.join(file), data).unwrap();
+        }
+
+        let files = collect_target_files(&dir).unwrap();
+        assert_eq!(files, ["B.jpg", "a.jpg", "b.jpg", "b/nested/c.jpg"]);
+
+        let manifest = build_manifest(RecordId::from("pool"), &dir, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(manifest.targets[1].hash, HashAlgorithm::Sha256.digest(b"lower").unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_instruction_batches() {
+        let manifest = PoolManifest {
+            pool_id: RecordId::from("pool"),
+            hash_algorithm: HashAlgorithm::Blake3,
+            targets: (0..50)
+                .map(|i| ManifestTarget {
+                    file: format!("{:02}.jpg", i),
+                    hash: [i as u8; 32],
+                })
+                .collect(),
+        };
+
+        let instructions = manifest.instructions(2, 150);
+        assert_eq!(instructions.len(), 3);
+        let mut hashes = Vec::new();
+        for (i, instruction) in instructions.into_iter().enumerate() {
+            match instruction {
+                RemoteViewingInstruction::CreateTargetPool { target_hashes, .. } if i == 0 => hashes.extend(target_hashes),
+                RemoteViewingInstruction::AppendTargetsToPool { target_hashes, .. } if i > 0 => {
+                    hashes.extend(target_hashes)
+                }
+                other => panic!("unexpected instruction {:?}", other),
+            }
+        }
+        assert_eq!(hashes, manifest.target_hashes());
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let manifest = PoolManifest {
+            pool_id: RecordId::Fixed([0xab; 32]),
+            hash_algorithm: HashAlgorithm::Sha256,
+            targets: vec![ManifestTarget {
+                file: "dir/\"quoted\".jpg".to_string(),
+                hash: [0; 32],
+            }],
+        };
+        assert_eq!(
+            manifest.canonical_json(),
+            format!(
+                "{{\"hash_algorithm\":\"Sha256\",\"pool_id\":\"hex:{}\",\"target_count\":1,\"targets\":[{{\"file\":\"dir/\\\"quoted\\\".jpg\",\"hash\":\"{}\",\"index\":0}}],\"version\":1}}",
+                "ab".repeat(32),
+                "00".repeat(32)
+            )
+        );
+
+        // The published manifest carries the same manifest, pretty-printed
+        let signed: Value = serde_json::from_str(&manifest.signed_json(&Pubkey::default(), &[1; 64])).unwrap();
+        assert_eq!(signed["manifest"].to_string(), manifest.canonical_json());
+    }
+
+    #[test]
+    fn test_check_pool() {
+        let manifest = PoolManifest {
+            pool_id: RecordId::from("pool"),
+            hash_algorithm: HashAlgorithm::Sha256,
+            targets: (0..3)
+                .map(|i| ManifestTarget {
+                    file: i.to_string(),
+                    hash: [i as u8; 32],
+                })
+                .collect(),
+        };
+        let mut pool = test_fixtures::pool(3);
+        assert_eq!(manifest.check_pool(&pool), Ok(()));
+
+        pool.targets[2] = [9; 32];
+        assert_eq!(manifest.check_pool(&pool), Err(ManifestMismatch::TargetHash { index: 2 }));
+        pool.targets.pop();
+        assert_eq!(
+            manifest.check_pool(&pool),
+            Err(ManifestMismatch::TargetCount { manifest: 3, pool: 2 })
+        );
+    }
+}
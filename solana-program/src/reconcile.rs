@@ -0,0 +1,187 @@
+// Reconciliation of an off-chain session database against on-chain sessions
+//
+// The database keeps session metadata while the chain keeps the proofs; this
+// compares an export of the former with decoded `Session` accounts and lists
+// where they disagree. Not compiled into the on-chain program.
+
+use crate::{hex, RecordId, Session};
+use std::collections::{HashMap, HashSet};
+
+/// One exported database row. Blank columns are not compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedSession {
+    pub session_id: RecordId,
+    pub pool_id: Option<RecordId>,
+    pub media_hash: Option<[u8; 32]>,
+    /// Target index the database recorded for the session
+    pub expected_target: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchField {
+    PoolId,
+    MediaHash,
+    AssignedTarget,
+}
+
+/// A field where the database and the session account disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub session_id: RecordId,
+    pub field: MismatchField,
+    pub expected: String,
+    pub on_chain: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    /// Sessions that agree on every compared field and are finalized
+    pub matched: usize,
+    /// In the database but with no session account
+    pub missing: Vec<RecordId>,
+    pub mismatched: Vec<Mismatch>,
+    /// On-chain but not finalized yet, so no target to compare
+    pub unfinalized: Vec<RecordId>,
+    /// Session accounts the database does not know about
+    pub orphans: Vec<RecordId>,
+    /// Listed more than once in the database export; only the first row is compared
+    pub duplicates: Vec<RecordId>,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty()
+            && self.mismatched.is_empty()
+            && self.unfinalized.is_empty()
+            && self.orphans.is_empty()
+            && self.duplicates.is_empty()
+    }
+}
+
+/// Compare exported rows with the decoded sessions. `on_chain` should cover
+/// the same scope as the export (e.g. the same pools) or every session outside
+/// it is reported as an orphan.
+pub fn reconcile(expected: &[ExpectedSession], on_chain: &[Session]) -> ReconciliationReport {
+    let sessions: HashMap<&RecordId, &Session> =
+        on_chain.iter().map(|session| (&session.session_id, session)).collect();
+    let mut report = ReconciliationReport::default();
+    let mut seen = HashSet::new();
+
+    for row in expected {
+        if !seen.insert(&row.session_id) {
+            report.duplicates.push(row.session_id.clone());
+            continue;
+        }
+        let Some(session) = sessions.get(&row.session_id) else {
+            report.missing.push(row.session_id.clone());
+            continue;
+        };
+
+        let mut mismatches = Vec::new();
+        let mut mismatch = |field, expected: String, on_chain: String| {
+            mismatches.push(Mismatch {
+                session_id: row.session_id.clone(),
+                field,
+                expected,
+                on_chain,
+            })
+        };
+        if let Some(pool_id) = row.pool_id.as_ref().filter(|pool_id| **pool_id != session.pool_id) {
+            mismatch(MismatchField::PoolId, pool_id.to_string(), session.pool_id.to_string());
+        }
+        if let Some(media_hash) = row.media_hash.filter(|hash| *hash != session.session_media_hash) {
+            mismatch(MismatchField::MediaHash, hex(&media_hash), hex(&session.session_media_hash));
+        }
+        // Unfinalized sessions have no assignment to compare yet
+        if session.finalized {
+            if let Some(target) = row.expected_target.filter(|target| *target != session.assigned_target_index) {
+                mismatch(
+                    MismatchField::AssignedTarget,
+                    target.to_string(),
+                    session.assigned_target_index.to_string(),
+                );
+            }
+        } else {
+            report.unfinalized.push(row.session_id.clone());
+        }
+
+        if session.finalized && mismatches.is_empty() {
+            report.matched += 1;
+        }
+        report.mismatched.extend(mismatches);
+    }
+
+    report.orphans = on_chain
+        .iter()
+        .filter(|session| !seen.contains(&session.session_id))
+        .map(|session| session.session_id.clone())
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn session(id: &str, finalized: bool, assigned_target_index: u16) -> Session {
+        let mut session = test_fixtures::session(RecordId::from(id), &test_fixtures::pool(5));
+        session.finalized = finalized;
+        session.assigned_target_index = assigned_target_index;
+        session
+    }
+
+    fn row(id: &str, media_hash: [u8; 32], expected_target: Option<u16>) -> ExpectedSession {
+        ExpectedSession {
+            session_id: RecordId::from(id),
+            pool_id: Some(RecordId::from("pool")),
+            media_hash: Some(media_hash),
+            expected_target,
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let on_chain = [
+            session("ok", true, 3),
+            session("wrong-target", true, 3),
+            session("wrong-media", true, 3),
+            session("pending", false, u16::MAX),
+            session("orphan", true, 1),
+        ];
+        let expected = [
+            row("ok", [1; 32], Some(3)),
+            row("wrong-target", [1; 32], Some(4)),
+            row("wrong-media", [2; 32], None),
+            row("pending", [1; 32], Some(0)),
+            row("missing", [1; 32], None),
+            row("ok", [1; 32], Some(3)),
+        ];
+
+        let report = reconcile(&expected, &on_chain);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.missing, [RecordId::from("missing")]);
+        assert_eq!(report.unfinalized, [RecordId::from("pending")]);
+        assert_eq!(report.orphans, [RecordId::from("orphan")]);
+        assert_eq!(report.duplicates, [RecordId::from("ok")]);
+        assert_eq!(
+            report.mismatched,
+            [
+                Mismatch {
+                    session_id: RecordId::from("wrong-target"),
+                    field: MismatchField::AssignedTarget,
+                    expected: "4".to_string(),
+                    on_chain: "3".to_string(),
+                },
+                Mismatch {
+                    session_id: RecordId::from("wrong-media"),
+                    field: MismatchField::MediaHash,
+                    expected: "02".repeat(32),
+                    on_chain: "01".repeat(32),
+                },
+            ]
+        );
+        assert!(!report.is_clean());
+        assert!(reconcile(&expected[..1], &on_chain[..1]).is_clean());
+    }
+}
@@ -0,0 +1,461 @@
+// Self-contained verification reports for sharing session results
+//
+// A report bundles the raw session and pool accounts, the transactions that
+// touched the session, the slot hash the assignment was drawn from and the
+// assignment recomputed step by step, rendered as JSON and as a static HTML
+// page that needs nothing but a browser. Not compiled into the on-chain program.
+
+use crate::legacy::{decode_pool, decode_session};
+use crate::pool_builder::pool_id_string;
+use crate::verify::{verify_target_assignment, VerificationError};
+use crate::{hex, pool_address, select_candidates, session_address, Session, SessionKind, TargetPool};
+use serde_json::{json, Value};
+use solana_program::{clock::Slot, pubkey::Pubkey};
+
+/// Bumped whenever fields are removed or change meaning
+pub const REPORT_VERSION: u64 = 1;
+
+/// A transaction that referenced the session account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionEvidence {
+    pub signature: String,
+    pub slot: Slot,
+    pub block_time: Option<i64>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotHashEvidence {
+    /// Slot whose hash drove the assignment
+    pub slot: Slot,
+    /// Hash the program copied into the session from SlotHashes
+    pub recorded: [u8; 32],
+    /// Hash of `slot` in a SlotHashes snapshot taken for the report, if the
+    /// slot was still retained
+    pub observed: Option<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationStep {
+    pub description: String,
+    pub value: String,
+}
+
+#[derive(Debug)]
+pub enum ReportError {
+    InvalidSessionData,
+    InvalidPoolData,
+    /// The pool account is not the one the session was submitted to
+    PoolMismatch,
+}
+
+#[derive(Debug)]
+pub struct VerificationReport {
+    pub program_id: Pubkey,
+    pub session_address: Pubkey,
+    pub pool_address: Pubkey,
+    pub session: Session,
+    pub pool: TargetPool,
+    pub session_data: Vec<u8>,
+    pub pool_data: Vec<u8>,
+    pub transactions: Vec<TransactionEvidence>,
+    pub slot_hash: SlotHashEvidence,
+    pub steps: Vec<DerivationStep>,
+    /// Recomputed target index, `None` until the session is finalized
+    pub target_index: Option<u16>,
+    pub target_hash: Option<[u8; 32]>,
+    pub result: Result<(), VerificationError>,
+}
+
+/// Build a report from raw account data as fetched from the chain. Decoding
+/// here rather than taking decoded accounts keeps the published bytes and the
+/// published fields in agreement.
+pub fn build_report(
+    program_id: &Pubkey,
+    session_data: &[u8],
+    pool_data: &[u8],
+    transactions: Vec<TransactionEvidence>,
+    observed_slot_hash: Option<[u8; 32]>,
+) -> Result<VerificationReport, ReportError> {
+    let session = decode_session(session_data).ok_or(ReportError::InvalidSessionData)?;
+    let pool = decode_pool(pool_data).ok_or(ReportError::InvalidPoolData)?;
+    if pool.pool_id != session.pool_id {
+        return Err(ReportError::PoolMismatch);
+    }
+
+    let slot_hash = SlotHashEvidence {
+        slot: session.entropy_slot,
+        recorded: session.submission_blockhash,
+        observed: observed_slot_hash,
+    };
+    let (steps, target_index) = derive_target_index(&session, &pool);
+    let target_hash = target_index.and_then(|index| pool.targets.get(index as usize).copied());
+    let result = match slot_hash.observed {
+        Some(observed) if session.kind == SessionKind::Standard && observed != slot_hash.recorded => {
+            Err(VerificationError::SlotHashMismatch)
+        }
+        _ => verify_target_assignment(&session, &pool).map(|_| ()),
+    };
+
+    Ok(VerificationReport {
+        program_id: *program_id,
+        session_address: session_address(program_id, &session.session_id),
+        pool_address: pool_address(program_id, &session.pool_id),
+        session,
+        pool,
+        session_data: session_data.to_vec(),
+        pool_data: pool_data.to_vec(),
+        transactions,
+        slot_hash,
+        steps,
+        target_index,
+        target_hash,
+        result,
+    })
+}
+
+// Walk through the assignment the way `select_target_index` and
+// `select_candidates` compute it
+fn derive_target_index(session: &Session, pool: &TargetPool) -> (Vec<DerivationStep>, Option<u16>) {
+    let mut steps = Vec::new();
+    let mut step = |description: String, value: String| steps.push(DerivationStep { description, value });
+
+    if !session.finalized {
+        step("Session is not finalized; no target has been drawn".to_string(), String::new());
+        return (steps, None);
+    }
+
+    let index = match session.kind {
+        SessionKind::Standard => {
+            let hash = &session.submission_blockhash;
+            // The legacy program took the hash as an argument rather than
+            // reading SlotHashes, so it has no entropy slot
+            let source = if session.layout_version == 0 {
+                format!(
+                    "Blockhash supplied by the finalizer of the legacy program, submitted at slot {}",
+                    session.submission_slot
+                )
+            } else if session.late_finalized {
+                format!(
+                    "Hash of slot {}, the first slot after the re-anchor at slot {}",
+                    session.entropy_slot, session.reanchor_slot
+                )
+            } else {
+                format!("Hash of slot {} from SlotHashes", session.entropy_slot)
+            };
+            step(source, hex(hash));
+
+            let value = u64::from_be_bytes(hash[..8].try_into().unwrap());
+            step("First 8 bytes read as a big-endian u64".to_string(), value.to_string());
+            step("Targets in the pool".to_string(), pool.target_count.to_string());
+
+            let completed = &session.completed_target_indices;
+            let excluded = if completed.is_empty() {
+                "none".to_string()
+            } else {
+                completed.iter().map(u16::to_string).collect::<Vec<_>>().join(", ")
+            };
+            step("Targets already completed by the viewer, excluded".to_string(), excluded);
+
+            let available: Vec<u16> = (0..pool.target_count).filter(|index| !completed.contains(index)).collect();
+            step("Targets still available, in index order".to_string(), available.len().to_string());
+            if available.is_empty() {
+                step("No targets left to assign".to_string(), String::new());
+                return (steps, None);
+            }
+
+            let position = value % available.len() as u64;
+            step(format!("{} mod {}", value, available.len()), position.to_string());
+            let index = available[position as usize];
+            step(format!("Available target at position {}", position), index.to_string());
+
+            if session.decoy_count > 0 {
+                let candidates = select_candidates(hash, pool.target_count, index, session.decoy_count).map_or_else(
+                    || "too few targets".to_string(),
+                    |candidates| candidates.iter().map(u16::to_string).collect::<Vec<_>>().join(", "),
+                );
+                step(
+                    format!(
+                        "Target and {} decoys drawn from the same hash, shuffled for judging",
+                        session.decoy_count
+                    ),
+                    candidates,
+                );
+            }
+            index
+        }
+        SessionKind::AssociativePrediction => {
+            let label = pool
+                .outcome_labels
+                .get(pool.resolved_outcome as usize)
+                .cloned()
+                .unwrap_or_default();
+            step(format!("Outcome resolved at slot {}", pool.resolved_slot), label);
+            step(
+                "Associative sessions are assigned the target of the resolved outcome".to_string(),
+                pool.resolved_outcome.to_string(),
+            );
+            pool.resolved_outcome
+        }
+    };
+
+    step("Index recorded by the program".to_string(), session.assigned_target_index.to_string());
+    if let Some(hash) = pool.targets.get(index as usize) {
+        step(format!("Target hash at index {}", index), hex(hash));
+    }
+    (steps, Some(index))
+}
+
+// Renders the JSON report as nested tables, so both forms show the same fields
+fn write_html(value: &Value, out: &mut String) {
+    // Long lists and blobs stay collapsed so the page remains readable
+    const COLLAPSE_ITEMS: usize = 16;
+    const COLLAPSE_CHARS: usize = 128;
+
+    match value {
+        Value::Null => out.push_str("<em>none</em>"),
+        Value::Bool(value) => out.push_str(if *value { "yes" } else { "no" }),
+        Value::Number(value) => out.push_str(&value.to_string()),
+        Value::String(value) if value.len() > COLLAPSE_CHARS => out.push_str(&format!(
+            "<details><summary>{} characters</summary><pre>{}</pre></details>",
+            value.len(),
+            html_escape(value)
+        )),
+        Value::String(value) => out.push_str(&format!("<code>{}</code>", html_escape(value))),
+        Value::Array(items) => {
+            let collapse = items.len() > COLLAPSE_ITEMS;
+            if collapse {
+                out.push_str(&format!("<details><summary>{} items</summary>", items.len()));
+            }
+            out.push_str("<ol start=\"0\">");
+            for item in items {
+                out.push_str("<li>");
+                write_html(item, out);
+                out.push_str("</li>");
+            }
+            out.push_str("</ol>");
+            if collapse {
+                out.push_str("</details>");
+            }
+        }
+        Value::Object(fields) => {
+            out.push_str("<table>");
+            for (key, value) in fields {
+                out.push_str(&format!("<tr><th>{}</th><td>", html_escape(key)));
+                write_html(value, out);
+                out.push_str("</td></tr>");
+            }
+            out.push_str("</table>");
+        }
+    }
+}
+
+impl VerificationReport {
+    // Field order is kept (`preserve_order`), so the JSON and HTML list
+    // fields in the order written here
+    fn to_value(&self) -> Value {
+        let session = &self.session;
+        let pool = &self.pool;
+        let derivation: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|step| json!({ "description": step.description, "value": step.value }))
+            .collect();
+        let transactions: Vec<Value> = self
+            .transactions
+            .iter()
+            .map(|transaction| {
+                json!({
+                    "signature": transaction.signature,
+                    "slot": transaction.slot,
+                    "block_time": transaction.block_time,
+                    "success": transaction.success,
+                })
+            })
+            .collect();
+
+        json!({
+            "version": REPORT_VERSION,
+            "verified": self.result.is_ok(),
+            "error": self.result.as_ref().err().map(|err| format!("{:?}", err)),
+            "program_id": self.program_id.to_string(),
+            "session_address": self.session_address.to_string(),
+            "pool_address": self.pool_address.to_string(),
+            "target_index": self.target_index,
+            "target_hash": self.target_hash.map(|hash| hex(&hash)),
+            "derivation": derivation,
+            "slot_hash": {
+                "slot": self.slot_hash.slot,
+                "recorded": hex(&self.slot_hash.recorded),
+                "observed": self.slot_hash.observed.map(|hash| hex(&hash)),
+            },
+            "transactions": transactions,
+            "session": {
+                "session_id": pool_id_string(&session.session_id),
+                "pool_id": pool_id_string(&session.pool_id),
+                "kind": format!("{:?}", session.kind),
+                "session_media_hash": hex(&session.session_media_hash),
+                "media_hash_algorithm": format!("{:?}", session.media_hash_algorithm),
+                "media_item_count": session.media_item_count,
+                "submitter": session.submitter.to_string(),
+                "viewer": session.viewer.to_string(),
+                "submission_slot": session.submission_slot,
+                "submitted_at": session.submitted_at,
+                "finalized": session.finalized,
+                "finalized_at": session.finalized_at,
+                "entropy_slot": session.entropy_slot,
+                "submission_blockhash": hex(&session.submission_blockhash),
+                "assigned_target_index": session.assigned_target_index,
+                "completed_target_indices": session.completed_target_indices,
+                "late_finalized": session.late_finalized,
+                "reanchor_slot": session.reanchor_slot,
+                "reanchor_count": session.reanchor_count,
+                "min_finalization_delay": session.min_finalization_delay,
+                "max_finalization_delay": session.max_finalization_delay,
+                "media_amendments": session.media_history.len(),
+                "candidates": session.candidates,
+                "tasking_commitment": hex(&session.tasking_commitment),
+                "tasking_revealed": session.tasking_revealed,
+                "experiment": session.experiment.to_string(),
+                "account_data": hex(&self.session_data),
+            },
+            "pool": {
+                "pool_id": pool_id_string(&pool.pool_id),
+                "creator": pool.creator.to_string(),
+                "kind": format!("{:?}", pool.kind),
+                "hash_algorithm": format!("{:?}", pool.hash_algorithm),
+                "target_count": pool.target_count,
+                "finalized": pool.finalized,
+                "created_at": pool.created_at,
+                "resolved_outcome": pool.resolved_outcome,
+                "resolved_slot": pool.resolved_slot,
+                "event_time": pool.event_time,
+                "submission_deadline": pool.submission_deadline,
+                "targets": pool.targets.iter().map(|hash| hex(hash)).collect::<Vec<_>>(),
+                "account_data": hex(&self.pool_data),
+            },
+        })
+    }
+
+    /// Pretty-printed JSON with a stable field order
+    pub fn to_json(&self) -> String {
+        let mut out = serde_json::to_string_pretty(&self.to_value()).expect("report serializes");
+        out.push('\n');
+        out
+    }
+
+    /// A standalone HTML page with no external resources; the JSON report is
+    /// embedded in a `<script type="application/json">` element
+    pub fn to_html(&self) -> String {
+        let verdict = match &self.result {
+            Ok(()) => "Assignment verified".to_string(),
+            Err(err) => format!("Verification failed: {:?}", err),
+        };
+        let title = format!("Session {}", pool_id_string(&self.session.session_id));
+
+        let mut body = String::new();
+        let Value::Object(sections) = self.to_value() else { unreachable!() };
+        for (key, value) in &sections {
+            if matches!(key.as_str(), "version" | "verified" | "error") {
+                continue;
+            }
+            body.push_str(&format!("<h2>{}</h2>\n", html_escape(&key.replace('_', " "))));
+            write_html(value, &mut body);
+            body.push('\n');
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:60rem;margin:2rem auto;padding:0 1rem}}\
+             table{{border-collapse:collapse}}th,td{{border:1px solid #ccc;padding:.25rem .5rem;text-align:left;vertical-align:top}}\
+             code,pre{{word-break:break-all;white-space:pre-wrap}}.ok{{color:#060}}.failed{{color:#a00}}</style>\n\
+             </head>\n<body>\n<h1>{title}</h1>\n<p class=\"{class}\"><strong>{verdict}</strong></p>\n\
+             <p>Standard sessions are assigned a target by reading the first 8 bytes of the entropy slot's hash as a \
+             big-endian integer and taking it modulo the number of targets the viewer has not completed. Every input \
+             is listed below alongside the raw account data, so the steps can be repeated offline and checked \
+             against any RPC node.</p>\n{body}<script type=\"application/json\" id=\"report\">\n{json}</script>\n\
+             </body>\n</html>\n",
+            title = html_escape(&title),
+            class = if self.result.is_ok() { "ok" } else { "failed" },
+            verdict = html_escape(&verdict),
+            body = body,
+            // A literal `<` could end the script element early
+            json = self.to_json().replace('<', "\\u003c"),
+        )
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{finalize, pool, session};
+    use crate::RecordId;
+    use borsh::BorshSerialize;
+
+    fn accounts(completed: Vec<u16>) -> (TargetPool, Session) {
+        let pool = pool(5);
+        let mut session = session(RecordId::from("<session>"), &pool);
+        session.completed_target_indices = completed;
+        finalize(&mut session, &pool, [7; 32]);
+        (pool, session)
+    }
+
+    #[test]
+    fn test_build_report() {
+        let program_id = crate::id();
+        let (pool, session) = accounts(vec![1, 3]);
+        let (pool_data, session_data) = (pool.try_to_vec().unwrap(), session.try_to_vec().unwrap());
+        let transactions = vec![TransactionEvidence {
+            signature: "sig".to_string(),
+            slot: 103,
+            block_time: None,
+            success: true,
+        }];
+
+        let report = build_report(&program_id, &session_data, &pool_data, transactions, Some([7; 32])).unwrap();
+        assert!(report.result.is_ok());
+        assert_eq!(report.target_index, Some(session.assigned_target_index));
+        assert_eq!(report.target_hash, Some([session.assigned_target_index as u8; 32]));
+        assert_eq!(report.steps[3].value, "1, 3");
+        assert_eq!(report.steps[4].value, "3");
+
+        let json = report.to_json();
+        assert!(json.contains("\"verified\": true"));
+        assert!(json.contains(&format!("\"account_data\": \"{}\"", hex(&session_data))));
+        let html = report.to_html();
+        assert!(html.contains("<h1>Session &lt;session&gt;</h1>"));
+        assert!(!html.contains("<session>"));
+
+        // A SlotHashes snapshot disagreeing with the recorded hash fails the report
+        let report = build_report(&program_id, &session_data, &pool_data, Vec::new(), Some([8; 32])).unwrap();
+        assert_eq!(report.result, Err(VerificationError::SlotHashMismatch));
+
+        // Decoys add the candidate draw, and a legacy session names the
+        // finalizer's blockhash instead of a slot
+        let mut session = crate::test_fixtures::session(RecordId::from("<session>"), &pool);
+        session.decoy_count = 2;
+        finalize(&mut session, &pool, [7; 32]);
+        let candidates = session.candidates.iter().map(u16::to_string).collect::<Vec<_>>().join(", ");
+        let (steps, _) = derive_target_index(&session, &pool);
+        assert_eq!(steps[7].description, "Target and 2 decoys drawn from the same hash, shuffled for judging");
+        assert_eq!(steps[7].value, candidates);
+        session.layout_version = 0;
+        let (steps, _) = derive_target_index(&session, &pool);
+        assert!(steps[0].description.starts_with("Blockhash supplied by the finalizer"));
+
+        let (mut other_pool, _) = accounts(Vec::new());
+        other_pool.pool_id = RecordId::from("other");
+        assert!(matches!(
+            build_report(&program_id, &session_data, &other_pool.try_to_vec().unwrap(), Vec::new(), None),
+            Err(ReportError::PoolMismatch)
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+// Account sizes and rent for planning costs
+//
+// Mirrors the Borsh layout of the program's accounts so the cost of a pool or
+// session can be worked out before anything is sent. The processors size
+// accounts by serializing them; the tests below check these functions against
+// that. Not compiled into the on-chain program.
+
+use crate::RecordId;
+use solana_program::{native_token::lamports_to_sol, rent::Rent};
+
+// Bytes of a `TargetPool` besides its id and target hashes, for a standard
+// pool (associative pools also store their outcome labels)
+const POOL_FIXED_SIZE: usize = 8 + 1 // discriminator, layout_version
+    + 32 // creator
+    + 2 // target_count
+    + 4 // targets length prefix
+    + 8 // created_at
+    + 1 // finalized
+    + 1 // hash_algorithm
+    + 8 + 8 // min/max_finalization_delay
+    + 1 // kind
+    + 4 // outcome_labels length prefix
+    + 32 // resolver
+    + 2 // resolved_outcome
+    + 8 // resolved_slot
+    + 8 + 8; // event_time, submission_deadline
+
+// Bytes of a freshly submitted `Session` besides its ids and completed indices
+const SESSION_FIXED_SIZE: usize = 8 + 1 // discriminator, layout_version
+    + 32 // session_media_hash
+    + 8 // submission_slot
+    + 32 // submission_blockhash
+    + 2 // assigned_target_index
+    + 32 // target_selector_program
+    + 32 // submitter
+    + 8 // submitted_at
+    + 1 // finalized
+    + 8 // finalized_at
+    + 4 // completed_target_indices length prefix
+    + 1 // media_hash_algorithm
+    + 2 // media_item_count
+    + 4 // media_history length prefix
+    + 8 // finalization_reward
+    + 8 // entropy_slot
+    + 1 // late_finalized
+    + 8 // reanchor_slot
+    + 2 // reanchor_count
+    + 8 + 8 // min/max_finalization_delay
+    + 1 // kind
+    + 4 // candidates length prefix
+    + 1 // decoy_count
+    + 32 // viewer
+    + 32 // designated_judge
+    + 32 // tasking_commitment
+    + 8 // tasking_reference
+    + 1 // tasking_revealed
+    + 32 // tasking_salt
+    + 32; // experiment
+
+/// Bytes each `AmendSessionMedia` adds to a session
+pub const MEDIA_AMENDMENT_SIZE: usize = 32 + 2 + 8;
+
+/// Bytes each judging candidate adds to a session at finalization. Their
+/// rent is escrowed at submission.
+pub const CANDIDATE_SIZE: usize = 2;
+
+// `StatCounters`: three u64 sums and six u32 counts
+const STAT_COUNTERS_SIZE: usize = 3 * 8 + 6 * 4;
+
+/// Serialized size of a pool or session id
+pub fn record_id_size(id: &RecordId) -> usize {
+    match id {
+        RecordId::Text(text) => 1 + 4 + text.len(),
+        RecordId::Fixed(_) => 1 + 32,
+    }
+}
+
+/// Size of a standard pool account holding `target_count` targets
+pub fn pool_account_size(target_count: usize, pool_id: &RecordId) -> usize {
+    record_id_size(pool_id) + POOL_FIXED_SIZE + 32 * target_count
+}
+
+/// Size of the stats account created alongside a pool
+pub fn pool_stats_account_size(target_count: usize) -> usize {
+    32 // pool
+        + 4 // sessions_finalized
+        + 4 // sessions_late_finalized
+        + 4 + 4 * target_count // assignment_counts
+        + 4 // ranked_judgements
+        + 8 + 8 + 8 // rank_sum, rank_mean_sum_x2, rank_variance_sum_x12
+        + 4 // first_place_hits
+        + 4 // rated_judgements
+        + 8 // rating_sum
+}
+
+/// Size of a session account at submission, before any amendments or judging
+/// candidates
+pub fn session_account_size(session_id: &RecordId, pool_id: &RecordId, completed_count: usize) -> usize {
+    record_id_size(session_id) + record_id_size(pool_id) + SESSION_FIXED_SIZE + 2 * completed_count
+}
+
+/// Size of a session account once finalized, with the target and
+/// `decoy_count` decoys drawn as judging candidates (none without decoys)
+pub fn finalized_session_account_size(
+    session_id: &RecordId,
+    pool_id: &RecordId,
+    completed_count: usize,
+    decoy_count: u8,
+) -> usize {
+    let candidate_count = if decoy_count == 0 { 0 } else { decoy_count as usize + 1 };
+    session_account_size(session_id, pool_id, completed_count) + CANDIDATE_SIZE * candidate_count
+}
+
+/// Size of a viewer stats account tracking `pool_count` pools
+pub fn viewer_stats_account_size(pool_count: usize) -> usize {
+    32 + STAT_COUNTERS_SIZE + 4 + pool_count * (32 + STAT_COUNTERS_SIZE)
+}
+
+/// Lamports needed to keep an account of `space` bytes rent exempt, using the
+/// cluster's default rent parameters
+pub fn rent_exempt_lamports(space: usize) -> u64 {
+    Rent::default().minimum_balance(space)
+}
+
+/// Lamports the creator locks up for a standard pool and its stats account
+pub fn pool_creation_lamports(target_count: usize, pool_id: &RecordId) -> u64 {
+    rent_exempt_lamports(pool_account_size(target_count, pool_id))
+        + rent_exempt_lamports(pool_stats_account_size(target_count))
+}
+
+/// Lamports a submitter locks up per session: rent for the session with room
+/// for the judging candidates drawn at finalization (the target plus
+/// `decoy_count` decoys), plus the escrowed finalization reward. A viewer's
+/// first session in a pool also grows their stats account (see
+/// `viewer_stats_account_size`).
+pub fn session_submission_lamports(
+    session_id: &RecordId,
+    pool_id: &RecordId,
+    completed_count: usize,
+    decoy_count: u8,
+    finalization_reward: u64,
+) -> u64 {
+    rent_exempt_lamports(finalized_session_account_size(session_id, pool_id, completed_count, decoy_count))
+        + finalization_reward
+}
+
+/// Format lamports as SOL for cost reports
+pub fn format_sol(lamports: u64) -> String {
+    format!("{:.9} SOL", lamports_to_sol(lamports))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{pool, session};
+    use crate::{MediaAmendment, PoolStatCounters, PoolStats, StatCounters, TargetPool, ViewerStats};
+    use borsh::BorshSerialize;
+    use solana_program::pubkey::Pubkey;
+
+    fn ids() -> Vec<RecordId> {
+        vec![
+            RecordId::from("p"),
+            RecordId::from("123e4567-e89b-12d3-a456-426614174000"),
+            RecordId::from_uuid([7; 16]),
+        ]
+    }
+
+    fn pool_with_id(pool_id: RecordId, target_count: u16) -> TargetPool {
+        let mut pool = pool(target_count);
+        pool.pool_id = pool_id;
+        pool
+    }
+
+    #[test]
+    fn test_pool_sizes_match_serialized_accounts() {
+        for pool_id in ids() {
+            for target_count in [0, 1, 10, 5000] {
+                let pool = pool_with_id(pool_id.clone(), target_count as u16);
+                assert_eq!(pool.try_to_vec().unwrap().len(), pool_account_size(target_count, &pool_id));
+
+                let stats = PoolStats::new(Pubkey::default(), target_count);
+                assert_eq!(stats.try_to_vec().unwrap().len(), pool_stats_account_size(target_count));
+            }
+        }
+    }
+
+    #[test]
+    fn test_session_sizes_match_serialized_accounts() {
+        for session_id in ids() {
+            for pool_id in ids() {
+                for completed_count in [0, 3] {
+                    let mut session = session(session_id.clone(), &pool_with_id(pool_id.clone(), 4));
+                    session.completed_target_indices = vec![0; completed_count];
+                    let size = session_account_size(&session_id, &pool_id, completed_count);
+                    assert_eq!(session.try_to_vec().unwrap().len(), size);
+
+                    // Growth from amendments and candidates drawn at finalization
+                    session.media_history.push(MediaAmendment {
+                        previous_media_hash: [0; 32],
+                        previous_media_item_count: 1,
+                        amended_slot: 0,
+                    });
+                    session.candidates = vec![0; 4];
+                    assert_eq!(
+                        session.try_to_vec().unwrap().len(),
+                        size + MEDIA_AMENDMENT_SIZE + 4 * CANDIDATE_SIZE
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_viewer_stats_size_matches_serialized_account() {
+        let mut stats = ViewerStats {
+            viewer: Pubkey::default(),
+            totals: StatCounters::default(),
+            pools: Vec::new(),
+        };
+        assert_eq!(stats.try_to_vec().unwrap().len(), viewer_stats_account_size(0));
+
+        stats.pools = vec![
+            PoolStatCounters {
+                pool: Pubkey::default(),
+                counters: StatCounters::default(),
+            };
+            3
+        ];
+        assert_eq!(stats.try_to_vec().unwrap().len(), viewer_stats_account_size(3));
+    }
+
+    #[test]
+    fn test_costs() {
+        // Default rent: 6960 lamports per byte, counting 128 bytes of account
+        // overhead
+        let pool_id = RecordId::from("pool");
+        assert_eq!(pool_creation_lamports(5000, &pool_id), 1_115_500_080 + 140_703_360);
+        assert_eq!(session_submission_lamports(&pool_id, &pool_id, 0, 0, 5000), 3_751_440 + 5000);
+        assert_eq!(session_submission_lamports(&pool_id, &pool_id, 0, 3, 5000), 3_807_120 + 5000);
+        assert_eq!(format_sol(1_500_000_000), "1.500000000 SOL");
+
+        // Matches what `create_session_account` charges the submitter
+        let pool = pool_with_id(pool_id.clone(), 10);
+        for decoy_count in [0, 1, 4] {
+            let mut session = session(RecordId::from("session"), &pool);
+            session.completed_target_indices = vec![0; 2];
+            session.decoy_count = decoy_count;
+            session.finalization_reward = 5000;
+            assert_eq!(
+                session_submission_lamports(&session.session_id, &pool_id, 2, decoy_count, 5000),
+                Rent::default().minimum_balance(session.finalized_space().unwrap()) + 5000
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// Pool-level significance from a decoded `PoolStats` account
+//
+// Everything here is computed from the on-chain counters alone, so anyone can
+// reproduce a published result from the account data. Not compiled into the
+// on-chain program.
+
+use crate::PoolStats;
+
+/// Outcome of one significance test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    /// Raw test statistic (sum of ranks, chi-square, ...)
+    pub statistic: f64,
+    /// Standard normal deviate; positive means "better than chance"
+    pub z_score: f64,
+    /// One-tailed p-value for `z_score`
+    pub p_value: f64,
+}
+
+/// Sum-of-ranks test over all ranked judgements in the pool. Under the null
+/// hypothesis the target's rank among `n` candidates is uniform on `1..=n`,
+/// giving a mean of `(n + 1) / 2` and variance of `(n^2 - 1) / 12` per
+/// judgement. Lower rank sums are better, so the z-score is
+/// `(expected - observed) / sd`. Returns `None` without ranked judgements.
+///
+/// The ranks must be independent, so the program counts one judgement per
+/// session, the blind one of its designated judge: several judges scoring one
+/// session all rank the same target among the same candidates against the
+/// same transcript.
+pub fn sum_of_ranks(stats: &PoolStats) -> Option<TestResult> {
+    if stats.ranked_judgements == 0 || stats.rank_variance_sum_x12 == 0 {
+        return None;
+    }
+
+    let observed = stats.rank_sum as f64;
+    let expected = stats.rank_mean_sum_x2 as f64 / 2.0;
+    let sd = (stats.rank_variance_sum_x12 as f64 / 12.0).sqrt();
+    let z_score = (expected - observed) / sd;
+
+    Some(TestResult {
+        statistic: observed,
+        z_score,
+        p_value: upper_tail_p_value(z_score),
+    })
+}
+
+/// Chi-square goodness-of-fit of the target assignment histogram against a
+/// uniform distribution, as a sanity check on target selection. The z-score
+/// uses the Wilson–Hilferty approximation; a large z means the assignments
+/// are less uniform than chance would explain. Returns `None` with fewer
+/// than two targets or no finalized sessions.
+pub fn assignment_uniformity(stats: &PoolStats) -> Option<TestResult> {
+    let buckets = stats.assignment_counts.len();
+    let total: u64 = stats.assignment_counts.iter().map(|&count| count as u64).sum();
+    if buckets < 2 || total == 0 {
+        return None;
+    }
+
+    let expected = total as f64 / buckets as f64;
+    let chi_square: f64 = stats
+        .assignment_counts
+        .iter()
+        .map(|&count| {
+            let diff = count as f64 - expected;
+            diff * diff / expected
+        })
+        .sum();
+
+    let dof = (buckets - 1) as f64;
+    let drift = 2.0 / (9.0 * dof);
+    let z_score = ((chi_square / dof).cbrt() - (1.0 - drift)) / drift.sqrt();
+
+    Some(TestResult {
+        statistic: chi_square,
+        z_score,
+        p_value: upper_tail_p_value(z_score),
+    })
+}
+
+/// Probability that a standard normal variable exceeds `z`
+pub fn upper_tail_p_value(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+// Complementary error function (Numerical Recipes `erfcc`), fractional error
+// below 1.2e-7 everywhere
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98 + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    fn stats() -> PoolStats {
+        PoolStats::new(Pubkey::default(), 4)
+    }
+
+    #[test]
+    fn test_upper_tail_p_value() {
+        assert!((upper_tail_p_value(0.0) - 0.5).abs() < 1e-6);
+        assert!((upper_tail_p_value(1.959_964) - 0.025).abs() < 1e-6);
+        assert!((upper_tail_p_value(-1.959_964) - 0.975).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sum_of_ranks() {
+        let mut stats = stats();
+        assert_eq!(sum_of_ranks(&stats), None);
+
+        // 12 judgements among 4 candidates: expected sum 30, variance 15
+        stats.ranked_judgements = 12;
+        stats.rank_mean_sum_x2 = 12 * 5;
+        stats.rank_variance_sum_x12 = 12 * 15;
+        stats.rank_sum = 30;
+        assert!(sum_of_ranks(&stats).unwrap().z_score.abs() < 1e-12);
+
+        stats.rank_sum = 20;
+        let result = sum_of_ranks(&stats).unwrap();
+        assert!((result.z_score - 10.0 / 15f64.sqrt()).abs() < 1e-12);
+        assert!(result.p_value < 0.01);
+    }
+
+    #[test]
+    fn test_assignment_uniformity() {
+        let mut stats = stats();
+        assert_eq!(assignment_uniformity(&stats), None);
+
+        stats.assignment_counts = vec![25, 25, 25, 25];
+        let uniform = assignment_uniformity(&stats).unwrap();
+        assert_eq!(uniform.statistic, 0.0);
+        assert!(uniform.p_value > 0.99);
+
+        stats.assignment_counts = vec![100, 0, 0, 0];
+        assert!(assignment_uniformity(&stats).unwrap().p_value < 1e-6);
+    }
+}
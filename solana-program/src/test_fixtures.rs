@@ -0,0 +1,84 @@
+// Accounts shared by the unit tests
+//
+// Built with the constructors and assignment code the processors use, so the
+// fixtures follow layout changes instead of restating every field.
+
+use crate::{draw_assignment, HashAlgorithm, RecordId, Session, SessionKind, TargetPool};
+use solana_program::{clock::Clock, pubkey::Pubkey};
+
+/// Slot every fixture session is submitted at
+pub const SUBMISSION_SLOT: u64 = 100;
+
+/// Standard pool `pool`, closed to appends, whose target at index `i` hashes
+/// to `[i; 32]`
+pub fn pool(target_count: u16) -> TargetPool {
+    let mut pool = TargetPool::new_standard(
+        RecordId::from("pool"),
+        Pubkey::default(),
+        (0..target_count).map(|i| [i as u8; 32]).collect(),
+        HashAlgorithm::Sha256,
+        2,
+        150,
+        0,
+    );
+    pool.finalized = true;
+    pool
+}
+
+/// Unresolved associative pool `pool` with one target per outcome, hashed
+/// like `pool`'s
+pub fn associative_pool(outcome_count: u16) -> TargetPool {
+    TargetPool::new_associative(
+        RecordId::from("pool"),
+        Pubkey::default(),
+        (0..outcome_count).map(|i| [i as u8; 32]).collect(),
+        HashAlgorithm::Sha256,
+        (0..outcome_count).map(|i| format!("outcome {}", i)).collect(),
+        Pubkey::default(),
+        0,
+        0,
+        0,
+    )
+}
+
+/// A session submitted to `pool` at `SUBMISSION_SLOT`, with media hash
+/// `[1; 32]` and no decoys
+pub fn session(session_id: RecordId, pool: &TargetPool) -> Session {
+    let clock = Clock {
+        slot: SUBMISSION_SLOT,
+        ..Clock::default()
+    };
+    Session::new_submitted(
+        &Pubkey::default(),
+        session_id,
+        pool,
+        Pubkey::default(),
+        Pubkey::default(),
+        &clock,
+        [1; 32],
+        HashAlgorithm::Sha256,
+        1,
+        Vec::new(),
+        Pubkey::default(),
+        0,
+        0,
+        [0; 32],
+        Pubkey::default(),
+    )
+}
+
+/// Finalize `session` as the program would with `slot_hash` as the hash of
+/// its entropy slot
+pub fn finalize(session: &mut Session, pool: &TargetPool, slot_hash: [u8; 32]) {
+    match session.kind {
+        SessionKind::Standard => {
+            let entropy_slot = session.entropy_slot;
+            draw_assignment(session, pool, slot_hash, entropy_slot).unwrap()
+        }
+        SessionKind::AssociativePrediction => {
+            session.assigned_target_index = pool.resolved_outcome;
+            session.entropy_slot = pool.resolved_slot;
+        }
+    }
+    session.finalized = true;
+}
@@ -0,0 +1,266 @@
+// Off-chain verification helpers
+//
+// These mirror the on-chain logic so anyone holding the decoded `Session` and
+// `TargetPool` accounts (plus the original media) can re-check an assignment
+// without trusting our website. Not compiled into the on-chain program.
+
+use crate::manifest::{verify_inclusion, InclusionProof};
+use crate::{
+    select_candidates, select_target_index, tasking_commitment, tasking_reference, HashAlgorithm, Session,
+    SessionKind, TargetPool,
+};
+use solana_program::pubkey::Pubkey;
+
+/// Maximum Hamming distance (in bits) at which two perceptual hashes are
+/// considered the same image. Tolerates re-encoding and minor resizing.
+pub const PERCEPTUAL_MATCH_THRESHOLD: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    SessionNotFinalized,
+    PoolMismatch,
+    TargetIndexOutOfRange,
+    TargetAssignmentMismatch { expected: u16, recorded: u16 },
+    CandidateMismatch,
+    TaskingNotRevealed,
+    TaskingMismatch,
+    AllTargetsCompleted,
+    HashMismatch,
+    /// The session commits a multi-item manifest; use `verify_session_media_item`
+    ManifestProofRequired,
+    InvalidInclusionProof,
+    /// Perceptual hashes cannot be recomputed from raw bytes here; the caller
+    /// must compute the perceptual hash and use `hash_matches` instead.
+    DigestUnavailable,
+    /// SlotHashes holds a different hash for the session's entropy slot
+    SlotHashMismatch,
+}
+
+impl HashAlgorithm {
+    /// Compute the 32-byte digest of `data`. Returns `None` for
+    /// `PerceptualHash`, which depends on an image decoder.
+    pub fn digest(&self, data: &[u8]) -> Option<[u8; 32]> {
+        match self {
+            HashAlgorithm::Sha256 => Some(solana_program::hash::hash(data).to_bytes()),
+            HashAlgorithm::Keccak256 => Some(solana_program::keccak::hash(data).to_bytes()),
+            HashAlgorithm::Blake3 => Some(solana_program::blake3::hash(data).to_bytes()),
+            HashAlgorithm::PerceptualHash => None,
+        }
+    }
+}
+
+/// Number of differing bits between two 32-byte hashes
+pub fn hamming_distance(a: &[u8; 32], b: &[u8; 32]) -> u32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+/// Compare a recorded hash against a freshly computed one using the rules of
+/// `algorithm`: exact equality for cryptographic hashes, Hamming distance for
+/// perceptual hashes.
+pub fn hash_matches(algorithm: HashAlgorithm, recorded: &[u8; 32], computed: &[u8; 32]) -> bool {
+    match algorithm {
+        HashAlgorithm::PerceptualHash => hamming_distance(recorded, computed) <= PERCEPTUAL_MATCH_THRESHOLD,
+        _ => recorded == computed,
+    }
+}
+
+/// Hash `data` with `algorithm` and compare it to `recorded`
+pub fn verify_content(algorithm: HashAlgorithm, recorded: &[u8; 32], data: &[u8]) -> Result<(), VerificationError> {
+    let computed = algorithm.digest(data).ok_or(VerificationError::DigestUnavailable)?;
+    if hash_matches(algorithm, recorded, &computed) {
+        Ok(())
+    } else {
+        Err(VerificationError::HashMismatch)
+    }
+}
+
+/// Check that `media` is the content committed in a single-item `session`
+pub fn verify_session_media(session: &Session, media: &[u8]) -> Result<(), VerificationError> {
+    if session.media_item_count > 1 {
+        return Err(VerificationError::ManifestProofRequired);
+    }
+    verify_content(session.media_hash_algorithm, &session.session_media_hash, media)
+}
+
+/// Check that `item` is one page of the manifest committed in `session`
+pub fn verify_session_media_item(
+    session: &Session,
+    item: &[u8],
+    proof: &InclusionProof,
+) -> Result<(), VerificationError> {
+    if proof.item_count != session.media_item_count {
+        return Err(VerificationError::InvalidInclusionProof);
+    }
+    let item_hash = session
+        .media_hash_algorithm
+        .digest(item)
+        .ok_or(VerificationError::DigestUnavailable)?;
+    if verify_inclusion(&session.session_media_hash, &item_hash, proof) {
+        Ok(())
+    } else {
+        Err(VerificationError::InvalidInclusionProof)
+    }
+}
+
+/// Check that `image` is the target at `index` in `pool`
+pub fn verify_target_image(pool: &TargetPool, index: u16, image: &[u8]) -> Result<(), VerificationError> {
+    let recorded = pool
+        .targets
+        .get(index as usize)
+        .ok_or(VerificationError::TargetIndexOutOfRange)?;
+    verify_content(pool.hash_algorithm, recorded, image)
+}
+
+/// Recompute the target assignment of a finalized session and return the
+/// assigned target hash if it matches what the program recorded.
+pub fn verify_target_assignment(session: &Session, pool: &TargetPool) -> Result<[u8; 32], VerificationError> {
+    if !session.finalized {
+        return Err(VerificationError::SessionNotFinalized);
+    }
+    if session.pool_id != pool.pool_id {
+        return Err(VerificationError::PoolMismatch);
+    }
+
+    let expected = match session.kind {
+        SessionKind::Standard => select_target_index(
+            &session.submission_blockhash,
+            pool.target_count,
+            &session.completed_target_indices,
+        )
+        .ok_or(VerificationError::AllTargetsCompleted)?,
+        // Associative sessions get the target tied to the resolved outcome
+        SessionKind::AssociativePrediction => pool.resolved_outcome,
+    };
+
+    if expected != session.assigned_target_index {
+        return Err(VerificationError::TargetAssignmentMismatch {
+            expected,
+            recorded: session.assigned_target_index,
+        });
+    }
+
+    pool.targets
+        .get(expected as usize)
+        .copied()
+        .ok_or(VerificationError::TargetIndexOutOfRange)
+}
+
+/// Recompute a session's shuffled judging candidates (target plus decoys)
+/// and check they match what the program stored
+pub fn verify_candidates(session: &Session, pool: &TargetPool) -> Result<(), VerificationError> {
+    // Sessions submitted without decoys never get candidates
+    let expected = if session.decoy_count == 0 {
+        Some(Vec::new())
+    } else {
+        select_candidates(
+            &session.submission_blockhash,
+            pool.target_count,
+            session.assigned_target_index,
+            session.decoy_count,
+        )
+    };
+
+    if expected.as_ref() == Some(&session.candidates) {
+        Ok(())
+    } else {
+        Err(VerificationError::CandidateMismatch)
+    }
+}
+
+/// Check that a revealed tasking salt matches the session's commitment and
+/// that its tasking code was derived from `session_address`
+pub fn verify_tasking(session: &Session, session_address: &Pubkey) -> Result<(), VerificationError> {
+    if !session.tasking_revealed {
+        return Err(VerificationError::TaskingNotRevealed);
+    }
+    if tasking_commitment(&session.tasking_salt) != session.tasking_commitment
+        || tasking_reference(session_address, &session.tasking_commitment) != session.tasking_reference
+    {
+        return Err(VerificationError::TaskingMismatch);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{associative_pool, finalize, pool, session};
+    use crate::RecordId;
+
+    fn finalized_session(pool: &TargetPool, slot_hash: [u8; 32]) -> Session {
+        let mut session = session(RecordId::from("session"), pool);
+        finalize(&mut session, pool, slot_hash);
+        session
+    }
+
+    #[test]
+    fn test_verify_target_assignment() {
+        let pool = pool(4);
+        let blockhash = [3u8; 32];
+        let expected = select_target_index(&blockhash, 4, &[]).unwrap();
+
+        let mut session = finalized_session(&pool, blockhash);
+        assert_eq!(verify_target_assignment(&session, &pool), Ok([expected as u8; 32]));
+
+        session.assigned_target_index = (expected + 1) % 4;
+        assert!(matches!(
+            verify_target_assignment(&session, &pool),
+            Err(VerificationError::TargetAssignmentMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_associative_assignment() {
+        let mut pool = associative_pool(4);
+        pool.resolved_outcome = 2;
+
+        let session = finalized_session(&pool, [0; 32]);
+        assert_eq!(verify_target_assignment(&session, &pool), Ok([2; 32]));
+    }
+
+    #[test]
+    fn test_verify_candidates() {
+        let pool = pool(4);
+        let blockhash = [3u8; 32];
+        let target = select_target_index(&blockhash, 4, &[]).unwrap();
+
+        let mut session = finalized_session(&pool, blockhash);
+        assert_eq!(verify_candidates(&session, &pool), Ok(()));
+
+        // Candidates are drawn for the decoy count fixed at submission
+        session.decoy_count = 2;
+        assert_eq!(verify_candidates(&session, &pool), Err(VerificationError::CandidateMismatch));
+        session.candidates = select_candidates(&blockhash, 4, target, 2).unwrap();
+        assert_eq!(verify_candidates(&session, &pool), Ok(()));
+        session.candidates = select_candidates(&blockhash, 4, target, 1).unwrap();
+        assert_eq!(verify_candidates(&session, &pool), Err(VerificationError::CandidateMismatch));
+    }
+
+    #[test]
+    fn test_digest_per_algorithm() {
+        let data = b"session page 1";
+        let sha = HashAlgorithm::Sha256.digest(data).unwrap();
+        let keccak = HashAlgorithm::Keccak256.digest(data).unwrap();
+        let blake = HashAlgorithm::Blake3.digest(data).unwrap();
+        assert_ne!(sha, keccak);
+        assert_ne!(sha, blake);
+        assert!(verify_content(HashAlgorithm::Blake3, &blake, data).is_ok());
+        assert_eq!(
+            verify_content(HashAlgorithm::Sha256, &blake, data),
+            Err(VerificationError::HashMismatch)
+        );
+        assert_eq!(HashAlgorithm::PerceptualHash.digest(data), None);
+    }
+
+    #[test]
+    fn test_perceptual_hash_tolerance() {
+        let recorded = [0u8; 32];
+        let mut close = [0u8; 32];
+        close[0] = 0b0000_0111;
+        let mut far = [0u8; 32];
+        far[..2].copy_from_slice(&[0xff, 0xff]);
+        assert!(hash_matches(HashAlgorithm::PerceptualHash, &recorded, &close));
+        assert!(!hash_matches(HashAlgorithm::PerceptualHash, &recorded, &far));
+        assert!(!hash_matches(HashAlgorithm::Sha256, &recorded, &close));
+    }
+}
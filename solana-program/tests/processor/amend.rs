@@ -0,0 +1,57 @@
+use crate::*;
+
+fn amend(context: &ProgramTestContext, session_id: &str, media_hash: [u8; 32]) -> Instruction {
+    let amend = RemoteViewingInstruction::AmendSessionMedia {
+        session_id: RecordId::from(session_id),
+        session_media_hash: media_hash,
+        media_item_count: 1,
+    };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from(session_id)), false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        config_account(),
+    ];
+    instruction(&amend, accounts)
+}
+
+async fn submit(context: &mut ProgramTestContext) -> Slot {
+    create_pool(context, "pool", 4).await;
+    let submit = submit_session(context, "session", "pool", 0);
+    send(context, &[submit], &[]).await.unwrap();
+    session(context, "session").await.submission_slot
+}
+
+#[tokio::test]
+async fn test_amend_in_later_transaction() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context).await;
+
+    // A separate transaction a few slots on, just before the entropy slot
+    warp(&mut context, submission_slot + MIN_DELAY - 1);
+    let amend = amend(&context, "session", [2; 32]);
+    send(&mut context, &[amend], &[]).await.unwrap();
+
+    let session = session(&mut context, "session").await;
+    assert_eq!(session.session_media_hash, [2; 32]);
+    assert_eq!(session.media_history.len(), 1);
+    assert_eq!(session.media_history[0].previous_media_hash, [1; 32]);
+    assert_eq!(session.media_history[0].amended_slot, submission_slot + MIN_DELAY - 1);
+}
+
+#[tokio::test]
+async fn test_amend_rejected_from_entropy_slot() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context).await;
+    let entropy_slot = session(&mut context, "session").await.entropy_slot;
+    assert_eq!(entropy_slot, submission_slot + MIN_DELAY);
+
+    // The entropy slot's hash is not public yet, but a leader producing it
+    // could already see the media hash
+    warp(&mut context, entropy_slot);
+    let amend = amend(&context, "session", [2; 32]);
+    let result = send(&mut context, &[amend], &[]).await;
+    assert_error(result, RemoteViewingError::AmendmentWindowClosed);
+    assert_eq!(session(&mut context, "session").await.session_media_hash, [1; 32]);
+}
@@ -0,0 +1,125 @@
+use crate::*;
+
+const OUTCOMES: [&str; 3] = ["up", "flat", "down"];
+
+/// A CreateAssociativePool for "pool" with the payer as creator and resolver
+pub fn create(context: &ProgramTestContext, event_time: i64, submission_deadline: i64) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let create = RemoteViewingInstruction::CreateAssociativePool {
+        pool_id: RecordId::from("pool"),
+        target_hashes: (0..OUTCOMES.len() as u8).map(|i| [i; 32]).collect(),
+        hash_algorithm: HashAlgorithm::Sha256,
+        outcome_labels: OUTCOMES.iter().map(|label| label.to_string()).collect(),
+        resolver: context.payer.pubkey(),
+        event_time,
+        submission_deadline,
+    };
+    let accounts = vec![
+        AccountMeta::new(pool, false),
+        AccountMeta::new_readonly(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+        config_account(),
+    ];
+    instruction(&create, accounts)
+}
+
+pub fn resolve(resolver: &Pubkey, outcome_index: u16) -> Instruction {
+    let resolve = RemoteViewingInstruction::ResolveOutcome { pool_id: RecordId::from("pool"), outcome_index };
+    let accounts = vec![
+        AccountMeta::new(pool_address(&id(), &RecordId::from("pool")), false),
+        AccountMeta::new_readonly(*resolver, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        config_account(),
+    ];
+    instruction(&resolve, accounts)
+}
+
+pub fn finalize(context: &ProgramTestContext) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let finalize = RemoteViewingInstruction::FinalizeAssociativeSession { session_id: RecordId::from("session") };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from("session")), false),
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+    ];
+    instruction(&finalize, accounts)
+}
+
+#[tokio::test]
+async fn test_associative_pool_times_validated() {
+    let mut context = start().await;
+    let now = now(&mut context).await;
+
+    // The deadline must be in the future and no later than the event
+    let create_instruction = create(&context, now + 200, now);
+    let result = send(&mut context, &[create_instruction], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidEventTime);
+    let create_instruction = create(&context, now + 200, now + 201);
+    let result = send(&mut context, &[create_instruction], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidEventTime);
+
+    let create_instruction = create(&context, now + 200, now + 200);
+    send(&mut context, &[create_instruction], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_submissions_close_at_deadline() {
+    let mut context = start().await;
+    let now = now(&mut context).await;
+    let create_instruction = create(&context, now + 200, now + 100);
+    send(&mut context, &[create_instruction], &[]).await.unwrap();
+
+    set_time(&mut context, now + 99).await;
+    let submit = submit_session(&context, "session", "pool", 0);
+    send(&mut context, &[submit], &[]).await.unwrap();
+
+    set_time(&mut context, now + 100).await;
+    let submit = submit_session(&context, "late", "pool", 0);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_error(result, RemoteViewingError::SubmissionDeadlinePassed);
+}
+
+#[tokio::test]
+async fn test_outcome_resolved_from_event_time() {
+    let mut context = start().await;
+    let now = now(&mut context).await;
+    let create_instruction = create(&context, now + 200, now + 100);
+    let submit = submit_session(&context, "session", "pool", 0);
+    send(&mut context, &[create_instruction, submit], &[]).await.unwrap();
+    let resolver = context.payer.pubkey();
+
+    let result = send(&mut context, &[resolve(&resolver, 2)], &[]).await;
+    assert_error(result, RemoteViewingError::EventNotReached);
+    let finalize_instruction = finalize(&context);
+    let result = send(&mut context, &[finalize_instruction], &[]).await;
+    assert_error(result, RemoteViewingError::OutcomeNotResolved);
+
+    set_time(&mut context, now + 200).await;
+    let outsider = Keypair::new();
+    let result = send(&mut context, &[resolve(&outsider.pubkey(), 2)], &[&outsider]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+    let result = send(&mut context, &[resolve(&resolver, 3)], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidOutcome);
+
+    send(&mut context, &[resolve(&resolver, 2)], &[]).await.unwrap();
+    let pool: TargetPool = account(&mut context, &pool_address(&id(), &RecordId::from("pool"))).await;
+    assert_eq!(pool.resolved_outcome, 2);
+
+    let finalize_instruction = finalize(&context);
+    send(&mut context, &[finalize_instruction], &[]).await.unwrap();
+    let session = session(&mut context, "session").await;
+    assert!(session.finalized);
+    assert_eq!(session.assigned_target_index, 2);
+    assert_eq!(session.entropy_slot, pool.resolved_slot);
+
+    // The outcome cannot be changed once recorded
+    let result = send(&mut context, &[resolve(&resolver, 1)], &[]).await;
+    assert_error(result, RemoteViewingError::OutcomeAlreadyResolved);
+}
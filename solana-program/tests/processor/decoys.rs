@@ -0,0 +1,57 @@
+use crate::*;
+use remote_viewing_verifier::{select_candidates, MAX_DECOY_COUNT};
+use solana_sdk::slot_hashes::SlotHashes;
+
+#[tokio::test]
+async fn test_decoy_count_validated_at_submission() {
+    let mut context = start().await;
+    create_pool(&mut context, "small", 4).await;
+    create_pool(&mut context, "large", 20).await;
+
+    // Every decoy must be a different target from the pool
+    let submit = submit_session(&context, "session", "small", 4);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidDecoyCount);
+
+    let submit = submit_session(&context, "session", "large", MAX_DECOY_COUNT + 1);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidDecoyCount);
+
+    let submit = submit_session(&context, "session", "small", 3);
+    send(&mut context, &[submit], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_candidates_drawn_at_finalization() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 8).await;
+    let submit = submit_session(&context, "session", "pool", 3);
+    send(&mut context, &[submit], &[]).await.unwrap();
+
+    // Nothing about the candidates is known until the target is drawn
+    let session = session(&mut context, "session").await;
+    assert_eq!(session.decoy_count, 3);
+    assert!(session.candidates.is_empty());
+
+    warp(&mut context, session.submission_slot + MIN_DELAY + 1);
+    let finalize = finalize_session(&context, "session", "pool");
+    send(&mut context, &[finalize], &[]).await.unwrap();
+
+    let slot_hashes = context.banks_client.get_sysvar::<SlotHashes>().await.unwrap();
+    let slot_hash = slot_hashes.get(&session.entropy_slot).unwrap().to_bytes();
+    let session = crate::session(&mut context, "session").await;
+    assert_eq!(session.candidates.len(), 4);
+    assert!(session.candidates.contains(&session.assigned_target_index));
+    assert_eq!(
+        Some(session.candidates.clone()),
+        select_candidates(&slot_hash, 8, session.assigned_target_index, 3)
+    );
+
+    // The account grew to hold them and stays rent exempt on the escrow paid
+    // at submission
+    let address = session_address(&id(), &RecordId::from("session"));
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), session.try_to_vec().unwrap().len());
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+}
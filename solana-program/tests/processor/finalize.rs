@@ -0,0 +1,140 @@
+use crate::*;
+use remote_viewing_verifier::{select_target_index, PoolStats};
+use solana_sdk::{account::AccountSharedData, slot_hashes::SlotHashes};
+
+fn finalize_batch(context: &ProgramTestContext, submission_slot: Slot, session_ids: &[&str]) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let finalize = RemoteViewingInstruction::FinalizeSessionsBatch { submission_slot };
+    let mut accounts = vec![
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+    ];
+    for session_id in session_ids {
+        accounts.push(AccountMeta::new(session_address(&id(), &RecordId::from(*session_id)), false));
+        accounts.push(AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false));
+    }
+    instruction(&finalize, accounts)
+}
+
+async fn pool_stats(context: &mut ProgramTestContext) -> PoolStats {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    account(context, &pool_stats_address(&id(), &pool)).await
+}
+
+/// Submit a session to a fresh four-target pool, returning its submission
+/// slot
+async fn submit(context: &mut ProgramTestContext) -> Slot {
+    create_pool(context, "pool", 4).await;
+    let submit = submit_session(context, "session", "pool", 0);
+    send(context, &[submit], &[]).await.unwrap();
+    session(context, "session").await.submission_slot
+}
+
+#[tokio::test]
+async fn test_finalize_draws_from_entropy_slot_hash() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context).await;
+    let entropy_slot = submission_slot + MIN_DELAY;
+    assert_eq!(session(&mut context, "session").await.entropy_slot, entropy_slot);
+
+    warp(&mut context, entropy_slot + 1);
+    let finalize = finalize_session(&context, "session", "pool");
+    send(&mut context, &[finalize], &[]).await.unwrap();
+
+    let slot_hashes = context.banks_client.get_sysvar::<SlotHashes>().await.unwrap();
+    let slot_hash = slot_hashes.get(&entropy_slot).unwrap().to_bytes();
+    let session = session(&mut context, "session").await;
+    assert!(session.finalized);
+    assert_eq!(session.entropy_slot, entropy_slot);
+    assert_eq!(session.submission_blockhash, slot_hash);
+    assert_eq!(Some(session.assigned_target_index), select_target_index(&slot_hash, 4, &[]));
+
+    let stats = pool_stats(&mut context).await;
+    assert_eq!(stats.sessions_finalized, 1);
+    assert_eq!(stats.assignment_counts[session.assigned_target_index as usize], 1);
+}
+
+#[tokio::test]
+async fn test_finalize_rejected_outside_window() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context).await;
+
+    // The entropy slot itself has no hash yet
+    warp(&mut context, submission_slot + MIN_DELAY);
+    let finalize = finalize_session(&context, "session", "pool");
+    let result = send(&mut context, &[finalize], &[]).await;
+    assert_error(result, RemoteViewingError::TooEarlyToFinalize);
+
+    warp(&mut context, submission_slot + MAX_DELAY + 1);
+    let finalize = finalize_session(&context, "session", "pool");
+    let result = send(&mut context, &[finalize], &[]).await;
+    assert_error(result, RemoteViewingError::InvalidSlotHash);
+}
+
+#[tokio::test]
+async fn test_finalize_rejects_substituted_pool() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context).await;
+    warp(&mut context, submission_slot + MIN_DELAY + 1);
+
+    // Another genuine pool is not the session's pool
+    create_pool(&mut context, "other", 1).await;
+    let finalize = finalize_session(&context, "session", "other");
+    let result = send(&mut context, &[finalize], &[]).await;
+    assert_error(result, RemoteViewingError::PoolNotFound);
+
+    // Nor is a copy of the session's pool that the program does not own
+    let copy = context.banks_client.get_account(pool_address(&id(), &RecordId::from("pool"))).await.unwrap().unwrap();
+    let fake = Pubkey::new_unique();
+    let mut fake_account = AccountSharedData::new(copy.lamports, copy.data.len(), &system_program::id());
+    fake_account.set_data_from_slice(&copy.data);
+    context.set_account(&fake, &fake_account);
+
+    let mut finalize = finalize_session(&context, "session", "pool");
+    finalize.accounts[1].pubkey = fake;
+    let result = send(&mut context, &[finalize], &[]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+    assert!(!session(&mut context, "session").await.finalized);
+}
+
+#[tokio::test]
+async fn test_finalize_batch() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+    let instructions = [
+        submit_session(&context, "a", "pool", 0),
+        submit_session(&context, "b", "pool", 0),
+        submit_session(&context, "c", "pool", 0),
+    ];
+    send(&mut context, &instructions, &[]).await.unwrap();
+    let submission_slot = session(&mut context, "a").await.submission_slot;
+    warp(&mut context, submission_slot + 1);
+    let submit = submit_session(&context, "later", "pool", 0);
+    send(&mut context, &[submit], &[]).await.unwrap();
+    warp(&mut context, submission_slot + MIN_DELAY + 1);
+
+    let finalize = finalize_batch(&context, submission_slot, &["a", "b"]);
+    send(&mut context, &[finalize], &[]).await.unwrap();
+    let a = session(&mut context, "a").await;
+    let b = session(&mut context, "b").await;
+    assert!(a.finalized && b.finalized);
+    assert_eq!(a.submission_blockhash, b.submission_blockhash);
+    assert_eq!(a.assigned_target_index, b.assigned_target_index);
+    assert_eq!(pool_stats(&mut context).await.sessions_finalized, 2);
+
+    // Finalized sessions are skipped rather than failing the batch
+    let finalize = finalize_batch(&context, submission_slot, &["a", "c"]);
+    send(&mut context, &[finalize], &[]).await.unwrap();
+    assert!(session(&mut context, "c").await.finalized);
+    assert_eq!(pool_stats(&mut context).await.sessions_finalized, 3);
+
+    let finalize = finalize_batch(&context, submission_slot, &["later"]);
+    let result = send(&mut context, &[finalize], &[]).await;
+    assert_error(result, RemoteViewingError::SessionSlotMismatch);
+}
@@ -0,0 +1,81 @@
+use crate::associative::{create, finalize, resolve};
+use crate::reanchor::{finalize_late, reanchor};
+use crate::*;
+use remote_viewing_verifier::ProgramConfig;
+use solana_sdk::account::{AccountSharedData, ReadableAccount, WritableAccount};
+use solana_sdk::rent::Rent;
+
+/// Write an initialized, frozen config account, funded from the payer so the
+/// bank's capitalization still adds up when later slots are warped to
+async fn freeze(context: &mut ProgramTestContext) {
+    let config = ProgramConfig {
+        governance: context.payer.pubkey(),
+        upgrade_delay: 0,
+        frozen: true,
+        frozen_changed_at: 0,
+        pending_upgrade_hash: [0; 32],
+        upgrade_announced_at: 0,
+        upgrade_effective_at: 0,
+        last_upgrade_hash: [0; 32],
+        last_upgraded_at: 0,
+        admin: context.payer.pubkey(),
+        paused: false,
+        max_targets_per_pool: 10000,
+    };
+    let data = config.try_to_vec().unwrap();
+    let lamports = Rent::default().minimum_balance(data.len());
+    let mut account = AccountSharedData::new(lamports, data.len(), &id());
+    account.set_data_from_slice(&data);
+    context.set_account(&config_address(&id()), &account);
+
+    let payer = context.payer.pubkey();
+    let mut payer_account: AccountSharedData =
+        context.banks_client.get_account(payer).await.unwrap().unwrap().into();
+    payer_account.set_lamports(payer_account.lamports() - lamports);
+    context.set_account(&payer, &payer_account);
+}
+
+#[tokio::test]
+async fn test_late_session_finalized_while_frozen() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+    let submit = submit_session(&context, "session", "pool", 0);
+    send(&mut context, &[submit], &[]).await.unwrap();
+    let submission_slot = session(&mut context, "session").await.submission_slot;
+    freeze(&mut context).await;
+
+    // Submission is refused, but an expired session can still be re-anchored
+    // and finalized
+    let submit = submit_session(&context, "other", "pool", 0);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_error(result, RemoteViewingError::ProgramFrozen);
+
+    warp(&mut context, submission_slot + MAX_DELAY + 1);
+    let instruction = reanchor(&context, "session");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let reanchor_slot = slot(&mut context).await;
+    warp(&mut context, reanchor_slot + MIN_DELAY);
+    warp(&mut context, reanchor_slot + MIN_DELAY + 1);
+    let instruction = finalize_late(&context, "session", "pool");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    assert!(session(&mut context, "session").await.finalized);
+}
+
+#[tokio::test]
+async fn test_associative_session_finalized_while_frozen() {
+    let mut context = start().await;
+    let now = now(&mut context).await;
+    let create_instruction = create(&context, now + 200, now + 100);
+    let submit = submit_session(&context, "session", "pool", 0);
+    send(&mut context, &[create_instruction, submit], &[]).await.unwrap();
+    freeze(&mut context).await;
+
+    set_time(&mut context, now + 200).await;
+    let resolver = context.payer.pubkey();
+    send(&mut context, &[resolve(&resolver, 1)], &[]).await.unwrap();
+    let finalize_instruction = finalize(&context);
+    send(&mut context, &[finalize_instruction], &[]).await.unwrap();
+    let session = session(&mut context, "session").await;
+    assert!(session.finalized);
+    assert_eq!(session.assigned_target_index, 1);
+}
@@ -0,0 +1,256 @@
+use crate::*;
+use remote_viewing_verifier::{
+    judge_authorization_address, judgement_address, judgement_commitment, Judgement, JudgingMethod, PoolStats,
+    ViewerStats, JUDGEMENT_REVEAL_PERIOD,
+};
+
+const SALT: [u8; 32] = [5; 32];
+
+// Every test judges one session, "session" in pool "pool", with the payer
+// as judge unless it says otherwise
+fn session_account() -> Pubkey {
+    session_address(&id(), &RecordId::from("session"))
+}
+
+fn judgement(judge: &Pubkey) -> Pubkey {
+    judgement_address(&id(), &session_account(), judge)
+}
+
+fn authorization(judge: &Pubkey) -> Pubkey {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    judge_authorization_address(&id(), &pool, judge)
+}
+
+/// Authorize `judge` to judge the pool's sessions, signed by the payer as
+/// pool creator
+async fn authorize_judge(context: &mut ProgramTestContext, judge: &Pubkey) {
+    let authorize = RemoteViewingInstruction::AuthorizeJudge {
+        pool_id: RecordId::from("pool"),
+        judge: *judge,
+    };
+    let accounts = vec![
+        AccountMeta::new(authorization(judge), false),
+        AccountMeta::new_readonly(pool_address(&id(), &RecordId::from("pool")), false),
+        AccountMeta::new_readonly(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        config_account(),
+    ];
+    send(context, &[instruction(&authorize, accounts)], &[]).await.unwrap();
+}
+
+fn commit(judge: &Pubkey, commitment: [u8; 32]) -> Instruction {
+    let commit = RemoteViewingInstruction::CommitJudgement {
+        session_id: RecordId::from("session"),
+        method: JudgingMethod::Rank,
+        commitment,
+    };
+    let accounts = vec![
+        AccountMeta::new(judgement(judge), false),
+        AccountMeta::new(session_account(), false),
+        AccountMeta::new_readonly(authorization(judge), false),
+        AccountMeta::new(*judge, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        config_account(),
+    ];
+    instruction(&commit, accounts)
+}
+
+fn judge(context: &ProgramTestContext, judge: &Pubkey, candidates: Vec<u16>, scores: Vec<u8>) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let judge_session = RemoteViewingInstruction::JudgeSession {
+        session_id: RecordId::from("session"),
+        method: JudgingMethod::Rank,
+        candidates,
+        scores,
+        salt: SALT,
+    };
+    let accounts = vec![
+        AccountMeta::new(judgement(judge), false),
+        AccountMeta::new_readonly(session_account(), false),
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new_readonly(authorization(judge), false),
+        AccountMeta::new(*judge, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+        config_account(),
+    ];
+    instruction(&judge_session, accounts)
+}
+
+fn forfeit(context: &ProgramTestContext) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let forfeit = RemoteViewingInstruction::ForfeitJudgement {
+        session_id: RecordId::from("session"),
+        judge: context.payer.pubkey(),
+    };
+    let accounts = vec![
+        AccountMeta::new(judgement(&context.payer.pubkey()), false),
+        AccountMeta::new_readonly(session_account(), false),
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new_readonly(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+        config_account(),
+    ];
+    instruction(&forfeit, accounts)
+}
+
+/// Submit a session with `decoy_count` decoys to a fresh pool the payer may
+/// judge, returning its submission slot
+async fn submit(context: &mut ProgramTestContext, decoy_count: u8) -> Slot {
+    create_pool(context, "pool", 8).await;
+    let payer = context.payer.pubkey();
+    authorize_judge(context, &payer).await;
+    let submit = submit_session(context, "session", "pool", decoy_count);
+    send(context, &[submit], &[]).await.unwrap();
+    session(context, "session").await.submission_slot
+}
+
+async fn finalize(context: &mut ProgramTestContext, submission_slot: Slot) -> Session {
+    warp(context, submission_slot + MIN_DELAY + 1);
+    let finalize = finalize_session(context, "session", "pool");
+    send(context, &[finalize], &[]).await.unwrap();
+    session(context, "session").await
+}
+
+#[tokio::test]
+async fn test_judging_requires_decoys() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context, 0).await;
+
+    let commit = commit(&context.payer.pubkey(), [1; 32]);
+    let result = send(&mut context, &[commit], &[]).await;
+    assert_error(result, RemoteViewingError::NotEnoughCandidates);
+
+    // Open judging would otherwise score the target alone, a guaranteed hit
+    let session = finalize(&mut context, submission_slot).await;
+    let judge = judge(&context, &context.payer.pubkey(), vec![session.assigned_target_index], vec![1]);
+    let result = send(&mut context, &[judge], &[]).await;
+    assert_error(result, RemoteViewingError::NotEnoughCandidates);
+}
+
+#[tokio::test]
+async fn test_open_judgement_scored_among_candidates() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context, 2).await;
+    let session = finalize(&mut context, submission_slot).await;
+    assert_eq!(session.candidates.len(), 3);
+
+    // Rank the target second
+    let mut candidates = session.candidates.clone();
+    let target = candidates.iter().position(|&c| c == session.assigned_target_index).unwrap();
+    candidates.swap(target, 1);
+    let judge = judge(&context, &context.payer.pubkey(), candidates, vec![1, 2, 3]);
+    send(&mut context, &[judge], &[]).await.unwrap();
+
+    let judgement = judgement(&context.payer.pubkey());
+    let judgement: Judgement = account(&mut context, &judgement).await;
+    assert!(judgement.judged && !judgement.forfeited);
+    assert_eq!(judgement.target_score, 2);
+
+    // Judged with the target known, so recorded but kept out of the stats
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let stats: PoolStats = account(&mut context, &pool_stats_address(&id(), &pool)).await;
+    assert_eq!((stats.ranked_judgements, stats.rank_sum, stats.first_place_hits), (0, 0, 0));
+}
+
+#[tokio::test]
+async fn test_unrevealed_blind_judgement_forfeited_as_miss() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context, 2).await;
+    // Blind: every target in the pool is ranked before the draw
+    let candidates: Vec<u16> = (0..8).collect();
+    let scores: Vec<u8> = (1..=8).collect();
+    let commitment = judgement_commitment(JudgingMethod::Rank, &candidates, &scores, &SALT);
+    let commit = commit(&context.payer.pubkey(), commitment);
+    send(&mut context, &[commit], &[]).await.unwrap();
+    let session = finalize(&mut context, submission_slot).await;
+    assert_eq!(session.designated_judge, context.payer.pubkey());
+
+    let instruction = forfeit(&context);
+    let result = send(&mut context, &[instruction], &[]).await;
+    assert_error(result, RemoteViewingError::RevealDeadlineNotReached);
+
+    set_time(&mut context, session.finalized_at + JUDGEMENT_REVEAL_PERIOD + 1).await;
+    let judge = judge(&context, &context.payer.pubkey(), candidates, scores);
+    let result = send(&mut context, &[judge], &[]).await;
+    assert_error(result, RemoteViewingError::RevealDeadlinePassed);
+
+    // Scored last among the session's candidates
+    let instruction = forfeit(&context);
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let judgement = judgement(&context.payer.pubkey());
+    let judgement: Judgement = account(&mut context, &judgement).await;
+    assert!(judgement.judged && judgement.forfeited);
+    assert_eq!(judgement.target_score, 3);
+
+    let viewer_stats = viewer_stats_address(&id(), &context.payer.pubkey());
+    let stats: ViewerStats = account(&mut context, &viewer_stats).await;
+    assert_eq!((stats.totals.ranked_judgements, stats.totals.rank_sum, stats.totals.first_place_hits), (1, 3, 0));
+
+    // Forfeiting is final
+    let slot = slot(&mut context).await;
+    warp(&mut context, slot + 1);
+    let instruction = forfeit(&context);
+    let result = send(&mut context, &[instruction], &[]).await;
+    assert_error(result, RemoteViewingError::JudgementAlreadySubmitted);
+}
+
+#[tokio::test]
+async fn test_only_designated_judge_counted() {
+    let mut context = start().await;
+    let submission_slot = submit(&mut context, 2).await;
+    let second_judge = Keypair::new();
+    authorize_judge(&mut context, &second_judge.pubkey()).await;
+    let third_judge = Keypair::new();
+    authorize_judge(&mut context, &third_judge.pubkey()).await;
+    let fund = [&second_judge, &third_judge]
+        .map(|judge| system_instruction::transfer(&context.payer.pubkey(), &judge.pubkey(), 1_000_000_000));
+    send(&mut context, &fund, &[]).await.unwrap();
+
+    // Both blind judges rank every target, in opposite orders
+    let candidates: Vec<u16> = (0..8).collect();
+    let first_scores: Vec<u8> = (1..=8).collect();
+    let second_scores: Vec<u8> = (1..=8).rev().collect();
+    let first = judgement_commitment(JudgingMethod::Rank, &candidates, &first_scores, &SALT);
+    let second = judgement_commitment(JudgingMethod::Rank, &candidates, &second_scores, &SALT);
+    let first = commit(&context.payer.pubkey(), first);
+    send(&mut context, &[first], &[]).await.unwrap();
+    send(&mut context, &[commit(&second_judge.pubkey(), second)], &[&second_judge]).await.unwrap();
+    let session = finalize(&mut context, submission_slot).await;
+    assert_eq!(session.designated_judge, context.payer.pubkey());
+
+    // The later commitment and an open judgement that places the target first
+    // are recorded, but the session is counted once, by its designated judge
+    let mut open_candidates = session.candidates.clone();
+    let target = open_candidates.iter().position(|&c| c == session.assigned_target_index).unwrap();
+    open_candidates.swap(target, 0);
+    let instructions = [
+        judge(&context, &second_judge.pubkey(), candidates.clone(), second_scores),
+        judge(&context, &third_judge.pubkey(), open_candidates, vec![1, 2, 3]),
+        judge(&context, &context.payer.pubkey(), candidates, first_scores),
+    ];
+    send(&mut context, &instructions, &[&second_judge, &third_judge]).await.unwrap();
+
+    let designated = judgement(&context.payer.pubkey());
+    let designated: Judgement = account(&mut context, &designated).await;
+    let third = judgement(&third_judge.pubkey());
+    let third: Judgement = account(&mut context, &third).await;
+    assert!(third.judged);
+    assert_eq!(third.target_score, 1);
+
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let stats: PoolStats = account(&mut context, &pool_stats_address(&id(), &pool)).await;
+    let first_place = u32::from(designated.target_score == 1);
+    assert_eq!(
+        (stats.ranked_judgements, stats.rank_sum, stats.first_place_hits),
+        (1, designated.target_score as u64, first_place)
+    );
+    let viewer_stats = viewer_stats_address(&id(), &context.payer.pubkey());
+    let viewer_stats: ViewerStats = account(&mut context, &viewer_stats).await;
+    assert_eq!(viewer_stats.totals.ranked_judgements, 1);
+}
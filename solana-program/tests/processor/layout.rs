@@ -0,0 +1,30 @@
+use crate::*;
+use remote_viewing_verifier::legacy::LegacyTargetPool;
+use solana_sdk::account::AccountSharedData;
+
+#[tokio::test]
+async fn test_legacy_pool_rejected() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+
+    // Rewrite the pool in the layout of the first deployment, at the same
+    // address and still owned by the program
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let legacy = LegacyTargetPool {
+        pool_id: "pool".to_string(),
+        creator: context.payer.pubkey(),
+        target_count: 4,
+        targets: (0..4).map(|i| [i; 32]).collect(),
+        created_at: 0,
+        finalized: true,
+    };
+    let data = legacy.try_to_vec().unwrap();
+    let lamports = context.banks_client.get_account(pool).await.unwrap().unwrap().lamports;
+    let mut account = AccountSharedData::new(lamports, data.len(), &id());
+    account.set_data_from_slice(&data);
+    context.set_account(&pool, &account);
+
+    let submit = submit_session(&context, "session", "pool", 0);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_error(result, RemoteViewingError::UnsupportedAccountLayout);
+}
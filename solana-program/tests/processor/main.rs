@@ -0,0 +1,197 @@
+// Processor tests against an in-process bank (solana-program-test)
+//
+// Each test starts a fresh bank with the program loaded natively and drives
+// it through real transactions, so account checks, sysvars and PDAs are
+// exercised the way a validator would. Slots are advanced with
+// `warp_to_slot`, which also records the parent slot's hash in SlotHashes.
+
+mod amend;
+mod associative;
+mod decoys;
+mod finalize;
+mod freeze;
+mod judging;
+mod layout;
+mod reanchor;
+mod viewer;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use remote_viewing_verifier::idl::instruction_accounts;
+use remote_viewing_verifier::{
+    config_address, id, pool_address, pool_stats_address, process_instruction, session_address,
+    viewer_stats_address, HashAlgorithm, RecordId, RemoteViewingError, RemoteViewingInstruction, Session,
+    TargetPool,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    clock::{Clock, Slot},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Finalization delay bounds of the pools created here
+pub const MIN_DELAY: u64 = 4;
+pub const MAX_DELAY: u64 = 150;
+
+pub async fn start() -> ProgramTestContext {
+    let mut program_test = ProgramTest::new("remote_viewing_verifier", id(), processor!(process_instruction));
+    program_test.prefer_bpf(false);
+    program_test.start_with_context().await
+}
+
+/// Build a program instruction, checking its accounts against the list the
+/// IDL publishes for it so the two cannot drift apart
+pub fn instruction(data: &RemoteViewingInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    let debug = format!("{:?}", data);
+    let name = debug.split([' ', '{']).next().unwrap();
+    let listed = instruction_accounts(name).unwrap_or_else(|| panic!("{} is not in the IDL", name));
+
+    let fixed = listed.leading.len() + listed.trailing.len();
+    if listed.variable {
+        assert!(accounts.len() >= fixed, "{}: {} accounts sent, IDL lists {}", name, accounts.len(), fixed);
+    } else {
+        assert_eq!(accounts.len(), fixed, "{}: account count differs from the IDL", name);
+    }
+    let trailing = &accounts[accounts.len() - listed.trailing.len()..];
+    let pairs = accounts.iter().zip(listed.leading).chain(trailing.iter().zip(listed.trailing));
+    for (meta, &(account, writable, signer, _)) in pairs {
+        assert_eq!(
+            (meta.is_writable, meta.is_signer),
+            (writable, signer),
+            "{}: {} is (writable, signer) in the IDL",
+            name,
+            account
+        );
+    }
+
+    Instruction::new_with_bytes(id(), &data.try_to_vec().unwrap(), accounts)
+}
+
+/// Sign with the payer and `signers` and process the transaction
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&context.payer.pubkey()), &all_signers, blockhash);
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Assert that a transaction failed with `expected` from its first instruction
+pub fn assert_error(result: Result<(), BanksClientError>, expected: RemoteViewingError) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(code)) => {
+            assert_eq!(code, expected.clone() as u32, "expected {:?}", expected)
+        }
+        other => panic!("expected {:?}, got {:?}", expected, other),
+    }
+}
+
+pub async fn slot(context: &mut ProgramTestContext) -> Slot {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+}
+
+pub fn warp(context: &mut ProgramTestContext, slot: Slot) {
+    context.warp_to_slot(slot).unwrap();
+}
+
+/// Move to the next slot with the clock at `unix_timestamp`. The new slot
+/// also gives the following transactions a fresh blockhash.
+pub async fn set_time(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let slot = slot(context).await;
+    warp(context, slot + 1);
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+pub async fn now(context: &mut ProgramTestContext) -> i64 {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+}
+
+pub async fn account<T: BorshDeserialize>(context: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("account exists");
+    T::try_from_slice(&account.data).unwrap()
+}
+
+pub async fn session(context: &mut ProgramTestContext, session_id: &str) -> Session {
+    account(context, &session_address(&id(), &RecordId::from(session_id))).await
+}
+
+pub fn config_account() -> AccountMeta {
+    AccountMeta::new_readonly(config_address(&id()), false)
+}
+
+/// Create a pool whose target `i` hashes to `[i; 32]`, paid for by the payer
+pub async fn create_pool(context: &mut ProgramTestContext, pool_id: &str, target_count: u8) {
+    let pool = pool_address(&id(), &RecordId::from(pool_id));
+    let create = RemoteViewingInstruction::CreateTargetPool {
+        pool_id: RecordId::from(pool_id),
+        target_hashes: (0..target_count).map(|i| [i; 32]).collect(),
+        hash_algorithm: HashAlgorithm::Sha256,
+        min_finalization_delay: MIN_DELAY,
+        max_finalization_delay: MAX_DELAY,
+    };
+    let accounts = vec![
+        AccountMeta::new(pool, false),
+        AccountMeta::new_readonly(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+        config_account(),
+    ];
+    send(context, &[instruction(&create, accounts)], &[]).await.unwrap();
+}
+
+/// A SubmitSession with the payer as submitter and viewer
+pub fn submit_session(context: &ProgramTestContext, session_id: &str, pool_id: &str, decoy_count: u8) -> Instruction {
+    let viewer = context.payer.pubkey();
+    let submit = RemoteViewingInstruction::SubmitSession {
+        session_id: RecordId::from(session_id),
+        pool_id: RecordId::from(pool_id),
+        session_media_hash: [1; 32],
+        target_selector_program: Pubkey::default(),
+        completed_target_indices: Vec::new(),
+        media_hash_algorithm: HashAlgorithm::Sha256,
+        media_item_count: 1,
+        finalization_reward: 0,
+        decoy_count,
+        tasking_commitment: [0; 32],
+        experiment: Pubkey::default(),
+    };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from(session_id)), false),
+        AccountMeta::new_readonly(pool_address(&id(), &RecordId::from(pool_id)), false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(viewer, true),
+        AccountMeta::new(viewer_stats_address(&id(), &viewer), false),
+        config_account(),
+    ];
+    instruction(&submit, accounts)
+}
+
+/// A FinalizeSession for a session submitted by `submit_session`
+pub fn finalize_session(context: &ProgramTestContext, session_id: &str, pool_id: &str) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from(pool_id));
+    let finalize = RemoteViewingInstruction::FinalizeSession {
+        session_id: RecordId::from(session_id),
+    };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from(session_id)), false),
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+    ];
+    instruction(&finalize, accounts)
+}
@@ -0,0 +1,132 @@
+use crate::*;
+use remote_viewing_verifier::{PoolStats, ViewerStats};
+
+pub fn reanchor(context: &ProgramTestContext, session_id: &str) -> Instruction {
+    let reanchor = RemoteViewingInstruction::ReanchorSession { session_id: RecordId::from(session_id) };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from(session_id)), false),
+        AccountMeta::new_readonly(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        config_account(),
+    ];
+    instruction(&reanchor, accounts)
+}
+
+pub fn finalize_late(context: &ProgramTestContext, session_id: &str, pool_id: &str) -> Instruction {
+    let pool = pool_address(&id(), &RecordId::from(pool_id));
+    let finalize = RemoteViewingInstruction::FinalizeLateSession { session_id: RecordId::from(session_id) };
+    let accounts = vec![
+        AccountMeta::new(session_address(&id(), &RecordId::from(session_id)), false),
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new(context.payer.pubkey(), true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new(viewer_stats_address(&id(), &context.payer.pubkey()), false),
+        AccountMeta::new(pool_stats_address(&id(), &pool), false),
+    ];
+    instruction(&finalize, accounts)
+}
+
+/// Submit a session and let its finalization window pass, returning the
+/// submission slot
+async fn submit_and_expire(context: &mut ProgramTestContext) -> Slot {
+    create_pool(context, "pool", 4).await;
+    let submit = submit_session(context, "session", "pool", 0);
+    send(context, &[submit], &[]).await.unwrap();
+    let submission_slot = session(context, "session").await.submission_slot;
+    warp(context, submission_slot + MAX_DELAY + 1);
+    submission_slot
+}
+
+#[tokio::test]
+async fn test_reanchor_rejected_while_window_open() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+    let submit = submit_session(&context, "session", "pool", 0);
+    send(&mut context, &[submit], &[]).await.unwrap();
+    let submission_slot = session(&mut context, "session").await.submission_slot;
+
+    warp(&mut context, submission_slot + MAX_DELAY);
+    let reanchor = reanchor(&context, "session");
+    let result = send(&mut context, &[reanchor], &[]).await;
+    assert_error(result, RemoteViewingError::FinalizationWindowOpen);
+}
+
+#[tokio::test]
+async fn test_reanchor_again_after_late_window() {
+    let mut context = start().await;
+    submit_and_expire(&mut context).await;
+
+    let instruction = reanchor(&context, "session");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let first_reanchor = session(&mut context, "session").await.reanchor_slot;
+    assert_eq!(first_reanchor, slot(&mut context).await);
+
+    // Not while the late window is still open
+    warp(&mut context, first_reanchor + MAX_DELAY);
+    let instruction = reanchor(&context, "session");
+    let result = send(&mut context, &[instruction], &[]).await;
+    assert_error(result, RemoteViewingError::FinalizationWindowOpen);
+
+    // Once it has passed too, the session is re-anchored again rather than
+    // left unfinalizable
+    warp(&mut context, first_reanchor + MAX_DELAY + 1);
+    let instruction = reanchor(&context, "session");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let session_state = session(&mut context, "session").await;
+    assert_eq!(session_state.reanchor_count, 2);
+    let reanchor_slot = session_state.reanchor_slot;
+    assert!(reanchor_slot > first_reanchor + MAX_DELAY);
+
+    warp(&mut context, reanchor_slot + MIN_DELAY);
+    warp(&mut context, reanchor_slot + MIN_DELAY + 1);
+    let instruction = finalize_late(&context, "session", "pool");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let session = session(&mut context, "session").await;
+    assert!(session.finalized && session.late_finalized);
+    assert!(session.entropy_slot > reanchor_slot);
+}
+
+#[tokio::test]
+async fn test_finalize_late_session() {
+    let mut context = start().await;
+    submit_and_expire(&mut context).await;
+    let instruction = reanchor(&context, "session");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let reanchor_slot = slot(&mut context).await;
+
+    // Produce a hash for a slot after the re-anchor
+    warp(&mut context, reanchor_slot + MIN_DELAY);
+    warp(&mut context, reanchor_slot + MIN_DELAY + 1);
+    let instruction = finalize_late(&context, "session", "pool");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+
+    let session = session(&mut context, "session").await;
+    assert!(session.finalized && session.late_finalized);
+    assert!(session.entropy_slot > reanchor_slot);
+}
+
+#[tokio::test]
+async fn test_late_finalization_kept_out_of_hit_rate_stats() {
+    let mut context = start().await;
+    submit_and_expire(&mut context).await;
+    let instruction = reanchor(&context, "session");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+    let reanchor_slot = slot(&mut context).await;
+    warp(&mut context, reanchor_slot + MIN_DELAY);
+    warp(&mut context, reanchor_slot + MIN_DELAY + 1);
+    let instruction = finalize_late(&context, "session", "pool");
+    send(&mut context, &[instruction], &[]).await.unwrap();
+
+    let pool = pool_address(&id(), &RecordId::from("pool"));
+    let pool_stats: PoolStats = account(&mut context, &pool_stats_address(&id(), &pool)).await;
+    assert_eq!(pool_stats.sessions_late_finalized, 1);
+    assert_eq!(pool_stats.sessions_finalized, 0);
+    assert_eq!(pool_stats.assignment_counts, vec![0; 4]);
+
+    let viewer_stats = viewer_stats_address(&id(), &context.payer.pubkey());
+    let viewer_stats: ViewerStats = account(&mut context, &viewer_stats).await;
+    assert_eq!(viewer_stats.totals.sessions_late_finalized, 1);
+    assert_eq!(viewer_stats.totals.sessions_finalized, 0);
+    assert_eq!(viewer_stats.pools[0].counters.sessions_late_finalized, 1);
+}
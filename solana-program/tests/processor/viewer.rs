@@ -0,0 +1,96 @@
+use crate::*;
+use remote_viewing_verifier::{BatchSessionEntry, ViewerStats};
+
+/// A SubmitSession paid for by the payer on behalf of `viewer`, who signs
+/// only when `signed`
+fn submit_for(context: &ProgramTestContext, session_id: &str, viewer: &Pubkey, signed: bool) -> Instruction {
+    let mut submit = submit_session(context, session_id, "pool", 0);
+    submit.accounts[5] = AccountMeta::new_readonly(*viewer, signed);
+    submit.accounts[6] = AccountMeta::new(viewer_stats_address(&id(), viewer), false);
+    submit
+}
+
+fn submit_batch(viewers: &[(&Pubkey, bool)], submitter: &Pubkey) -> Instruction {
+    let sessions = (0..viewers.len())
+        .map(|i| BatchSessionEntry {
+            session_id: RecordId::from(format!("session-{}", i).as_str()),
+            session_media_hash: [1; 32],
+            completed_target_indices: Vec::new(),
+            media_item_count: 1,
+            tasking_commitment: [0; 32],
+        })
+        .collect::<Vec<_>>();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(pool_address(&id(), &RecordId::from("pool")), false),
+        AccountMeta::new(*submitter, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    for (entry, (viewer, signed)) in sessions.iter().zip(viewers) {
+        accounts.push(AccountMeta::new(session_address(&id(), &entry.session_id), false));
+        accounts.push(AccountMeta::new_readonly(**viewer, *signed));
+        accounts.push(AccountMeta::new(viewer_stats_address(&id(), viewer), false));
+    }
+    accounts.push(config_account());
+    let submit = RemoteViewingInstruction::SubmitSessionBatch {
+        pool_id: RecordId::from("pool"),
+        target_selector_program: Pubkey::default(),
+        media_hash_algorithm: HashAlgorithm::Sha256,
+        finalization_reward: 0,
+        sessions,
+        experiment: Pubkey::default(),
+        decoy_count: 0,
+    };
+    instruction(&submit, accounts)
+}
+
+fn assert_missing_signature(result: Result<(), BanksClientError>) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn test_viewer_must_sign_submission() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+    let viewer = Keypair::new();
+
+    // A submitter cannot credit a session to someone else's track record
+    let submit = submit_for(&context, "session", &viewer.pubkey(), false);
+    let result = send(&mut context, &[submit], &[]).await;
+    assert_missing_signature(result);
+
+    let submit = submit_for(&context, "session", &viewer.pubkey(), true);
+    send(&mut context, &[submit], &[&viewer]).await.unwrap();
+    let session = session(&mut context, "session").await;
+    assert_eq!(session.viewer, viewer.pubkey());
+    assert_eq!(session.submitter, context.payer.pubkey());
+
+    let stats: ViewerStats = account(&mut context, &viewer_stats_address(&id(), &viewer.pubkey())).await;
+    assert_eq!(stats.totals.sessions_submitted, 1);
+    let payer_stats = viewer_stats_address(&id(), &context.payer.pubkey());
+    assert!(context.banks_client.get_account(payer_stats).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_every_batch_viewer_must_sign() {
+    let mut context = start().await;
+    create_pool(&mut context, "pool", 4).await;
+    let (first, second) = (Keypair::new(), Keypair::new());
+    let payer = context.payer.pubkey();
+
+    let submit = submit_batch(&[(&first.pubkey(), true), (&second.pubkey(), false)], &payer);
+    let result = send(&mut context, &[submit], &[&first]).await;
+    assert_missing_signature(result);
+
+    let submit = submit_batch(&[(&first.pubkey(), true), (&second.pubkey(), true)], &payer);
+    send(&mut context, &[submit], &[&first, &second]).await.unwrap();
+    for (i, viewer) in [&first, &second].iter().enumerate() {
+        let session = session(&mut context, &format!("session-{}", i)).await;
+        assert_eq!(session.viewer, viewer.pubkey());
+        let stats: ViewerStats = account(&mut context, &viewer_stats_address(&id(), &viewer.pubkey())).await;
+        assert_eq!(stats.totals.sessions_submitted, 1);
+    }
+}
@@ -7,6 +7,7 @@ import {
   TransactionInstruction,
   sendAndConfirmTransaction,
   SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
 } from '@solana/web3.js';
 import { createHash } from 'crypto';
 import bs58 from 'bs58';
@@ -19,32 +20,153 @@ import {
   PoolData,
 } from './types';
 
-// Instruction discriminators - these match the Rust enum variant order
+// Instruction discriminators - these match the Rust enum variant order, as
+// listed in solana-program/idl/remote_viewing_verifier.json
 enum InstructionType {
   CreateTargetPool = 0,
   SubmitSession = 1,
   FinalizeSession = 2,
   AppendTargetsToPool = 3,
   FinalizePool = 4,
+  AmendSessionMedia = 5,
+  SubmitSessionBatch = 6,
+  FinalizeSessionsBatch = 7,
+  ReanchorSession = 8,
+  FinalizeLateSession = 9,
+  CreateAssociativePool = 10,
+  ResolveOutcome = 11,
+  FinalizeAssociativeSession = 12,
+  AuthorizeJudge = 13,
+  CommitJudgement = 14,
+  JudgeSession = 15,
+  RevealTasking = 16,
+  CreateExperiment = 17,
+  UpdateExperimentPlan = 18,
+  InitializeConfig = 19,
+  SetFrozen = 20,
+  AnnounceUpgrade = 21,
+  ExecuteUpgrade = 22,
+  SetGovernance = 23,
+  SetPaused = 24,
+  SetLimits = 25,
+  SetAdmin = 26,
+  ForfeitJudgement = 27,
 }
 
+// Algorithm of pool target hashes and session media hashes (Rust
+// `HashAlgorithm`, encoded as its variant index)
+export enum HashAlgorithm {
+  Sha256 = 0,
+  Keccak256 = 1,
+  Blake3 = 2,
+  PerceptualHash = 3,
+}
+
+// Finalization delay bounds, in slots, of pools created here: the program's
+// minimum, and the oldest slot SlotHashes still holds
+const MIN_FINALIZATION_DELAY_SLOTS = 2;
+const MAX_FINALIZATION_DELAY_SLOTS = 511;
+
+// Longest text id the program accepts, in bytes (Rust `MAX_ID_LEN`)
+const MAX_ID_LEN = 64;
+
+// Pool or session id (Rust `RecordId`): bounded text, or 32 fixed bytes such
+// as a zero-padded UUID. Ids are written the way rv-cli takes them:
+// `uuid:<uuid>` and `hex:<64 hex digits>` are fixed, anything else is text.
+class RecordId {
+  enum: 'text' | 'fixed';
+  text?: string;
+  fixed?: Uint8Array;
+
+  private constructor(text?: string, fixed?: Uint8Array) {
+    this.enum = fixed ? 'fixed' : 'text';
+    this.text = text;
+    this.fixed = fixed;
+  }
+
+  static parse(id: string): RecordId {
+    let recordId: RecordId;
+    if (id.startsWith('uuid:')) {
+      const uuid = id.slice(5).replace(/-/g, '');
+      if (!/^[0-9a-fA-F]{32}$/.test(uuid)) {
+        throw new Error(`Invalid UUID id: ${id}`);
+      }
+      const bytes = new Uint8Array(32);
+      bytes.set(Buffer.from(uuid, 'hex'));
+      recordId = new RecordId(undefined, bytes);
+    } else if (id.startsWith('hex:')) {
+      if (!/^[0-9a-fA-F]{64}$/.test(id.slice(4))) {
+        throw new Error(`Invalid hex id (must be 32 bytes): ${id}`);
+      }
+      recordId = new RecordId(undefined, Buffer.from(id.slice(4), 'hex'));
+    } else {
+      if (Buffer.byteLength(id, 'utf8') > MAX_ID_LEN) {
+        throw new Error(`Id longer than ${MAX_ID_LEN} bytes: ${id}`);
+      }
+      recordId = new RecordId(id);
+    }
+
+    if (recordId.fixed ? recordId.fixed.every(byte => byte === 0) : !id) {
+      throw new Error('Ids must be non-empty and non-zero');
+    }
+    return recordId;
+  }
+
+  // 32-byte PDA seed: the SHA-256 of a text id, or the fixed bytes as they are
+  seed(): Buffer {
+    if (this.fixed) {
+      return Buffer.from(this.fixed);
+    }
+    return createHash('sha256').update(this.text!).digest();
+  }
+}
+
+// Borsh enum: a u8 tag (0 text, 1 fixed) and the variant's value
+const RECORD_ID_SCHEMA: [Function, unknown] = [
+  RecordId,
+  {
+    kind: 'enum',
+    field: 'enum',
+    values: [
+      ['text', 'string'],
+      ['fixed', ['u8', 32]],
+    ],
+  },
+];
+
 // Define the schema for Borsh serialization matching Rust enum structure
 class CreateTargetPoolInstruction {
-  pool_id: string;
+  pool_id: RecordId;
   target_hashes: Uint8Array[];
+  hash_algorithm: number;
+  min_finalization_delay: number;
+  max_finalization_delay: number;
 
-  constructor(poolId: string, targetHashes: string[]) {
-    this.pool_id = poolId;
+  constructor(
+    poolId: string,
+    targetHashes: string[],
+    hashAlgorithm: HashAlgorithm,
+  ) {
+    this.pool_id = RecordId.parse(poolId);
     this.target_hashes = targetHashes.map(hash => Buffer.from(hash, 'hex'));
+    this.hash_algorithm = hashAlgorithm;
+    this.min_finalization_delay = MIN_FINALIZATION_DELAY_SLOTS;
+    this.max_finalization_delay = MAX_FINALIZATION_DELAY_SLOTS;
   }
 }
 
 class SubmitSessionInstruction {
-  session_id: string;
-  pool_id: string;
+  session_id: RecordId;
+  pool_id: RecordId;
   session_media_hash: Uint8Array;
   target_selector_program: Uint8Array;
   completed_target_indices: number[];
+  media_hash_algorithm: number;
+  media_item_count: number;
+  finalization_reward: number;
+  tasking_commitment: Uint8Array;
+  experiment: Uint8Array;
+  decoy_count: number;
 
   constructor(
     sessionId: string,
@@ -52,118 +174,140 @@ class SubmitSessionInstruction {
     sessionMediaHash: Uint8Array,
     targetSelectorProgram: PublicKey,
     completedTargetIndices: number[],
+    mediaHashAlgorithm: HashAlgorithm,
   ) {
-    this.session_id = sessionId;
-    this.pool_id = poolId;
+    this.session_id = RecordId.parse(sessionId);
+    this.pool_id = RecordId.parse(poolId);
     this.session_media_hash = sessionMediaHash;
     this.target_selector_program = targetSelectorProgram.toBuffer();
     this.completed_target_indices = completedTargetIndices;
+    this.media_hash_algorithm = mediaHashAlgorithm;
+    // A single media hash, no finalization reward, not tasker-blind, outside
+    // any experiment and without decoys
+    this.media_item_count = 1;
+    this.finalization_reward = 0;
+    this.tasking_commitment = new Uint8Array(32);
+    this.experiment = new Uint8Array(32);
+    this.decoy_count = 0;
   }
 }
 
+// The program draws the target from SlotHashes itself, so only the session
+// is named
 class FinalizeSessionInstruction {
-  session_id: string;
-  submission_blockhash: string; // Changed to base58 string
-  completed_target_indices: number[];
+  session_id: RecordId;
 
-  constructor(
-    sessionId: string,
-    submissionBlockhashBase58: string,
-    completedTargetIndices: number[],
-  ) {
-    this.session_id = sessionId;
-    this.submission_blockhash = submissionBlockhashBase58;
-    this.completed_target_indices = completedTargetIndices;
+  constructor(sessionId: string) {
+    this.session_id = RecordId.parse(sessionId);
   }
 }
 
 class AppendTargetsToPoolInstruction {
-  pool_id: string;
+  pool_id: RecordId;
   target_hashes: Uint8Array[];
 
   constructor(poolId: string, targetHashes: string[]) {
-    this.pool_id = poolId;
+    this.pool_id = RecordId.parse(poolId);
     this.target_hashes = targetHashes.map(hash => Buffer.from(hash, 'hex'));
   }
 }
 
 class FinalizePoolInstruction {
-  pool_id: string;
+  pool_id: RecordId;
 
   constructor(poolId: string) {
-    this.pool_id = poolId;
+    this.pool_id = RecordId.parse(poolId);
   }
 }
 
 // Borsh schemas matching Rust enum structure
-const CREATE_POOL_SCHEMA = new Map([
+const CREATE_POOL_SCHEMA = new Map<Function, unknown>([
+  RECORD_ID_SCHEMA,
   [
     CreateTargetPoolInstruction,
     {
       kind: 'struct',
       fields: [
-        ['pool_id', 'string'],
+        ['pool_id', RecordId],
         ['target_hashes', [['u8', 32]]],
+        ['hash_algorithm', 'u8'],
+        ['min_finalization_delay', 'u64'],
+        ['max_finalization_delay', 'u64'],
       ],
     },
   ],
 ]);
 
-const SUBMIT_SESSION_SCHEMA = new Map([
+const SUBMIT_SESSION_SCHEMA = new Map<Function, unknown>([
+  RECORD_ID_SCHEMA,
   [
     SubmitSessionInstruction,
     {
       kind: 'struct',
       fields: [
-        ['session_id', 'string'],
-        ['pool_id', 'string'],
+        ['session_id', RecordId],
+        ['pool_id', RecordId],
         ['session_media_hash', ['u8', 32]],
         ['target_selector_program', ['u8', 32]],
         ['completed_target_indices', ['u16']],
+        ['media_hash_algorithm', 'u8'],
+        ['media_item_count', 'u16'],
+        ['finalization_reward', 'u64'],
+        ['tasking_commitment', ['u8', 32]],
+        ['experiment', ['u8', 32]],
+        ['decoy_count', 'u8'],
       ],
     },
   ],
 ]);
 
-const FINALIZE_SESSION_SCHEMA = new Map([
+const FINALIZE_SESSION_SCHEMA = new Map<Function, unknown>([
+  RECORD_ID_SCHEMA,
   [
     FinalizeSessionInstruction,
     {
       kind: 'struct',
-      fields: [
-        ['session_id', 'string'],
-        ['submission_blockhash', 'string'], // Changed to string for base58
-        ['completed_target_indices', ['u16']],
-      ],
+      fields: [['session_id', RecordId]],
     },
   ],
 ]);
 
-const APPEND_TARGETS_SCHEMA = new Map([
+const APPEND_TARGETS_SCHEMA = new Map<Function, unknown>([
+  RECORD_ID_SCHEMA,
   [
     AppendTargetsToPoolInstruction,
     {
       kind: 'struct',
       fields: [
-        ['pool_id', 'string'],
+        ['pool_id', RecordId],
         ['target_hashes', [['u8', 32]]],
       ],
     },
   ],
 ]);
 
-const FINALIZE_POOL_SCHEMA = new Map([
+const FINALIZE_POOL_SCHEMA = new Map<Function, unknown>([
+  RECORD_ID_SCHEMA,
   [
     FinalizePoolInstruction,
     {
       kind: 'struct',
-      fields: [['pool_id', 'string']],
+      fields: [['pool_id', RecordId]],
     },
   ],
 ]);
 
+// Pool and session accounts start with an 8-byte discriminator and a layout
+// version. Accounts from the first deployment have neither and start with
+// the u32 length of their text id, so they never match a discriminator; they
+// are still decoded, with layout version 0.
+const POOL_DISCRIMINATOR = Buffer.from('rv-pool\0', 'latin1');
+const SESSION_DISCRIMINATOR = Buffer.from('rv-sessn', 'latin1');
+const ACCOUNT_LAYOUT_VERSION = 1;
+
 // Data structures for reading blockchain data
 class SessionAccount {
+  layout_version: number = 0;
   session_id: string = '';
   pool_id: string = '';
   session_media_hash: Uint8Array = new Uint8Array(32);
@@ -176,140 +320,216 @@ class SessionAccount {
   finalized: boolean = false;
   finalized_at: bigint = BigInt(0);
   completed_target_indices: number[] = [];
+  media_hash_algorithm: number = 0;
+  media_item_count: number = 1;
+  media_history_length: number = 0;
+  finalization_reward: bigint = BigInt(0);
+  entropy_slot: bigint = BigInt(0);
+  late_finalized: boolean = false;
+  reanchor_slot: bigint = BigInt(0);
+  reanchor_count: number = 0;
+  kind: number = 0;
+  candidates: number[] = [];
+  viewer: Uint8Array = new Uint8Array(32);
+  experiment: Uint8Array = new Uint8Array(32);
+  decoy_count: number = 0;
 }
 
 class PoolAccount {
+  layout_version: number = 0;
   pool_id: string = '';
   creator: Uint8Array = new Uint8Array(32);
   target_count: number = 0;
   targets: Uint8Array[] = [];
   created_at: bigint = BigInt(0);
   finalized: boolean = false;
+  hash_algorithm: number = 0;
+  min_finalization_delay: bigint = BigInt(MIN_FINALIZATION_DELAY_SLOTS);
+  max_finalization_delay: bigint = BigInt(MAX_FINALIZATION_DELAY_SLOTS);
+  kind: number = 0;
+  outcome_labels: string[] = [];
+  resolver: Uint8Array = new Uint8Array(32);
+  resolved_outcome: number = 0xffff;
+  resolved_slot: bigint = BigInt(0);
+  event_time: bigint = BigInt(0);
+  submission_deadline: bigint = BigInt(0);
 }
 
-// Manual deserialization functions
+// Sequential little-endian reader over Borsh-encoded account data
+class BorshReader {
+  private view: DataView;
+  private offset = 0;
+
+  constructor(private data: Buffer) {
+    this.view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  }
+
+  u8(): number {
+    return this.view.getUint8(this.offset++);
+  }
+
+  bool(): boolean {
+    return this.u8() !== 0;
+  }
+
+  u16(): number {
+    const value = this.view.getUint16(this.offset, true);
+    this.offset += 2;
+    return value;
+  }
+
+  u32(): number {
+    const value = this.view.getUint32(this.offset, true);
+    this.offset += 4;
+    return value;
+  }
+
+  u64(): bigint {
+    const value = this.view.getBigUint64(this.offset, true);
+    this.offset += 8;
+    return value;
+  }
+
+  i64(): bigint {
+    const value = this.view.getBigInt64(this.offset, true);
+    this.offset += 8;
+    return value;
+  }
+
+  bytes(length: number): Uint8Array {
+    if (this.offset + length > this.data.length) {
+      throw new Error('Account data too short');
+    }
+    const value = new Uint8Array(this.data.slice(this.offset, this.offset + length));
+    this.offset += length;
+    return value;
+  }
+
+  // Rust String: 4-byte length + utf8 bytes
+  string(): string {
+    return Buffer.from(this.bytes(this.u32())).toString('utf8');
+  }
+
+  // Rust Vec<T>: 4-byte length + elements
+  vec<T>(item: () => T): T[] {
+    const length = this.u32();
+    const items: T[] = [];
+    for (let i = 0; i < length; i++) {
+      items.push(item());
+    }
+    return items;
+  }
+
+  // RecordId: text ids as they are, fixed ids as `hex:<64 hex digits>`, which
+  // `RecordId.parse` reads back to the same id
+  recordId(): string {
+    const tag = this.u8();
+    if (tag === 0) {
+      return this.string();
+    }
+    if (tag === 1) {
+      return `hex:${Buffer.from(this.bytes(32)).toString('hex')}`;
+    }
+    throw new Error(`Invalid record id tag ${tag}`);
+  }
+}
+
+// Read the discriminator and layout version, returning the version, or 0 for
+// a legacy account without them
+function readLayoutVersion(
+  reader: BorshReader,
+  data: Buffer,
+  discriminator: Buffer,
+): number {
+  if (data.length < 9 || !data.subarray(0, 8).equals(discriminator)) {
+    return 0;
+  }
+  reader.bytes(8);
+  const version = reader.u8();
+  if (version !== ACCOUNT_LAYOUT_VERSION) {
+    throw new Error(`Unsupported account layout version ${version}`);
+  }
+  return version;
+}
+
+// Manual deserialization functions. The legacy layouts are the current ones
+// without the prefix, with plain string ids and cut off after the fields the
+// first deployment had.
 function deserializeSessionAccount(data: Buffer): SessionAccount {
-  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
-  let offset = 0;
-
-  // Read session_id (string: 4-byte length + utf8 bytes)
-  const sessionIdLen = view.getUint32(offset, true);
-  offset += 4;
-  const session_id = data.slice(offset, offset + sessionIdLen).toString('utf8');
-  offset += sessionIdLen;
-
-  // Read pool_id (string: 4-byte length + utf8 bytes)
-  const poolIdLen = view.getUint32(offset, true);
-  offset += 4;
-  const pool_id = data.slice(offset, offset + poolIdLen).toString('utf8');
-  offset += poolIdLen;
-
-  // Read session_media_hash (32 bytes)
-  const session_media_hash = new Uint8Array(data.slice(offset, offset + 32));
-  offset += 32;
-
-  // Read submission_slot (u64 - 8 bytes)
-  const submission_slot = view.getBigUint64(offset, true);
-  offset += 8;
-
-  // Read submission_blockhash (32 bytes)
-  const submission_blockhash = new Uint8Array(data.slice(offset, offset + 32));
-  offset += 32;
-
-  // Read assigned_target_index (u16 - 2 bytes)
-  const assigned_target_index = view.getUint16(offset, true);
-  offset += 2;
-
-  // Read target_selector_program (32 bytes)
-  const target_selector_program = new Uint8Array(
-    data.slice(offset, offset + 32),
+  const reader = new BorshReader(data);
+  const account = new SessionAccount();
+  account.layout_version = readLayoutVersion(
+    reader,
+    data,
+    SESSION_DISCRIMINATOR,
   );
-  offset += 32;
-
-  // Read submitter (32 bytes)
-  const submitter = new Uint8Array(data.slice(offset, offset + 32));
-  offset += 32;
-
-  // Read submitted_at (i64 - 8 bytes)
-  const submitted_at = view.getBigInt64(offset, true);
-  offset += 8;
-
-  // Read finalized (bool - 1 byte)
-  const finalized = data[offset] !== 0;
-  offset += 1;
-
-  // Read finalized_at (i64 - 8 bytes)
-  const finalized_at = view.getBigInt64(offset, true);
-  offset += 8;
-
-  // Read completed_target_indices (Vec<u16>: 4-byte length + u16 elements)
-  const completedIndicesLen = view.getUint32(offset, true);
-  offset += 4;
-  const completed_target_indices: number[] = [];
-  for (let i = 0; i < completedIndicesLen; i++) {
-    completed_target_indices.push(view.getUint16(offset, true));
-    offset += 2;
+  const legacy = account.layout_version === 0;
+
+  account.session_id = legacy ? reader.string() : reader.recordId();
+  account.pool_id = legacy ? reader.string() : reader.recordId();
+  account.session_media_hash = reader.bytes(32);
+  account.submission_slot = reader.u64();
+  account.submission_blockhash = reader.bytes(32);
+  account.assigned_target_index = reader.u16();
+  account.target_selector_program = reader.bytes(32);
+  account.submitter = reader.bytes(32);
+  account.submitted_at = reader.i64();
+  account.finalized = reader.bool();
+  account.finalized_at = reader.i64();
+  account.completed_target_indices = reader.vec(() => reader.u16());
+  if (legacy) {
+    // Legacy sessions count towards their submitter
+    account.viewer = account.submitter;
+    return account;
   }
 
-  const account = new SessionAccount();
-  account.session_id = session_id;
-  account.pool_id = pool_id;
-  account.session_media_hash = session_media_hash;
-  account.submission_slot = submission_slot;
-  account.submission_blockhash = submission_blockhash;
-  account.assigned_target_index = assigned_target_index;
-  account.target_selector_program = target_selector_program;
-  account.submitter = submitter;
-  account.submitted_at = submitted_at;
-  account.finalized = finalized;
-  account.finalized_at = finalized_at;
-  account.completed_target_indices = completed_target_indices;
+  account.media_hash_algorithm = reader.u8();
+  account.media_item_count = reader.u16();
+  // MediaAmendment: previous hash, previous item count, amended slot
+  account.media_history_length = reader.vec(() => reader.bytes(32 + 2 + 8)).length;
+  account.finalization_reward = reader.u64();
+  account.entropy_slot = reader.u64();
+  account.late_finalized = reader.bool();
+  account.reanchor_slot = reader.u64();
+  account.reanchor_count = reader.u16();
+  reader.u64(); // min_finalization_delay
+  reader.u64(); // max_finalization_delay
+  account.kind = reader.u8();
+  account.candidates = reader.vec(() => reader.u16());
+  account.viewer = reader.bytes(32);
+  reader.bytes(32 + 8 + 1 + 32); // tasking commitment, reference, revealed flag, salt
+  account.experiment = reader.bytes(32);
+  account.decoy_count = reader.u8();
 
   return account;
 }
 
 function deserializePoolAccount(data: Buffer): PoolAccount {
-  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
-  let offset = 0;
-
-  // Read pool_id (string: 4-byte length + utf8 bytes)
-  const poolIdLen = view.getUint32(offset, true);
-  offset += 4;
-  const pool_id = data.slice(offset, offset + poolIdLen).toString('utf8');
-  offset += poolIdLen;
-
-  // Read creator (32 bytes)
-  const creator = new Uint8Array(data.slice(offset, offset + 32));
-  offset += 32;
-
-  // Read target_count (u16 - 2 bytes)
-  const target_count = view.getUint16(offset, true);
-  offset += 2;
-
-  // Read targets (Vec<[u8; 32]>: 4-byte length + target_count * 32 bytes)
-  const targetsLen = view.getUint32(offset, true);
-  offset += 4;
-  const targets: Uint8Array[] = [];
-  for (let i = 0; i < targetsLen; i++) {
-    targets.push(new Uint8Array(data.slice(offset, offset + 32)));
-    offset += 32;
+  const reader = new BorshReader(data);
+  const account = new PoolAccount();
+  account.layout_version = readLayoutVersion(reader, data, POOL_DISCRIMINATOR);
+  const legacy = account.layout_version === 0;
+
+  account.pool_id = legacy ? reader.string() : reader.recordId();
+  account.creator = reader.bytes(32);
+  account.target_count = reader.u16();
+  account.targets = reader.vec(() => reader.bytes(32));
+  account.created_at = reader.i64();
+  account.finalized = reader.bool();
+  if (legacy) {
+    return account;
   }
 
-  // Read created_at (i64 - 8 bytes)
-  const created_at = view.getBigInt64(offset, true);
-  offset += 8;
-
-  // Read finalized (bool - 1 byte)
-  const finalized = data[offset] !== 0;
-  offset += 1;
-
-  const account = new PoolAccount();
-  account.pool_id = pool_id;
-  account.creator = creator;
-  account.target_count = target_count;
-  account.targets = targets;
-  account.created_at = created_at;
-  account.finalized = finalized;
+  account.hash_algorithm = reader.u8();
+  account.min_finalization_delay = reader.u64();
+  account.max_finalization_delay = reader.u64();
+  account.kind = reader.u8();
+  account.outcome_labels = reader.vec(() => reader.string());
+  account.resolver = reader.bytes(32);
+  account.resolved_outcome = reader.u16();
+  account.resolved_slot = reader.u64();
+  account.event_time = reader.i64();
+  account.submission_deadline = reader.i64();
 
   return account;
 }
@@ -349,7 +569,10 @@ export class RemoteViewingSDK {
     }
   }
 
-  async createNewPool(targetHashes: string[]): Promise<CreatePoolResult> {
+  async createNewPool(
+    targetHashes: string[],
+    hashAlgorithm: HashAlgorithm = HashAlgorithm.Sha256,
+  ): Promise<CreatePoolResult> {
     // Validate input parameters
     if (!targetHashes || targetHashes.length === 0) {
       throw new Error('Target hashes cannot be empty');
@@ -360,18 +583,20 @@ export class RemoteViewingSDK {
 
     if (targetHashes.length <= MAX_HASHES_PER_TRANSACTION) {
       // Single pool - use existing logic
-      return this.createSinglePool(targetHashes);
+      return this.createSinglePool(targetHashes, hashAlgorithm);
     } else {
       // Multiple pools needed - use append logic for large pools
       return this.createPoolWithAppends(
         targetHashes,
         MAX_HASHES_PER_TRANSACTION,
+        hashAlgorithm,
       );
     }
   }
 
   private async createSinglePool(
     targetHashes: string[],
+    hashAlgorithm: HashAlgorithm,
   ): Promise<CreatePoolResult> {
     if (targetHashes.length > 10000) {
       throw new Error('Too many target hashes (max 10000)');
@@ -406,6 +631,7 @@ export class RemoteViewingSDK {
       const instructionData = this.encodeCreatePoolInstruction(
         poolId,
         targetHashes,
+        hashAlgorithm,
       );
 
       // Build instruction
@@ -419,6 +645,12 @@ export class RemoteViewingSDK {
             isSigner: false,
             isWritable: false,
           },
+          {
+            pubkey: await this.getPoolStatsPDA(poolPDA),
+            isSigner: false,
+            isWritable: true,
+          },
+          await this.getConfigAccount(),
         ],
         data: instructionData,
       });
@@ -451,6 +683,7 @@ export class RemoteViewingSDK {
   private async createPoolWithAppends(
     targetHashes: string[],
     batchSize: number,
+    hashAlgorithm: HashAlgorithm,
   ): Promise<CreatePoolResult> {
     const poolId = `pool_${Date.now()}`;
 
@@ -474,7 +707,11 @@ export class RemoteViewingSDK {
     const firstBatch = batches[0];
     console.log(`Creating initial pool with ${firstBatch.length} targets`);
 
-    const createResult = await this.createSinglePoolWithId(poolId, firstBatch);
+    const createResult = await this.createSinglePoolWithId(
+      poolId,
+      firstBatch,
+      hashAlgorithm,
+    );
     allSignatures.push(createResult.signature);
 
     // Append remaining batches
@@ -518,6 +755,7 @@ export class RemoteViewingSDK {
   private async createSinglePoolWithId(
     poolId: string,
     targetHashes: string[],
+    hashAlgorithm: HashAlgorithm,
   ): Promise<CreatePoolResult> {
     // Validate all hashes are 32 bytes hex strings
     for (const hash of targetHashes) {
@@ -535,6 +773,7 @@ export class RemoteViewingSDK {
       const instructionData = this.encodeCreatePoolInstruction(
         poolId,
         targetHashes,
+        hashAlgorithm,
       );
 
       // Build instruction
@@ -548,6 +787,12 @@ export class RemoteViewingSDK {
             isSigner: false,
             isWritable: false,
           },
+          {
+            pubkey: await this.getPoolStatsPDA(poolPDA),
+            isSigner: false,
+            isWritable: true,
+          },
+          await this.getConfigAccount(),
         ],
         data: instructionData,
       });
@@ -622,6 +867,12 @@ export class RemoteViewingSDK {
             isSigner: false,
             isWritable: false,
           },
+          {
+            pubkey: await this.getPoolStatsPDA(poolPDA),
+            isSigner: false,
+            isWritable: true,
+          },
+          await this.getConfigAccount(),
         ],
         data: instructionData,
       });
@@ -704,6 +955,7 @@ export class RemoteViewingSDK {
     poolId: string,
     sessionMediaHash: string,
     completedTargetIndices: number[] = [],
+    mediaHashAlgorithm: HashAlgorithm = HashAlgorithm.Sha256,
   ): Promise<SubmitSessionResult> {
     // Validate input parameters
     if (!sessionId || sessionId.trim() === '') {
@@ -739,9 +991,11 @@ export class RemoteViewingSDK {
         mediaHashBytes,
         this.programId, // Using program ID as target selector for now
         completedTargetIndices,
+        mediaHashAlgorithm,
       );
 
-      // Build instruction
+      // Build instruction. The payer is also the viewer the session counts
+      // towards, so it signs in both roles.
       const instruction = new TransactionInstruction({
         programId: this.programId,
         keys: [
@@ -754,6 +1008,13 @@ export class RemoteViewingSDK {
             isWritable: false,
           },
           { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
+          {
+            pubkey: await this.getViewerStatsPDA(this.payer.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          await this.getConfigAccount(),
         ],
         data: instructionData,
       });
@@ -798,7 +1059,6 @@ export class RemoteViewingSDK {
   async finalizeSession(
     sessionId: string,
     poolId: string,
-    completedTargetIndices: number[] = [],
   ): Promise<FinalizeSessionResult> {
     const sessionPDA = await this.getSessionPDA(sessionId);
     const poolPDA = await this.getPoolPDA(poolId);
@@ -807,18 +1067,20 @@ export class RemoteViewingSDK {
     console.log('Session PDA:', sessionPDA.toBase58());
 
     try {
-      // Get the session data to retrieve the submission slot
+      // Get the session data to retrieve its entropy slot and viewer
       const sessionData = await this.getSessionData(sessionId);
       if (!sessionData) {
         throw new Error(`Session ${sessionId} not found`);
       }
 
-      // Wait for at least 2 slots to pass (security requirement)
+      // The target is drawn from the hash of the entropy slot (submission
+      // slot plus the pool's minimum delay), which SlotHashes only holds
+      // once a later slot has been produced
       console.log(
         'Waiting for sufficient slots to pass before finalization...',
       );
       const currentSlot = await this.connection.getSlot();
-      const requiredSlot = sessionData.submissionSlot + 2;
+      const requiredSlot = sessionData.entropySlot + 1;
 
       if (currentSlot < requiredSlot) {
         const slotsToWait = requiredSlot - currentSlot;
@@ -840,30 +1102,10 @@ export class RemoteViewingSDK {
         }
       }
 
-      // Get the blockhash for the submission slot
-      const slotInfo = await this.connection.getBlock(
-        sessionData.submissionSlot,
-        {
-          maxSupportedTransactionVersion: 0,
-        },
-      );
-      if (!slotInfo) {
-        throw new Error(
-          `Could not fetch block for slot ${sessionData.submissionSlot}`,
-        );
-      }
-
-      // Keep blockhash as base58 string (same format as Solana Explorer)
-      const submissionBlockhashBase58 = slotInfo.blockhash;
-
       // Create instruction data
-      const instructionData = this.encodeFinalizeSessionInstruction(
-        sessionId,
-        submissionBlockhashBase58,
-        completedTargetIndices,
-      );
+      const instructionData = this.encodeFinalizeSessionInstruction(sessionId);
 
-      // Build instruction (removed SYSVAR_SLOT_HASHES_PUBKEY)
+      // Build instruction
       const instruction = new TransactionInstruction({
         programId: this.programId,
         keys: [
@@ -871,6 +1113,23 @@ export class RemoteViewingSDK {
           { pubkey: poolPDA, isSigner: false, isWritable: false },
           { pubkey: this.payer.publicKey, isSigner: true, isWritable: true },
           { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+          {
+            pubkey: SYSVAR_SLOT_HASHES_PUBKEY,
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: await this.getViewerStatsPDA(
+              new PublicKey(sessionData.viewer),
+            ),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: await this.getPoolStatsPDA(poolPDA),
+            isSigner: false,
+            isWritable: true,
+          },
         ],
         data: instructionData,
       });
@@ -898,7 +1157,7 @@ export class RemoteViewingSDK {
 
       return {
         transactionSignature: signature,
-        blockHash: submissionBlockhashBase58, // Return base58 format
+        blockHash: finalizedSessionData.submissionBlockhash, // Entropy slot hash, base58
         assignedTargetHash: finalizedSessionData.assignedTargetHash || '',
         assignedTargetIndex: finalizedSessionData.assignedTargetIndex,
         explorerUrl,
@@ -1103,26 +1362,54 @@ export class RemoteViewingSDK {
     );
   }
 
+  // Same seeds as `pool_address`, `session_address`, `pool_stats_address`,
+  // `viewer_stats_address` and `config_address` in the program
   private async getPoolPDA(poolId: string): Promise<PublicKey> {
-    // Hash the poolId to ensure it fits within the 32-byte seed limit
-    const poolIdHash = createHash('sha256').update(poolId).digest();
     const [pda] = await PublicKey.findProgramAddress(
-      [Buffer.from('target_pool'), poolIdHash],
+      [Buffer.from('target_pool'), RecordId.parse(poolId).seed()],
       this.programId,
     );
     return pda;
   }
 
   private async getSessionPDA(sessionId: string): Promise<PublicKey> {
-    // Hash the sessionId to ensure it fits within the 32-byte seed limit
-    const sessionIdHash = createHash('sha256').update(sessionId).digest();
     const [pda] = await PublicKey.findProgramAddress(
-      [Buffer.from('session'), sessionIdHash],
+      [Buffer.from('session'), RecordId.parse(sessionId).seed()],
+      this.programId,
+    );
+    return pda;
+  }
+
+  private async getPoolStatsPDA(poolPDA: PublicKey): Promise<PublicKey> {
+    const [pda] = await PublicKey.findProgramAddress(
+      [Buffer.from('pool_stats'), poolPDA.toBuffer()],
+      this.programId,
+    );
+    return pda;
+  }
+
+  private async getViewerStatsPDA(viewer: PublicKey): Promise<PublicKey> {
+    const [pda] = await PublicKey.findProgramAddress(
+      [Buffer.from('viewer_stats'), viewer.toBuffer()],
       this.programId,
     );
     return pda;
   }
 
+  // Program config, passed last to instructions that honour the freeze and
+  // pause switches
+  private async getConfigAccount(): Promise<{
+    pubkey: PublicKey;
+    isSigner: boolean;
+    isWritable: boolean;
+  }> {
+    const [pda] = await PublicKey.findProgramAddress(
+      [Buffer.from('config')],
+      this.programId,
+    );
+    return { pubkey: pda, isSigner: false, isWritable: false };
+  }
+
   private hashToIndex(blockHashBase58: string, targetCount: number): number {
     // Convert base58 string to bytes - this matches the contract's calculate_target_index function
     const hash = Buffer.from(bs58.decode(blockHashBase58));
@@ -1135,8 +1422,13 @@ export class RemoteViewingSDK {
   private encodeCreatePoolInstruction(
     poolId: string,
     targetHashes: string[],
+    hashAlgorithm: HashAlgorithm,
   ): Buffer {
-    const instruction = new CreateTargetPoolInstruction(poolId, targetHashes);
+    const instruction = new CreateTargetPoolInstruction(
+      poolId,
+      targetHashes,
+      hashAlgorithm,
+    );
     const data = borsh.serialize(CREATE_POOL_SCHEMA, instruction);
     // Prepend the enum variant discriminator (0 for CreateTargetPool)
     return Buffer.concat([
@@ -1151,6 +1443,7 @@ export class RemoteViewingSDK {
     sessionMediaHash: Uint8Array,
     targetSelectorProgram: PublicKey,
     completedTargetIndices: number[],
+    mediaHashAlgorithm: HashAlgorithm,
   ): Buffer {
     const instruction = new SubmitSessionInstruction(
       sessionId,
@@ -1158,6 +1451,7 @@ export class RemoteViewingSDK {
       sessionMediaHash,
       targetSelectorProgram,
       completedTargetIndices,
+      mediaHashAlgorithm,
     );
     const data = borsh.serialize(SUBMIT_SESSION_SCHEMA, instruction);
     // Prepend the enum variant discriminator (1 for SubmitSession)
@@ -1167,16 +1461,8 @@ export class RemoteViewingSDK {
     ]);
   }
 
-  private encodeFinalizeSessionInstruction(
-    sessionId: string,
-    submissionBlockhashBase58: string,
-    completedTargetIndices: number[],
-  ): Buffer {
-    const instruction = new FinalizeSessionInstruction(
-      sessionId,
-      submissionBlockhashBase58,
-      completedTargetIndices,
-    );
+  private encodeFinalizeSessionInstruction(sessionId: string): Buffer {
+    const instruction = new FinalizeSessionInstruction(sessionId);
     const data = borsh.serialize(FINALIZE_SESSION_SCHEMA, instruction);
     // Prepend the enum variant discriminator (2 for FinalizeSession)
     return Buffer.concat([
@@ -1240,6 +1526,11 @@ export class RemoteViewingSDK {
         finalizedAt: Number(sessionAccount.finalized_at),
         sessionPDA: sessionPDA.toBase58(),
         completedTargetIndices: sessionAccount.completed_target_indices,
+        layoutVersion: sessionAccount.layout_version,
+        viewer: new PublicKey(sessionAccount.viewer).toBase58(),
+        entropySlot: Number(sessionAccount.entropy_slot),
+        lateFinalized: sessionAccount.late_finalized,
+        candidates: sessionAccount.candidates,
       };
     } catch (error) {
       console.error('Error getting session data:', error);
@@ -1268,6 +1559,9 @@ export class RemoteViewingSDK {
         createdAt: Number(poolAccount.created_at),
         poolPDA: poolPDA.toBase58(),
         finalized: poolAccount.finalized,
+        layoutVersion: poolAccount.layout_version,
+        hashAlgorithm: poolAccount.hash_algorithm,
+        kind: poolAccount.kind,
       };
     } catch (error) {
       console.error('Error getting pool data:', error);
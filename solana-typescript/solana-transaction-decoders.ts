@@ -27,6 +27,14 @@ function readUInt16LE(buffer: Uint8Array, offset: number): number {
   return buffer[offset] | (buffer[offset + 1] << 8);
 }
 
+// Read a u64/i64 as a number; exact for values below 2^53, which covers
+// slots and unix times
+function readUInt64LE(buffer: Uint8Array, offset: number): number {
+  return (
+    readUInt32LE(buffer, offset) + readUInt32LE(buffer, offset + 4) * 0x100000000
+  );
+}
+
 function uint8ArrayToHex(buffer: Uint8Array): string {
   return Array.from(buffer)
     .map(b => b.toString(16).padStart(2, '0'))
@@ -37,24 +45,67 @@ function uint8ArrayToUtf8(buffer: Uint8Array): string {
   return new TextDecoder().decode(buffer);
 }
 
-// Instruction decoder functions
-export function decodeCreateTargetPoolInstruction(hex: string): {
+// Read a pool or session id (Rust RecordId: tag 0 then a string, or tag 1
+// then 32 fixed bytes), returning it with the offset past it. Fixed ids are
+// given as `hex:<64 hex digits>`, the form rv-cli and the SDK accept.
+function readRecordId(buffer: Uint8Array, offset: number): [string, number] {
+  const tag = readUInt8(buffer, offset);
+  offset += 1;
+  if (tag === 0) {
+    const length = readUInt32LE(buffer, offset);
+    offset += 4;
+    const id = uint8ArrayToUtf8(buffer.subarray(offset, offset + length));
+    return [id, offset + length];
+  }
+  if (tag === 1) {
+    const id = `hex:${uint8ArrayToHex(buffer.subarray(offset, offset + 32))}`;
+    return [id, offset + 32];
+  }
+  throw new Error(`invalid id tag ${tag}`);
+}
+
+// Instruction decoder functions, following the argument order of the
+// program's instructions (solana-program/idl/remote_viewing_verifier.json)
+type CreateTargetPoolData = {
   instruction: 0;
   pool_id: string;
   target_hashes: string[];
-} {
+  hash_algorithm: number;
+  min_finalization_delay: number;
+  max_finalization_delay: number;
+};
+
+type SubmitSessionData = {
+  instruction: 1;
+  session_id: string;
+  pool_id: string;
+  session_media_hash: string;
+  target_selector_program: string;
+  completed_target_indices: number[];
+  media_hash_algorithm: number;
+  media_item_count: number;
+  finalization_reward: number;
+  tasking_commitment: string;
+  experiment: string;
+  decoy_count: number;
+};
+
+type FinalizeSessionData = {
+  instruction: 2;
+  session_id: string;
+};
+
+export function decodeCreateTargetPoolInstruction(
+  hex: string,
+): CreateTargetPoolData {
   try {
     const buffer = hexToUint8Array(hex);
     let offset = 0;
     const instruction = readUInt8(buffer, offset) as 0;
     offset += 1;
-    // Pool ID (Rust string: 4-byte length prefix, then utf8 bytes)
-    const poolIdLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const pool_id = uint8ArrayToUtf8(
-      buffer.subarray(offset, offset + poolIdLen),
-    );
-    offset += poolIdLen;
+    // Pool ID (RecordId)
+    let pool_id: string;
+    [pool_id, offset] = readRecordId(buffer, offset);
     // Number of hashes (Rust u32)
     const numHashes = readUInt32LE(buffer, offset);
     offset += 4;
@@ -63,7 +114,20 @@ export function decodeCreateTargetPoolInstruction(hex: string): {
       target_hashes.push(uint8ArrayToHex(buffer.subarray(offset, offset + 32)));
       offset += 32;
     }
-    return { instruction, pool_id, target_hashes };
+    // Hash algorithm (enum - 1 byte)
+    const hash_algorithm = readUInt8(buffer, offset);
+    offset += 1;
+    // Min/max finalization delay (u64 - 8 bytes each)
+    const min_finalization_delay = readUInt64LE(buffer, offset);
+    const max_finalization_delay = readUInt64LE(buffer, offset + 8);
+    return {
+      instruction,
+      pool_id,
+      target_hashes,
+      hash_algorithm,
+      min_finalization_delay,
+      max_finalization_delay,
+    };
   } catch (err: unknown) {
     if (err instanceof Error) {
       throw new Error('Failed to decode CreateTargetPool: ' + err.message);
@@ -72,33 +136,17 @@ export function decodeCreateTargetPoolInstruction(hex: string): {
   }
 }
 
-export function decodeSubmitSessionInstruction(hex: string): {
-  instruction: 1;
-  session_id: string;
-  pool_id: string;
-  session_media_hash: string;
-  target_selector_program: string;
-  completed_target_indices: number[];
-} {
+export function decodeSubmitSessionInstruction(hex: string): SubmitSessionData {
   try {
     const buffer = hexToUint8Array(hex);
     let offset = 0;
     const instruction = readUInt8(buffer, offset) as 1;
     offset += 1;
-    // Session ID (Rust string: 4-byte length prefix, then utf8 bytes)
-    const sessionIdLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const session_id = uint8ArrayToUtf8(
-      buffer.subarray(offset, offset + sessionIdLen),
-    );
-    offset += sessionIdLen;
-    // Pool ID (Rust string: 4-byte length prefix, then utf8 bytes)
-    const poolIdLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const pool_id = uint8ArrayToUtf8(
-      buffer.subarray(offset, offset + poolIdLen),
-    );
-    offset += poolIdLen;
+    // Session and pool IDs (RecordId)
+    let session_id: string;
+    let pool_id: string;
+    [session_id, offset] = readRecordId(buffer, offset);
+    [pool_id, offset] = readRecordId(buffer, offset);
     // Session media hash (32 bytes)
     const session_media_hash = uint8ArrayToHex(
       buffer.subarray(offset, offset + 32),
@@ -117,6 +165,23 @@ export function decodeSubmitSessionInstruction(hex: string): {
       completed_target_indices.push(readUInt16LE(buffer, offset));
       offset += 2;
     }
+    // Media hash algorithm (enum - 1 byte) and item count (u16)
+    const media_hash_algorithm = readUInt8(buffer, offset);
+    offset += 1;
+    const media_item_count = readUInt16LE(buffer, offset);
+    offset += 2;
+    // Finalization reward in lamports (u64)
+    const finalization_reward = readUInt64LE(buffer, offset);
+    offset += 8;
+    // Tasking commitment and experiment (32 bytes each, zero when unused)
+    const tasking_commitment = uint8ArrayToHex(
+      buffer.subarray(offset, offset + 32),
+    );
+    offset += 32;
+    const experiment = uint8ArrayToHex(buffer.subarray(offset, offset + 32));
+    offset += 32;
+    // Decoy count (u8)
+    const decoy_count = readUInt8(buffer, offset);
     return {
       instruction,
       session_id,
@@ -124,6 +189,12 @@ export function decodeSubmitSessionInstruction(hex: string): {
       session_media_hash,
       target_selector_program,
       completed_target_indices,
+      media_hash_algorithm,
+      media_item_count,
+      finalization_reward,
+      tasking_commitment,
+      experiment,
+      decoy_count,
     };
   } catch (err: unknown) {
     if (err instanceof Error) {
@@ -133,45 +204,20 @@ export function decodeSubmitSessionInstruction(hex: string): {
   }
 }
 
-export function decodeFinalizeSessionInstruction(hex: string): {
-  instruction: 2;
-  session_id: string;
-  submission_blockhash: string;
-  completed_target_indices: number[];
-} {
+// The program draws the target from SlotHashes, so the instruction carries
+// only the session ID
+export function decodeFinalizeSessionInstruction(
+  hex: string,
+): FinalizeSessionData {
   try {
     const buffer = hexToUint8Array(hex);
     let offset = 0;
     const instruction = readUInt8(buffer, offset) as 2;
     offset += 1;
-    // Session ID (Rust string: 4-byte length prefix, then utf8 bytes)
-    const sessionIdLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const session_id = uint8ArrayToUtf8(
-      buffer.subarray(offset, offset + sessionIdLen),
-    );
-    offset += sessionIdLen;
-    // Submission blockhash (now a base58 string: 4-byte length prefix, then utf8 bytes)
-    const blockhashLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const submission_blockhash = uint8ArrayToUtf8(
-      buffer.subarray(offset, offset + blockhashLen),
-    );
-    offset += blockhashLen;
-    // Completed target indices (Vec<u16>: 4-byte length prefix, then u16 elements)
-    const completedIndicesLen = readUInt32LE(buffer, offset);
-    offset += 4;
-    const completed_target_indices: number[] = [];
-    for (let i = 0; i < completedIndicesLen; i++) {
-      completed_target_indices.push(readUInt16LE(buffer, offset));
-      offset += 2;
-    }
-    return {
-      instruction,
-      session_id,
-      submission_blockhash,
-      completed_target_indices,
-    };
+    // Session ID (RecordId)
+    let session_id: string;
+    [session_id, offset] = readRecordId(buffer, offset);
+    return { instruction, session_id };
   } catch (err: unknown) {
     if (err instanceof Error) {
       throw new Error('Failed to decode FinalizeSession: ' + err.message);
@@ -180,16 +226,30 @@ export function decodeFinalizeSessionInstruction(hex: string): {
   }
 }
 
-// Pool account decoder function
-export function decodePoolAccount(base64Data: string): {
+// Pool accounts start with this discriminator and a layout version byte.
+// Pools from the first deployment have neither (they start with the u32
+// length of their text id) and are decoded with layout version 0.
+const POOL_DISCRIMINATOR = [0x72, 0x76, 0x2d, 0x70, 0x6f, 0x6f, 0x6c, 0x00]; // "rv-pool\0"
+const ACCOUNT_LAYOUT_VERSION = 1;
+
+type PoolAccount = {
   type: 'poolAccount';
+  layout_version: number;
   pool_id: string;
   creator: string;
   target_count: number;
   targets: string[];
   created_at: number;
   finalized: boolean;
-} {
+  hash_algorithm: number;
+  min_finalization_delay: number;
+  max_finalization_delay: number;
+  kind: number;
+  outcome_labels: string[];
+};
+
+// Pool account decoder function
+export function decodePoolAccount(base64Data: string): PoolAccount {
   try {
     // Decode base64 to buffer (browser-compatible)
     const binaryString = atob(base64Data);
@@ -199,11 +259,31 @@ export function decodePoolAccount(base64Data: string): {
     }
     let offset = 0;
 
-    // Read pool_id (string: 4-byte length + utf8 bytes)
-    const poolIdLen = readUInt32LE(data, offset);
-    offset += 4;
-    const pool_id = uint8ArrayToUtf8(data.subarray(offset, offset + poolIdLen));
-    offset += poolIdLen;
+    const readString = (): string => {
+      const length = readUInt32LE(data, offset);
+      offset += 4;
+      const value = uint8ArrayToUtf8(data.subarray(offset, offset + length));
+      offset += length;
+      return value;
+    };
+
+    // Read the discriminator and layout version, if present
+    const legacy =
+      data.length < 9 || POOL_DISCRIMINATOR.some((byte, i) => data[i] !== byte);
+    let layout_version = 0;
+    let pool_id: string;
+    if (legacy) {
+      // Read pool_id (string: 4-byte length + utf8 bytes)
+      pool_id = readString();
+    } else {
+      layout_version = data[8];
+      offset += 9;
+      if (layout_version !== ACCOUNT_LAYOUT_VERSION) {
+        throw new Error(`unsupported layout version ${layout_version}`);
+      }
+      // Read pool_id (RecordId)
+      [pool_id, offset] = readRecordId(data, offset);
+    }
 
     // Read creator (32 bytes)
     const creator = data.subarray(offset, offset + 32);
@@ -224,24 +304,55 @@ export function decodePoolAccount(base64Data: string): {
     }
 
     // Read created_at (i64 - 8 bytes)
-    // Note: JavaScript doesn't have native i64, so we'll read as two 32-bit values
-    const createdAtLow = readUInt32LE(data, offset);
-    const createdAtHigh = readUInt32LE(data, offset + 4);
-    const created_at = createdAtLow + createdAtHigh * 0x100000000;
+    const created_at = readUInt64LE(data, offset);
     offset += 8;
 
     // Read finalized (bool - 1 byte)
     const finalized = data[offset] !== 0;
+    offset += 1;
 
-    return {
+    const pool: PoolAccount = {
       type: 'poolAccount' as const,
+      layout_version,
       pool_id,
       creator: uint8ArrayToHex(creator),
       target_count,
       targets,
       created_at,
       finalized,
+      // Legacy pools are SHA-256, standard, with the program's default
+      // delays (MIN_FINALIZATION_DELAY_SLOTS and MAX_FINALIZATION_DELAY_SLOTS)
+      hash_algorithm: 0,
+      min_finalization_delay: 2,
+      max_finalization_delay: 511,
+      kind: 0,
+      outcome_labels: [],
     };
+    if (legacy) {
+      return pool;
+    }
+
+    // Read hash_algorithm (enum - 1 byte)
+    pool.hash_algorithm = readUInt8(data, offset);
+    offset += 1;
+
+    // Read min/max_finalization_delay (u64 - 8 bytes each)
+    pool.min_finalization_delay = readUInt64LE(data, offset);
+    pool.max_finalization_delay = readUInt64LE(data, offset + 8);
+    offset += 16;
+
+    // Read kind (enum - 1 byte)
+    pool.kind = readUInt8(data, offset);
+    offset += 1;
+
+    // Read outcome_labels (Vec<String>); resolver and resolution follow
+    const labelsLen = readUInt32LE(data, offset);
+    offset += 4;
+    for (let i = 0; i < labelsLen; i++) {
+      pool.outcome_labels.push(readString());
+    }
+
+    return pool;
   } catch (error) {
     throw new Error(`Failed to decode pool account: ${error}`);
   }
@@ -256,30 +367,10 @@ function isLikelyBase64(str: string): boolean {
 
 // Auto-detect data type and decode (hex instruction or base64 pool account)
 export function decodeInstruction(input: string):
-  | { instruction: 0; pool_id: string; target_hashes: string[] }
-  | {
-      instruction: 1;
-      session_id: string;
-      pool_id: string;
-      session_media_hash: string;
-      target_selector_program: string;
-      completed_target_indices: number[];
-    }
-  | {
-      instruction: 2;
-      session_id: string;
-      submission_blockhash: string;
-      completed_target_indices: number[];
-    }
-  | {
-      type: 'poolAccount';
-      pool_id: string;
-      creator: string;
-      target_count: number;
-      targets: string[];
-      created_at: number;
-      finalized: boolean;
-    } {
+  | CreateTargetPoolData
+  | SubmitSessionData
+  | FinalizeSessionData
+  | PoolAccount {
   // First, try to detect if this looks like base64 pool account data
   if (isLikelyBase64(input)) {
     try {
@@ -60,6 +60,11 @@ export interface SessionData {
   finalizedAt: number;
   sessionPDA: string;
   completedTargetIndices: number[];
+  layoutVersion: number; // 0 for a session from the first deployment
+  viewer: string; // The submitter for legacy sessions
+  entropySlot: number; // Slot whose hash drove the assignment, 0 for legacy sessions
+  lateFinalized: boolean;
+  candidates: number[]; // Target and decoys for judging, empty without decoys
 }
 
 export interface PoolData {
@@ -70,6 +75,9 @@ export interface PoolData {
   createdAt: number;
   poolPDA: string;
   finalized: boolean;
+  layoutVersion: number; // 0 for a pool from the first deployment
+  hashAlgorithm: number; // HashAlgorithm variant index, 0 (SHA-256) for legacy pools
+  kind: number; // SessionKind variant index: 0 standard, 1 associative
 }
 
 export interface RemoteViewingConfig {